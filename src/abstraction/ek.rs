@@ -0,0 +1,188 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for retrieving the Endorsement Key certificates
+//!
+//! TPM manufacturers provision the certificates of the Endorsement Keys in NV indices defined by
+//! the TCG EK Credential Profile. This module reads those indices and returns the DER-encoded
+//! certificates they contain.
+//!
+//! The indices are read using the sessions set on the `Context`; if no session is set, a password
+//! session is used for the duration of the call.
+use crate::response_code::{Error, Result, WrapperErrorKind as ErrorKind};
+use crate::tss2_esys::{ESYS_TR, ESYS_TR_NONE, ESYS_TR_PASSWORD, TPM2_HANDLE};
+use crate::utils::algorithm_specifiers::AsymmetricAlgorithm;
+use crate::Context;
+use log::error;
+
+/// NV index of the RSA 2048 EK certificate (low range).
+pub const RSA_2048_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_0002;
+/// NV index of the ECC NIST P256 EK certificate (low range).
+pub const ECC_P256_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_000a;
+/// NV index of the RSA 2048 EK certificate (high range).
+pub const RSA_2048_EK_CERTIFICATE_HIGH_NV_INDEX: TPM2_HANDLE = 0x01c0_0012;
+/// NV index of the ECC NIST P256 EK certificate (high range).
+pub const ECC_P256_EK_CERTIFICATE_HIGH_NV_INDEX: TPM2_HANDLE = 0x01c0_0014;
+/// NV index of the ECC NIST P384 EK certificate (high range).
+pub const ECC_P384_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_0016;
+/// NV index of the ECC NIST P521 EK certificate (high range).
+pub const ECC_P521_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_0018;
+/// NV index of the ECC SM2 P256 EK certificate (high range).
+pub const ECC_SM2_P256_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_001a;
+/// NV index of the RSA 3072 EK certificate (high range).
+pub const RSA_3072_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_001c;
+/// NV index of the RSA 4096 EK certificate (high range).
+pub const RSA_4096_EK_CERTIFICATE_NV_INDEX: TPM2_HANDLE = 0x01c0_001e;
+/// First NV index of the EK certificate chain.
+pub const EK_CERTIFICATE_CHAIN_NV_INDEX_FIRST: TPM2_HANDLE = 0x01c0_0100;
+/// Last NV index of the EK certificate chain.
+pub const EK_CERTIFICATE_CHAIN_NV_INDEX_LAST: TPM2_HANDLE = 0x01c0_01ff;
+
+// All indices which can hold an EK certificate, in the order in which they are enumerated.
+const EK_CERTIFICATE_NV_INDICES: [TPM2_HANDLE; 9] = [
+    RSA_2048_EK_CERTIFICATE_NV_INDEX,
+    ECC_P256_EK_CERTIFICATE_NV_INDEX,
+    RSA_2048_EK_CERTIFICATE_HIGH_NV_INDEX,
+    ECC_P256_EK_CERTIFICATE_HIGH_NV_INDEX,
+    ECC_P384_EK_CERTIFICATE_NV_INDEX,
+    ECC_P521_EK_CERTIFICATE_NV_INDEX,
+    ECC_SM2_P256_EK_CERTIFICATE_NV_INDEX,
+    RSA_3072_EK_CERTIFICATE_NV_INDEX,
+    RSA_4096_EK_CERTIFICATE_NV_INDEX,
+];
+
+/// Retrieve the DER-encoded EK certificate for the given algorithm.
+///
+/// For RSA, the RSA 2048 certificate is returned; for ECC, the NIST P256 one. The low range index
+/// is tried first, then the high range one.
+///
+/// # Errors
+/// * errors returned while reading the NV index are forwarded; if neither index is defined, the
+/// error obtained for the high range one is returned
+/// * if the content of the index is not a DER-encoded structure, an `InvalidParam` wrapper error
+/// is returned
+pub fn retrieve_ek_certificate(
+    context: &mut Context,
+    algorithm: AsymmetricAlgorithm,
+) -> Result<Vec<u8>> {
+    let (low, high) = match algorithm {
+        AsymmetricAlgorithm::Rsa => (
+            RSA_2048_EK_CERTIFICATE_NV_INDEX,
+            RSA_2048_EK_CERTIFICATE_HIGH_NV_INDEX,
+        ),
+        AsymmetricAlgorithm::Ecc => (
+            ECC_P256_EK_CERTIFICATE_NV_INDEX,
+            ECC_P256_EK_CERTIFICATE_HIGH_NV_INDEX,
+        ),
+    };
+    read_certificate(context, low).or_else(|_| read_certificate(context, high))
+}
+
+/// Retrieve all EK certificates found in the TPM, along with the NV index holding each of them.
+///
+/// The certificate chain is not included; use `retrieve_ek_certificate_chain` for it.
+pub fn list_ek_certificates(context: &mut Context) -> Result<Vec<(TPM2_HANDLE, Vec<u8>)>> {
+    let mut certificates = Vec::new();
+    for nv_index in EK_CERTIFICATE_NV_INDICES.iter() {
+        if let Ok(certificate) = read_certificate(context, *nv_index) {
+            certificates.push((*nv_index, certificate));
+        }
+    }
+    Ok(certificates)
+}
+
+/// Retrieve the certificates of the EK certificate chain, if provisioned.
+///
+/// The chain is stored in consecutive NV indices starting at
+/// `EK_CERTIFICATE_CHAIN_NV_INDEX_FIRST`, each holding one or more concatenated DER certificates.
+/// The certificates are returned in the order in which they are stored; an empty vector is
+/// returned if no chain is provisioned.
+///
+/// # Errors
+/// * if the content of an index is not a sequence of DER-encoded structures, an `InvalidParam`
+/// wrapper error is returned
+pub fn retrieve_ek_certificate_chain(context: &mut Context) -> Result<Vec<Vec<u8>>> {
+    let mut certificates = Vec::new();
+    for nv_index in EK_CERTIFICATE_CHAIN_NV_INDEX_FIRST..=EK_CERTIFICATE_CHAIN_NV_INDEX_LAST {
+        let data = match read_nv_index(context, nv_index) {
+            Ok(data) => data,
+            Err(_) => break,
+        };
+        let mut remaining = &data[..];
+        while !remaining.is_empty() && !is_padding(remaining) {
+            let len = der_length(remaining)?;
+            certificates.push(remaining[..len].to_vec());
+            remaining = &remaining[len..];
+        }
+    }
+    Ok(certificates)
+}
+
+/// Read the DER-encoded certificate stored in an NV index.
+///
+/// Any padding after the end of the DER structure is removed.
+///
+/// # Errors
+/// * errors returned while reading the NV index are forwarded
+/// * if the content of the index is not a DER-encoded structure, an `InvalidParam` wrapper error
+/// is returned
+pub fn read_certificate(context: &mut Context, nv_index: TPM2_HANDLE) -> Result<Vec<u8>> {
+    let mut data = read_nv_index(context, nv_index)?;
+    let len = der_length(&data)?;
+    data.truncate(len);
+    Ok(data)
+}
+
+// Read the whole content of an NV index, authorizing with the index itself.
+fn read_nv_index(context: &mut Context, nv_index: TPM2_HANDLE) -> Result<Vec<u8>> {
+    let sessions = context.sessions();
+    if sessions.0 == ESYS_TR_NONE {
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+    }
+    let result = context.tr_from_tpm_public(nv_index).and_then(|nv_handle| {
+        let data = read_nv_handle(context, nv_handle);
+        let closed = context.tr_close(nv_handle);
+        data.and_then(|data| closed.map(|_| data))
+    });
+    context.set_sessions(sessions);
+    result
+}
+
+fn read_nv_handle(context: &mut Context, nv_handle: ESYS_TR) -> Result<Vec<u8>> {
+    let size = context.nv_read_public(nv_handle)?.data_size();
+    context.nv_read(nv_handle, nv_handle, size, 0)
+}
+
+// Some manufacturers pad the indices with 0x00 or 0xFF bytes.
+fn is_padding(data: &[u8]) -> bool {
+    data.iter().all(|byte| *byte == 0x00) || data.iter().all(|byte| *byte == 0xff)
+}
+
+// Compute the total length (header included) of the DER structure at the start of `data`.
+fn der_length(data: &[u8]) -> Result<usize> {
+    let invalid = || {
+        error!("Error: NV index does not contain a valid DER structure.");
+        Error::local_error(ErrorKind::InvalidParam)
+    };
+    // A certificate is a SEQUENCE
+    if data.len() < 2 || data[0] != 0x30 {
+        return Err(invalid());
+    }
+    let (header_len, content_len) = if data[1] < 0x80 {
+        (2, usize::from(data[1]))
+    } else {
+        let num_bytes = usize::from(data[1] & 0x7f);
+        if num_bytes == 0 || num_bytes > 4 || data.len() < 2 + num_bytes {
+            return Err(invalid());
+        }
+        let content_len = data[2..2 + num_bytes]
+            .iter()
+            .fold(0_usize, |len, byte| (len << 8) | usize::from(*byte));
+        (2 + num_bytes, content_len)
+    };
+    let len = header_len + content_len;
+    if len > data.len() {
+        return Err(invalid());
+    }
+    Ok(len)
+}
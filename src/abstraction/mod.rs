@@ -1,4 +1,7 @@
 // Copyright 2019 Contributors to the Parsec project.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod ek;
+pub mod nv;
+pub mod tpm_info;
 pub mod transient;
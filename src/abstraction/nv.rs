@@ -0,0 +1,312 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for typed access to special NV indices
+//!
+//! The TPM supports, besides ordinary NV storage, indices whose content can only be modified
+//! through dedicated commands: counters (TPM2_NV_Increment), bit fields (TPM2_NV_SetBits) and
+//! extend indices (TPM2_NV_Extend). The wrappers in this module check the type of the index when
+//! they are created and then only expose the operations valid for it, decoding the content of the
+//! index into an appropriate Rust type.
+//!
+//! The wrappers only hold the ESYS handles of the index and of the entity providing the
+//! authorization; every operation takes the `Context` through which it is executed. The sessions
+//! set on the context are used for authorization.
+use crate::response_code::{Error, Result, WrapperErrorKind as ErrorKind};
+use crate::tss2_esys::ESYS_TR;
+use crate::utils::nv::{NvIndexType, NvPublic};
+use crate::Context;
+use enumflags2::{BitFlags, RawBitFlags};
+use log::error;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+// Size in bytes of the content of counter and bit field indices.
+const NV_U64_SIZE: u16 = 8;
+
+fn check_index_type(nv_public: &NvPublic, expected: NvIndexType) -> Result<()> {
+    let index_type = nv_public.attributes().index_type()?;
+    if index_type != expected {
+        error!(
+            "Error: NV index 0x{:08x} has type {:?}, expected {:?}.",
+            nv_public.nv_index(),
+            index_type,
+            expected
+        );
+        return Err(Error::local_error(ErrorKind::InconsistentParams));
+    }
+    Ok(())
+}
+
+fn define_typed(
+    context: &mut Context,
+    hierarchy_auth_handle: ESYS_TR,
+    auth: &[u8],
+    nv_public: &NvPublic,
+    expected: NvIndexType,
+) -> Result<ESYS_TR> {
+    check_index_type(nv_public, expected)?;
+    context.nv_define_space(hierarchy_auth_handle, auth, nv_public)
+}
+
+fn open_typed(
+    context: &mut Context,
+    nv_index_handle: ESYS_TR,
+    expected: NvIndexType,
+) -> Result<NvPublic> {
+    let nv_public = context.nv_read_public(nv_index_handle)?;
+    check_index_type(&nv_public, expected)?;
+    Ok(nv_public)
+}
+
+fn read_u64(context: &mut Context, auth_handle: ESYS_TR, nv_index_handle: ESYS_TR) -> Result<u64> {
+    let data = context.nv_read(auth_handle, nv_index_handle, NV_U64_SIZE, 0)?;
+    let bytes: [u8; 8] = data[..]
+        .try_into()
+        .map_err(|_| Error::local_error(ErrorKind::WrongParamSize))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// NV index of type counter.
+///
+/// The value of the counter is a 64 bit integer which can only be incremented.
+#[derive(Debug, Clone, Copy)]
+pub struct NvCounter {
+    auth_handle: ESYS_TR,
+    nv_index_handle: ESYS_TR,
+}
+
+impl NvCounter {
+    /// Define a new counter index and return a wrapper for it.
+    ///
+    /// `auth_handle` is used to authorize the subsequent operations on the counter; it can be the
+    /// handle of the index itself (pass `None`) or that of a hierarchy.
+    ///
+    /// # Errors
+    /// * if the type in the attributes of `nv_public` is not `NvIndexType::Counter`, an
+    /// `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_define_space` are forwarded
+    pub fn define(
+        context: &mut Context,
+        hierarchy_auth_handle: ESYS_TR,
+        auth: &[u8],
+        nv_public: &NvPublic,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        let nv_index_handle = define_typed(
+            context,
+            hierarchy_auth_handle,
+            auth,
+            nv_public,
+            NvIndexType::Counter,
+        )?;
+        Ok(NvCounter {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+        })
+    }
+
+    /// Create a wrapper for an existing counter index.
+    ///
+    /// # Errors
+    /// * if the index is not a counter, an `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_read_public` are forwarded
+    pub fn open(
+        context: &mut Context,
+        nv_index_handle: ESYS_TR,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        let _ = open_typed(context, nv_index_handle, NvIndexType::Counter)?;
+        Ok(NvCounter {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+        })
+    }
+
+    /// Get the ESYS handle of the index.
+    pub fn nv_index_handle(&self) -> ESYS_TR {
+        self.nv_index_handle
+    }
+
+    /// Increment the counter by one.
+    pub fn increment(&self, context: &mut Context) -> Result<()> {
+        context.nv_increment(self.auth_handle, self.nv_index_handle)
+    }
+
+    /// Read the current value of the counter.
+    ///
+    /// The counter must have been incremented at least once before it can be read.
+    pub fn read(&self, context: &mut Context) -> Result<u64> {
+        read_u64(context, self.auth_handle, self.nv_index_handle)
+    }
+}
+
+/// NV index of type bit field.
+///
+/// The content of the index is a 64 bit value whose bits can only be set, never cleared. The bits
+/// are represented as a set of flags of type `T`, an enum deriving `enumflags2::BitFlags` with
+/// `u64` as its representation.
+#[derive(Debug, Clone, Copy)]
+pub struct NvBits<T: RawBitFlags<Type = u64>> {
+    auth_handle: ESYS_TR,
+    nv_index_handle: ESYS_TR,
+    flags: PhantomData<T>,
+}
+
+impl<T: RawBitFlags<Type = u64>> NvBits<T> {
+    /// Define a new bit field index and return a wrapper for it.
+    ///
+    /// `auth_handle` is used to authorize the subsequent operations on the index; it can be the
+    /// handle of the index itself (pass `None`) or that of a hierarchy.
+    ///
+    /// # Errors
+    /// * if the type in the attributes of `nv_public` is not `NvIndexType::Bits`, an
+    /// `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_define_space` are forwarded
+    pub fn define(
+        context: &mut Context,
+        hierarchy_auth_handle: ESYS_TR,
+        auth: &[u8],
+        nv_public: &NvPublic,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        let nv_index_handle = define_typed(
+            context,
+            hierarchy_auth_handle,
+            auth,
+            nv_public,
+            NvIndexType::Bits,
+        )?;
+        Ok(NvBits {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+            flags: PhantomData,
+        })
+    }
+
+    /// Create a wrapper for an existing bit field index.
+    ///
+    /// # Errors
+    /// * if the index is not a bit field, an `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_read_public` are forwarded
+    pub fn open(
+        context: &mut Context,
+        nv_index_handle: ESYS_TR,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        let _ = open_typed(context, nv_index_handle, NvIndexType::Bits)?;
+        Ok(NvBits {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+            flags: PhantomData,
+        })
+    }
+
+    /// Get the ESYS handle of the index.
+    pub fn nv_index_handle(&self) -> ESYS_TR {
+        self.nv_index_handle
+    }
+
+    /// Set the given bits in the index. Bits already set are left unchanged.
+    pub fn set_bits(&self, context: &mut Context, bits: BitFlags<T>) -> Result<()> {
+        context.nv_set_bits(self.auth_handle, self.nv_index_handle, bits.bits())
+    }
+
+    /// Read the bits currently set in the index.
+    ///
+    /// Bits which do not correspond to a flag of `T` are ignored; use `read_raw` to access them.
+    pub fn read(&self, context: &mut Context) -> Result<BitFlags<T>> {
+        Ok(BitFlags::from_bits_truncate(self.read_raw(context)?))
+    }
+
+    /// Read the raw 64 bit value of the index.
+    pub fn read_raw(&self, context: &mut Context) -> Result<u64> {
+        read_u64(context, self.auth_handle, self.nv_index_handle)
+    }
+}
+
+/// NV index of type extend.
+///
+/// The content of the index is a digest computed with the name algorithm of the index, which can
+/// only be modified by extending it, in the same way as a PCR.
+#[derive(Debug, Clone, Copy)]
+pub struct NvExtend {
+    auth_handle: ESYS_TR,
+    nv_index_handle: ESYS_TR,
+    digest_size: u16,
+}
+
+impl NvExtend {
+    /// Define a new extend index and return a wrapper for it.
+    ///
+    /// The data size of `nv_public` must match the digest size of its name algorithm.
+    /// `auth_handle` is used to authorize the subsequent operations on the index; it can be the
+    /// handle of the index itself (pass `None`) or that of a hierarchy.
+    ///
+    /// # Errors
+    /// * if the data size of `nv_public` is not the digest size of its name algorithm, a
+    /// `WrongParamSize` wrapper error is returned
+    /// * if the type in the attributes of `nv_public` is not `NvIndexType::Extend`, an
+    /// `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_define_space` are forwarded
+    pub fn define(
+        context: &mut Context,
+        hierarchy_auth_handle: ESYS_TR,
+        auth: &[u8],
+        nv_public: &NvPublic,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        if usize::from(nv_public.data_size()) != nv_public.name_alg().digest_size() {
+            error!(
+                "Error: the data size of an extend index must be the digest size of {:?}.",
+                nv_public.name_alg()
+            );
+            return Err(Error::local_error(ErrorKind::WrongParamSize));
+        }
+        let nv_index_handle = define_typed(
+            context,
+            hierarchy_auth_handle,
+            auth,
+            nv_public,
+            NvIndexType::Extend,
+        )?;
+        Ok(NvExtend {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+            digest_size: nv_public.data_size(),
+        })
+    }
+
+    /// Create a wrapper for an existing extend index.
+    ///
+    /// # Errors
+    /// * if the index is not an extend index, an `InconsistentParams` wrapper error is returned
+    /// * errors returned by `Context::nv_read_public` are forwarded
+    pub fn open(
+        context: &mut Context,
+        nv_index_handle: ESYS_TR,
+        auth_handle: Option<ESYS_TR>,
+    ) -> Result<Self> {
+        let nv_public = open_typed(context, nv_index_handle, NvIndexType::Extend)?;
+        Ok(NvExtend {
+            auth_handle: auth_handle.unwrap_or(nv_index_handle),
+            nv_index_handle,
+            digest_size: nv_public.data_size(),
+        })
+    }
+
+    /// Get the ESYS handle of the index.
+    pub fn nv_index_handle(&self) -> ESYS_TR {
+        self.nv_index_handle
+    }
+
+    /// Extend the index with the given data.
+    pub fn extend(&self, context: &mut Context, data: &[u8]) -> Result<()> {
+        context.nv_extend(self.auth_handle, self.nv_index_handle, data)
+    }
+
+    /// Read the current digest stored in the index.
+    pub fn read(&self, context: &mut Context) -> Result<Vec<u8>> {
+        context.nv_read(self.auth_handle, self.nv_index_handle, self.digest_size, 0)
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for identifying a TPM
+//!
+//! `TpmInfo` gathers the fixed properties of a TPM (`TPM2_PT_FIXED` group) which identify it:
+//! its manufacturer, firmware version and the version of the specification it implements, along
+//! with a few of its limits. It can be serialized with `serde` to be reported as-is, e.g. in an
+//! inventory.
+use crate::constants::{
+    TPM2_PT_FAMILY_INDICATOR, TPM2_PT_FIRMWARE_VERSION_1, TPM2_PT_FIRMWARE_VERSION_2,
+    TPM2_PT_LEVEL, TPM2_PT_MANUFACTURER, TPM2_PT_MAX_DIGEST, TPM2_PT_MAX_OBJECT_CONTEXT,
+    TPM2_PT_MAX_SESSION_CONTEXT, TPM2_PT_NV_BUFFER_MAX, TPM2_PT_PCR_COUNT, TPM2_PT_REVISION,
+    TPM2_PT_VENDOR_STRING_1, TPM2_PT_VENDOR_STRING_2, TPM2_PT_VENDOR_STRING_3,
+    TPM2_PT_VENDOR_STRING_4,
+};
+use crate::response_code::{Error, Result, WrapperErrorKind as ErrorKind};
+use crate::tss2_esys::TPM2_PT;
+use crate::Context;
+use log::error;
+use serde::Serialize;
+
+// Manufacturers registered in the TCG TPM Vendor ID Registry, by vendor ID.
+const MANUFACTURERS: [(&str, &str); 24] = [
+    ("AMD", "AMD"),
+    ("ATML", "Atmel"),
+    ("BRCM", "Broadcom"),
+    ("CSCO", "Cisco"),
+    ("FLYS", "Flyslice Technologies"),
+    ("GOOG", "Google"),
+    ("HISI", "Huawei"),
+    ("HPE", "HPE"),
+    ("IBM", "IBM"),
+    ("IFX", "Infineon"),
+    ("INTC", "Intel"),
+    ("LEN", "Lenovo"),
+    ("MSFT", "Microsoft"),
+    ("NSM", "National Semiconductor"),
+    ("NTC", "Nuvoton Technology"),
+    ("NTZ", "Nationz"),
+    ("QCOM", "Qualcomm"),
+    ("ROCC", "Fuzhou Rockchip"),
+    ("SMSC", "SMSC"),
+    ("SMSN", "Samsung"),
+    ("SNS", "Sinosun"),
+    ("STM", "STMicroelectronics"),
+    ("TXN", "Texas Instruments"),
+    ("WEC", "Winbond"),
+];
+
+/// Identity of a TPM, as reported by its fixed properties.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TpmInfo {
+    /// Vendor ID of the manufacturer (`TPM2_PT_MANUFACTURER`), e.g. "IFX".
+    pub manufacturer_id: String,
+    /// Name of the manufacturer, if its vendor ID is registered by the TCG.
+    pub manufacturer_name: Option<&'static str>,
+    /// Vendor-defined string (`TPM2_PT_VENDOR_STRING_1` to `TPM2_PT_VENDOR_STRING_4`).
+    pub vendor_string: String,
+    /// Vendor-specific firmware version (`TPM2_PT_FIRMWARE_VERSION_1` and
+    /// `TPM2_PT_FIRMWARE_VERSION_2`).
+    pub firmware_version: u64,
+    /// Family of the specification implemented (`TPM2_PT_FAMILY_INDICATOR`), e.g. "2.0".
+    pub spec_family: String,
+    /// Level of the specification implemented (`TPM2_PT_LEVEL`).
+    pub spec_level: u32,
+    /// Revision of the specification implemented, times 100 (`TPM2_PT_REVISION`).
+    pub spec_revision: u32,
+    /// Maximum size of the data in a single NV read or write (`TPM2_PT_NV_BUFFER_MAX`).
+    pub max_nv_buffer_size: u32,
+    /// Maximum size of a digest produced by the TPM (`TPM2_PT_MAX_DIGEST`).
+    pub max_digest_size: u32,
+    /// Maximum size of an object context (`TPM2_PT_MAX_OBJECT_CONTEXT`).
+    pub max_object_context_size: u32,
+    /// Maximum size of a session context (`TPM2_PT_MAX_SESSION_CONTEXT`).
+    pub max_session_context_size: u32,
+    /// Number of PCRs implemented (`TPM2_PT_PCR_COUNT`).
+    pub pcr_count: u32,
+}
+
+impl TpmInfo {
+    /// Build the identity of a TPM from its fixed properties, as returned by
+    /// `Context::get_fixed_properties`.
+    ///
+    /// # Errors
+    /// * if any of the properties, apart from the vendor strings, is missing, a `ParamsMissing`
+    /// wrapper error is returned
+    pub fn from_properties(properties: &[(TPM2_PT, u32)]) -> Result<Self> {
+        let property = |tag: TPM2_PT| -> Result<u32> {
+            properties
+                .iter()
+                .find(|(property, _)| *property == tag)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| {
+                    error!("Error: TPM property {:#x} was not reported.", tag);
+                    Error::local_error(ErrorKind::ParamsMissing)
+                })
+        };
+
+        let manufacturer_id = property_string(&[property(TPM2_PT_MANUFACTURER)?]);
+        let manufacturer_name = MANUFACTURERS
+            .iter()
+            .find(|(id, _)| *id == manufacturer_id)
+            .map(|(_, name)| *name);
+        let vendor_string = property_string(
+            &[
+                TPM2_PT_VENDOR_STRING_1,
+                TPM2_PT_VENDOR_STRING_2,
+                TPM2_PT_VENDOR_STRING_3,
+                TPM2_PT_VENDOR_STRING_4,
+            ]
+            .iter()
+            .filter_map(|tag| property(*tag).ok())
+            .collect::<Vec<u32>>(),
+        );
+
+        Ok(TpmInfo {
+            manufacturer_id,
+            manufacturer_name,
+            vendor_string,
+            firmware_version: (u64::from(property(TPM2_PT_FIRMWARE_VERSION_1)?) << 32)
+                | u64::from(property(TPM2_PT_FIRMWARE_VERSION_2)?),
+            spec_family: property_string(&[property(TPM2_PT_FAMILY_INDICATOR)?]),
+            spec_level: property(TPM2_PT_LEVEL)?,
+            spec_revision: property(TPM2_PT_REVISION)?,
+            max_nv_buffer_size: property(TPM2_PT_NV_BUFFER_MAX)?,
+            max_digest_size: property(TPM2_PT_MAX_DIGEST)?,
+            max_object_context_size: property(TPM2_PT_MAX_OBJECT_CONTEXT)?,
+            max_session_context_size: property(TPM2_PT_MAX_SESSION_CONTEXT)?,
+            pcr_count: property(TPM2_PT_PCR_COUNT)?,
+        })
+    }
+}
+
+/// Read the identity of the TPM.
+///
+/// # Errors
+/// * errors returned while reading the fixed properties are forwarded
+/// * if any of the properties, apart from the vendor strings, is missing, a `ParamsMissing`
+/// wrapper error is returned
+pub fn get_tpm_info(context: &mut Context) -> Result<TpmInfo> {
+    TpmInfo::from_properties(&context.get_fixed_properties()?)
+}
+
+// Decode the 4-character strings held by properties, dropping the NUL and space padding.
+fn property_string(values: &[u32]) -> String {
+    values
+        .iter()
+        .flat_map(|value| value.to_be_bytes().to_vec())
+        .filter(|byte| *byte != 0)
+        .map(char::from)
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
@@ -16,15 +16,19 @@
 use crate::constants::*;
 use crate::response_code::{Error, Result, WrapperErrorKind as ErrorKind};
 use crate::tss2_esys::*;
-use crate::utils::algorithm_specifiers::{Cipher, EllipticCurve, HashingAlgorithm};
+use crate::utils::algorithm_specifiers::{
+    AsymmetricAlgorithm, Cipher, EllipticCurve, HashingAlgorithm,
+};
+use crate::utils::capabilities::Algorithm;
 use crate::utils::tickets::VerifiedTicket;
 use crate::utils::{
-    self, create_restricted_decryption_rsa_public, create_unrestricted_signing_ecc_public,
-    create_unrestricted_signing_rsa_public, AsymSchemeUnion, Hierarchy, PublicIdUnion, PublicKey,
-    TpmaSessionBuilder, TpmsContext, RSA_KEY_SIZES,
+    self, create_restricted_decryption_ecc_public, create_restricted_decryption_rsa_public,
+    create_unrestricted_signing_ecc_public, create_unrestricted_signing_rsa_public,
+    AsymSchemeUnion, Hierarchy, PublicIdUnion, PublicKey, PublicParmsUnion, TpmaSessionBuilder,
+    TpmsContext, TpmsEccParmsBuilder, TpmsRsaParmsBuilder, RSA_KEY_SIZES,
 };
 use crate::{Context, Tcti};
-use log::error;
+use log::{error, info};
 use std::convert::{TryFrom, TryInto};
 
 /// Structure offering an abstracted programming experience.
@@ -40,9 +44,18 @@ use std::convert::{TryFrom, TryInto};
 pub struct TransientKeyContext {
     context: Context,
     root_key_handle: ESYS_TR,
+    negotiated_algorithms: Option<NegotiatedAlgorithms>,
 }
 
 impl TransientKeyContext {
+    /// Get the algorithms chosen for the context, if they were negotiated with the TPM.
+    ///
+    /// `None` is returned if the context was built without
+    /// `TransientKeyContextBuilder::with_algorithm_negotiation`.
+    pub fn negotiated_algorithms(&self) -> Option<NegotiatedAlgorithms> {
+        self.negotiated_algorithms
+    }
+
     /// Create a new signing key.
     ///
     /// A key is created as a descendant of the context root key, with the given parameters.
@@ -77,7 +90,7 @@ impl TransientKeyContext {
         };
 
         self.set_session_attrs()?;
-        let (key_priv, key_pub) = self.context.create_key(
+        let (key_priv, key_pub, _) = self.context.create_key(
             self.root_key_handle,
             &self.get_public_from_params(key_params)?,
             &key_auth,
@@ -279,7 +292,7 @@ impl TransientKeyContext {
                 Err(e)
             })?;
         self.context.flush_context(key_handle)?;
-        Ok(verified.try_into()?)
+        Ok(verified)
     }
 
     /// Sets the encrypt and decrypt flags on the main session used by the context.
@@ -302,20 +315,22 @@ impl TransientKeyContext {
 /// # Default values
 /// * TCTI: Device TCTI
 /// * Hierarchy: Owner hierarchy
-/// * Root key size: 2048 bits
+/// * Root key algorithm: 2048 bit RSA
 /// * Root key authentication size: 32 bytes
 /// * Hierarchy authentication value: Empty array of bytes
 /// * Session encryption cipher: 256 bit AES in CFB mode
 /// * Session hash algorithm: SHA256
+/// * Algorithm negotiation: disabled
 #[derive(Debug)]
 pub struct TransientKeyContextBuilder {
     tcti: Tcti,
     hierarchy: Hierarchy,
-    root_key_size: u16, // TODO: replace with root key PUBLIC definition
+    root_key_algorithm: RootKeyAlgorithm, // TODO: replace with root key PUBLIC definition
     root_key_auth_size: usize,
     hierarchy_auth: Vec<u8>,
     default_context_cipher: Cipher,
     session_hash_alg: TPM2_ALG_ID, // TODO: Create Rust-native version
+    algorithm_preferences: Option<AlgorithmPreferences>,
 }
 
 impl TransientKeyContextBuilder {
@@ -324,11 +339,12 @@ impl TransientKeyContextBuilder {
         TransientKeyContextBuilder {
             tcti: Tcti::Device,
             hierarchy: Hierarchy::Owner,
-            root_key_size: 2048,
+            root_key_algorithm: RootKeyAlgorithm::Rsa(2048),
             root_key_auth_size: 32,
             hierarchy_auth: Vec::new(),
             default_context_cipher: Cipher::aes_256_cfb(),
             session_hash_alg: TPM2_ALG_SHA256,
+            algorithm_preferences: None,
         }
     }
 
@@ -345,8 +361,16 @@ impl TransientKeyContextBuilder {
     }
 
     /// Choose length in bits of primary key that will serve as parent to all user keys.
+    ///
+    /// The primary key is then an RSA key.
     pub fn with_root_key_size(mut self, root_key_size: u16) -> Self {
-        self.root_key_size = root_key_size;
+        self.root_key_algorithm = RootKeyAlgorithm::Rsa(root_key_size);
+        self
+    }
+
+    /// Choose the algorithm of the primary key that will serve as parent to all user keys.
+    pub fn with_root_key_algorithm(mut self, root_key_algorithm: RootKeyAlgorithm) -> Self {
+        self.root_key_algorithm = root_key_algorithm;
         self
     }
 
@@ -378,6 +402,20 @@ impl TransientKeyContextBuilder {
         self
     }
 
+    /// Negotiate the algorithms used within this context with the TPM.
+    ///
+    /// When bootstrapping the context, the capabilities of the TPM are queried and the parameters
+    /// are checked with `Context::test_parms`; for each of the cipher, the session hash algorithm
+    /// and the root key algorithm, the first entry of the corresponding `preferences` list which
+    /// is supported is chosen. The values set with `with_default_context_cipher`,
+    /// `with_session_hash_alg`, `with_root_key_size` and `with_root_key_algorithm` are then
+    /// ignored. The choices made can be retrieved with
+    /// `TransientKeyContext::negotiated_algorithms`.
+    pub fn with_algorithm_negotiation(mut self, preferences: AlgorithmPreferences) -> Self {
+        self.algorithm_preferences = Some(preferences);
+        self
+    }
+
     /// Bootstrap the TransientKeyContext.
     ///
     /// The root key is created as a primary key in the provided hierarchy and thus authentication is
@@ -391,36 +429,53 @@ impl TransientKeyContextBuilder {
     /// * it is the responsability of the client to set a sufficiently secure default cipher for the context
     ///
     /// # Constraints
-    /// * `root_key_size` must be 1024, 2048, 3072 or 4096
+    /// * the size of RSA root keys must be 1024, 2048, 3072 or 4096
     /// * `root_key_auth_size` must be at most 32
     ///
     /// # Errors
     /// * errors are returned if any method calls return an error: `Context::get_random`,
     /// `Context::start_auth_session`, `Context::create_primary_key`, `Context::flush_context`,
-    /// `Context::set_handle_auth`
-    /// * if the root key authentication size is given greater than 32 or if the root key size is
-    /// not 1024, 2048, 3072 or 4096, a `WrongParamSize` wrapper error is returned
+    /// `Context::set_handle_auth`, `Context::get_algorithms`, `Context::get_ecc_curves`
+    /// * if the root key authentication size is given greater than 32 or if the size of an RSA
+    /// root key is not 1024, 2048, 3072 or 4096, a `WrongParamSize` wrapper error is returned
+    /// * if algorithms are negotiated and none of the preferred cipher, hash or root key
+    /// algorithms is supported by the TPM, an `UnsupportedParam` wrapper error is returned
     pub unsafe fn build(self) -> Result<TransientKeyContext> {
         if self.root_key_auth_size > 32 {
             return Err(Error::local_error(ErrorKind::WrongParamSize));
         }
-        if RSA_KEY_SIZES
-            .iter()
-            .find(|sz| **sz == self.root_key_size)
-            .is_none()
-        {
-            error!("The reference implementation only supports key sizes of 1,024 and 2,048 bits.");
-            return Err(Error::local_error(ErrorKind::WrongParamSize));
+        check_root_key_algorithm(self.root_key_algorithm)?;
+        if let Some(preferences) = &self.algorithm_preferences {
+            for root_key_algorithm in &preferences.root_key_algorithms {
+                check_root_key_algorithm(*root_key_algorithm)?;
+            }
         }
         let mut context = Context::new(self.tcti)?;
 
+        let negotiated_algorithms = match &self.algorithm_preferences {
+            Some(preferences) => Some(negotiate_algorithms(&mut context, preferences)?),
+            None => None,
+        };
+        let (cipher, session_hash_alg, root_key_algorithm) = match negotiated_algorithms {
+            Some(algorithms) => (
+                algorithms.cipher,
+                algorithms.session_hash_algorithm.into(),
+                algorithms.root_key_algorithm,
+            ),
+            None => (
+                self.default_context_cipher,
+                self.session_hash_alg,
+                self.root_key_algorithm,
+            ),
+        };
+
         let session = context.start_auth_session(
             ESYS_TR_NONE,
             ESYS_TR_NONE,
             &[],
             TPM2_SE_HMAC,
-            self.default_context_cipher.into(),
-            self.session_hash_alg,
+            cipher.into(),
+            session_hash_alg,
         )?;
         let session_attr = TpmaSessionBuilder::new()
             .with_flag(TPMA_SESSION_DECRYPT)
@@ -438,13 +493,15 @@ impl TransientKeyContextBuilder {
             context.tr_set_auth(self.hierarchy.esys_rh(), &self.hierarchy_auth)?;
         }
 
-        let root_key_handle = context.create_primary_key(
+        let root_key_public = match root_key_algorithm {
+            RootKeyAlgorithm::Rsa(key_bits) => {
+                create_restricted_decryption_rsa_public(cipher, key_bits, 0)?
+            }
+            RootKeyAlgorithm::Ecc(curve) => create_restricted_decryption_ecc_public(cipher, curve)?,
+        };
+        let (root_key_handle, _) = context.create_primary_key(
             self.hierarchy.esys_rh(),
-            &create_restricted_decryption_rsa_public(
-                self.default_context_cipher,
-                self.root_key_size,
-                0,
-            )?,
+            &root_key_public,
             &root_key_auth,
             &[],
             &[],
@@ -456,8 +513,8 @@ impl TransientKeyContextBuilder {
             ESYS_TR_NONE,
             &[],
             TPM2_SE_HMAC,
-            self.default_context_cipher.into(),
-            self.session_hash_alg,
+            cipher.into(),
+            session_hash_alg,
         )?;
         let (old_session, _, _) = context.sessions();
         context.set_sessions((new_session, ESYS_TR_NONE, ESYS_TR_NONE));
@@ -465,6 +522,7 @@ impl TransientKeyContextBuilder {
         Ok(TransientKeyContext {
             context,
             root_key_handle,
+            negotiated_algorithms,
         })
     }
 }
@@ -475,6 +533,154 @@ impl Default for TransientKeyContextBuilder {
     }
 }
 
+/// Algorithm of the root key of a `TransientKeyContext`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RootKeyAlgorithm {
+    /// RSA key of the given size in bits
+    ///
+    /// Can only be one of: 1024, 2048, 3072 or 4096
+    Rsa(u16),
+    /// ECC key based on the given curve
+    Ecc(EllipticCurve),
+}
+
+/// Algorithms a `TransientKeyContextBuilder` can choose from when negotiating with the TPM.
+///
+/// Each list is ordered by preference, the strongest algorithm coming first.
+///
+/// # Default values
+/// * Ciphers: 256 bit AES in CFB mode, 128 bit AES in CFB mode
+/// * Session hash algorithms: SHA512, SHA384, SHA256
+/// * Root key algorithms: ECC NIST P384, 3072 bit RSA, ECC NIST P256, 2048 bit RSA
+#[derive(Debug, Clone)]
+pub struct AlgorithmPreferences {
+    /// Ciphers used for session-based encryption and for encrypting user keys
+    ///
+    /// *Must* be usable for session-based encryption, i.e. in CFB mode
+    pub ciphers: Vec<Cipher>,
+    /// Hash algorithms used by the sessions
+    pub session_hash_algorithms: Vec<HashingAlgorithm>,
+    /// Algorithms of the root key
+    pub root_key_algorithms: Vec<RootKeyAlgorithm>,
+}
+
+impl Default for AlgorithmPreferences {
+    fn default() -> Self {
+        AlgorithmPreferences {
+            ciphers: vec![Cipher::aes_256_cfb(), Cipher::aes_128_cfb()],
+            session_hash_algorithms: vec![
+                HashingAlgorithm::Sha512,
+                HashingAlgorithm::Sha384,
+                HashingAlgorithm::Sha256,
+            ],
+            root_key_algorithms: vec![
+                RootKeyAlgorithm::Ecc(EllipticCurve::NistP384),
+                RootKeyAlgorithm::Rsa(3072),
+                RootKeyAlgorithm::Ecc(EllipticCurve::NistP256),
+                RootKeyAlgorithm::Rsa(2048),
+            ],
+        }
+    }
+}
+
+/// Algorithms chosen for a `TransientKeyContext` after negotiating with the TPM.
+#[derive(Debug, Clone, Copy)]
+pub struct NegotiatedAlgorithms {
+    /// Cipher used for session-based encryption and for encrypting user keys
+    pub cipher: Cipher,
+    /// Hash algorithm used by the sessions
+    pub session_hash_algorithm: HashingAlgorithm,
+    /// Algorithm of the root key
+    pub root_key_algorithm: RootKeyAlgorithm,
+}
+
+// Check that an RSA root key has one of the supported sizes.
+fn check_root_key_algorithm(root_key_algorithm: RootKeyAlgorithm) -> Result<()> {
+    if let RootKeyAlgorithm::Rsa(key_bits) = root_key_algorithm {
+        if RSA_KEY_SIZES.iter().find(|sz| **sz == key_bits).is_none() {
+            error!("The reference implementation only supports key sizes of 1,024 and 2,048 bits.");
+            return Err(Error::local_error(ErrorKind::WrongParamSize));
+        }
+    }
+    Ok(())
+}
+
+// Choose, for each kind of algorithm, the first preferred one supported by the TPM.
+fn negotiate_algorithms(
+    context: &mut Context,
+    preferences: &AlgorithmPreferences,
+) -> Result<NegotiatedAlgorithms> {
+    let algorithms = context.get_algorithms()?;
+    let ecc_curves = if algorithms.contains(&Algorithm::Asymmetric(AsymmetricAlgorithm::Ecc)) {
+        context.get_ecc_curves()?
+    } else {
+        Vec::new()
+    };
+
+    let cipher = preferences
+        .ciphers
+        .iter()
+        .cloned()
+        .find(|cipher| {
+            context
+                .test_parms(PublicParmsUnion::SymDetail(*cipher))
+                .is_ok()
+        })
+        .ok_or_else(|| no_supported_algorithm("cipher"))?;
+
+    let session_hash_algorithm = preferences
+        .session_hash_algorithms
+        .iter()
+        .cloned()
+        .find(|hashing_algorithm| algorithms.contains(&Algorithm::Hashing(*hashing_algorithm)))
+        .ok_or_else(|| no_supported_algorithm("session hash algorithm"))?;
+
+    let mut root_key_algorithm = None;
+    for candidate in &preferences.root_key_algorithms {
+        let parms = match *candidate {
+            RootKeyAlgorithm::Rsa(key_bits) => {
+                if !algorithms.contains(&Algorithm::Asymmetric(AsymmetricAlgorithm::Rsa)) {
+                    continue;
+                }
+                PublicParmsUnion::RsaDetail(
+                    TpmsRsaParmsBuilder::new_restricted_decryption_key(cipher.into(), key_bits, 0)
+                        .build()?,
+                )
+            }
+            RootKeyAlgorithm::Ecc(curve) => {
+                if !ecc_curves.contains(&curve) {
+                    continue;
+                }
+                PublicParmsUnion::EccDetail(
+                    TpmsEccParmsBuilder::new_restricted_decryption_key(cipher, curve).build()?,
+                )
+            }
+        };
+        if context.test_parms(parms).is_ok() {
+            root_key_algorithm = Some(*candidate);
+            break;
+        }
+    }
+    let root_key_algorithm =
+        root_key_algorithm.ok_or_else(|| no_supported_algorithm("root key algorithm"))?;
+
+    let negotiated_algorithms = NegotiatedAlgorithms {
+        cipher,
+        session_hash_algorithm,
+        root_key_algorithm,
+    };
+    info!("Negotiated algorithms: {:?}", negotiated_algorithms);
+    Ok(negotiated_algorithms)
+}
+
+fn no_supported_algorithm(kind: &str) -> Error {
+    error!(
+        "Error: none of the preferred {}s is supported by the TPM.",
+        kind
+    );
+    Error::local_error(ErrorKind::UnsupportedParam)
+}
+
 /// Parameters for the kinds of keys supported by the context
 #[derive(Debug, Clone, Copy)]
 pub enum KeyParams {
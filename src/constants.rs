@@ -69,7 +69,7 @@ pub const TPM2_CC_ClockSet: TPM2_CC = 0x00000128;
 pub const TPM2_CC_HierarchyChangeAuth: TPM2_CC = 0x00000129;
 pub const TPM2_CC_NV_DefineSpace: TPM2_CC = 0x0000012a;
 pub const TPM2_CC_PCR_Allocate: TPM2_CC = 0x0000012b;
-pub const TPM2_CC_PCR_SetAuthPolicy: TPM2_CC = 0x0F000012c;
+pub const TPM2_CC_PCR_SetAuthPolicy: TPM2_CC = 0x0000012c;
 pub const TPM2_CC_PP_Commands: TPM2_CC = 0x0000012d;
 pub const TPM2_CC_SetPrimaryPolicy: TPM2_CC = 0x0000012e;
 pub const TPM2_CC_FieldUpgradeStart: TPM2_CC = 0x0000012f;
@@ -611,3 +611,35 @@ pub const TPMA_CC_RHANDLE: TPMA_CC = 0x10000000; /* SET 1 indicates the presence
 pub const TPMA_CC_V: TPMA_CC = 0x20000000; /* SET 1 indicates that the command is vendor-specific. CLEAR 0 indicates that the command is defined in a version of this specification */
 pub const TPMA_CC_RES_MASK: TPMA_CC = 0xC0000000; /* allocated for software shall be zero */
 pub const TPMA_CC_RES_SHIFT: u32 = 30;
+
+pub const TPMA_NV_PPWRITE: TPMA_NV = 0x00000001; /* SET 1 The Index data can be written if Platform Authorization is provided. CLEAR 0 Writing of the Index data cannot be authorized with Platform Authorization. */
+pub const TPMA_NV_OWNERWRITE: TPMA_NV = 0x00000002; /* SET 1 The Index data can be written if Owner Authorization is provided. CLEAR 0 Writing of the Index data cannot be authorized with Owner Authorization. */
+pub const TPMA_NV_AUTHWRITE: TPMA_NV = 0x00000004; /* SET 1 Authorizations to change the Index contents that require USER role may be provided with an HMAC session or password. CLEAR 0 Authorizations to change the Index contents that require USER role may not be provided with an HMAC session or password. */
+pub const TPMA_NV_POLICYWRITE: TPMA_NV = 0x00000008; /* SET 1 Authorizations to change the Index contents that require USER role may be provided with a policy session. CLEAR 0 Authorizations to change the Index contents that require USER role may not be provided with a policy session. */
+pub const TPMA_NV_TPM2_NT_MASK: TPMA_NV = 0x000000F0; /* The type of the index. NOTE A TPM is not required to support all TPM2_NT values */
+pub const TPMA_NV_RESERVED1_MASK: TPMA_NV = 0x00000300; /* shall be zero. Reserved for future use */
+pub const TPMA_NV_POLICY_DELETE: TPMA_NV = 0x00000400; /* SET 1 Index may not be deleted unless the authPolicy is satisfied using TPM2_NV_UndefineSpaceSpecial. CLEAR 0 Index may be deleted with proper platform or owner authorization using TPM2_NV_UndefineSpace. */
+pub const TPMA_NV_WRITELOCKED: TPMA_NV = 0x00000800; /* SET 1 Index cannot be written. CLEAR 0 Index can be written. */
+pub const TPMA_NV_WRITEALL: TPMA_NV = 0x00001000; /* SET 1 A partial write of the Index data is not allowed. The write size shall match the defined space size. CLEAR 0 Partial writes are allowed. This setting is required if the .dataSize of the Index is larger than NV_MAX_BUFFER_SIZE for the implementation. */
+pub const TPMA_NV_WRITEDEFINE: TPMA_NV = 0x00002000; /* SET 1 TPM2_NV_WriteLock may be used to prevent further writes to this location. CLEAR 0 TPM2_NV_WriteLock does not block subsequent writes if TPMA_NV_WRITE_STCLEAR is also CLEAR. */
+pub const TPMA_NV_WRITE_STCLEAR: TPMA_NV = 0x00004000; /* SET 1 TPM2_NV_WriteLock may be used to prevent further writes to this location until the next TPM Reset or TPM Restart. CLEAR 0 TPM2_NV_WriteLock does not block subsequent writes if TPMA_NV_WRITEDEFINE is also CLEAR. */
+pub const TPMA_NV_GLOBALLOCK: TPMA_NV = 0x00008000; /* SET 1 If TPM2_NV_GlobalWriteLock is successful then further writes to this location are not permitted until the next TPM Reset or TPM Restart. CLEAR 0 TPM2_NV_GlobalWriteLock has no effect on the writing of the data at this NV Index. */
+pub const TPMA_NV_PPREAD: TPMA_NV = 0x00010000; /* SET 1 The Index data can be read if Platform Authorization is provided. CLEAR 0 Reading of the Index data cannot be authorized with Platform Authorization. */
+pub const TPMA_NV_OWNERREAD: TPMA_NV = 0x00020000; /* SET 1 The Index data can be read if Owner Authorization is provided. CLEAR 0 Reading of the Index data cannot be authorized with Owner Authorization. */
+pub const TPMA_NV_AUTHREAD: TPMA_NV = 0x00040000; /* SET 1 The Index data may be read if the authValue is provided. CLEAR 0 Reading of the Index data cannot be authorized with the Index authValue. */
+pub const TPMA_NV_POLICYREAD: TPMA_NV = 0x00080000; /* SET 1 The Index data may be read if the authPolicy is satisfied. CLEAR 0 Reading of the Index data cannot be authorized with the Index authPolicy. */
+pub const TPMA_NV_RESERVED2_MASK: TPMA_NV = 0x01F00000; /* shall be zero. Reserved for future use */
+pub const TPMA_NV_NO_DA: TPMA_NV = 0x02000000; /* SET 1 Authorization failures of the Index do not affect the DA logic and authorization of the Index is not blocked when the TPM is in Lockout mode. CLEAR 0 Authorization failures of the Index will increment the authorization failure counter and authorizations of this Index are not allowed when the TPM is in Lockout mode. */
+pub const TPMA_NV_ORDERLY: TPMA_NV = 0x04000000; /* SET 1 NV Index state is only required to be saved when the TPM performs an orderly shutdown TPM2_Shutdown. CLEAR 0 NV Index state is required to be persistent after the command to update the Index completes successfully that is the NV update is synchronous with the update command. */
+pub const TPMA_NV_CLEAR_STCLEAR: TPMA_NV = 0x08000000; /* SET 1 TPMA_NV_WRITTEN for the Index is CLEAR by TPM Reset or TPM Restart. CLEAR 0 TPMA_NV_WRITTEN is not changed by TPM Restart. NOTE 1 This attribute may only be SET if TPM2_NT is not TPM2_NT_COUNTER. */
+pub const TPMA_NV_READLOCKED: TPMA_NV = 0x10000000; /* SET 1 Reads of the Index are blocked until the next TPM Reset or TPM Restart. CLEAR 0 Reads of the Index are allowed if proper authorization is provided. */
+pub const TPMA_NV_WRITTEN: TPMA_NV = 0x20000000; /* SET 1 Index has been written. CLEAR 0 Index has not been written. */
+pub const TPMA_NV_PLATFORMCREATE: TPMA_NV = 0x40000000; /* SET 1 This Index may be undefined with Platform Authorization but not with Owner Authorization. CLEAR 0 This Index may be undefined using Owner Authorization but not with Platform Authorization. */
+pub const TPMA_NV_READ_STCLEAR: TPMA_NV = 0x80000000; /* SET 1 TPM2_NV_ReadLock may be SET for this Index. CLEAR 0 TPM2_NV_ReadLock has no effect on this Index. */
+
+pub const TPM2_NT_ORDINARY: TPM2_NT = 0x0; /* Ordinary contains data that is opaque to the TPM that can only be modified using TPM2_NV_Write. */
+pub const TPM2_NT_COUNTER: TPM2_NT = 0x1; /* Counter contains an 8-octet value that is to be used as a counter and can only be modified with TPM2_NV_Increment */
+pub const TPM2_NT_BITS: TPM2_NT = 0x2; /* Bit Field contains an 8-octet value to be used as a bit field and can only be modified with TPM2_NV_SetBits. */
+pub const TPM2_NT_EXTEND: TPM2_NT = 0x4; /* Extend contains a digest-sized value used like a PCR. The Index can only be modified using TPM2_NV_Extend. The extend will use the nameAlg of the Index. */
+pub const TPM2_NT_PIN_FAIL: TPM2_NT = 0x8; /* PIN Fail - contains a pinCount that increments on a PIN authorization failure and a pinLimit */
+pub const TPM2_NT_PIN_PASS: TPM2_NT = 0x9; /* PIN Pass - contains a pinCount that increments on a PIN authorization success and a pinLimit */
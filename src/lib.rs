@@ -37,7 +37,7 @@
 //! Only platforms based on processors with a word size of at least 16 bits are supported.
 //!
 //! The crate is expected to successfully compile and run using the nightly compiler and any other
-//! Rust compiler since 1.38.0.
+//! Rust compiler since 1.65.0.
 //!
 //! # Disclaimer
 //!
@@ -58,8 +58,9 @@
 //! * response_code - implements error code parsing for the formats defined in the TSS spec and
 //! exposes it along with wrapper-specific error types.
 //! * abstraction - intended to offer abstracted interfaces that focus on providing different
-//! kinds of user experience to the developers; at the moment the only implementation allows for a
-//! resource-handle-free coding experience by working soloely with object contexts.
+//! kinds of user experience to the developers; the `transient` module allows for a
+//! resource-handle-free coding experience by working soloely with object contexts, while the `nv`
+//! module offers typed access to special NV indices.
 //! * tss2_esys - exposes raw FFI bindings to the Enhanced System API.
 //! * constants - exposes constants that were ported to Rust manually as bindgen does not support
 //! converting them yet.
@@ -118,6 +119,11 @@ pub mod response_code;
 pub mod utils;
 
 pub use abstraction::transient::TransientKeyContext;
+use constants::{
+    TPM2_ALG_LAST, TPM2_CAP_ALGS, TPM2_CAP_COMMANDS, TPM2_CAP_ECC_CURVES, TPM2_CAP_HANDLES,
+    TPM2_CAP_PCRS, TPM2_CAP_TPM_PROPERTIES, TPM2_CC_LAST, TPM2_PT_FIXED, TPM2_PT_GROUP,
+    TPM2_PT_NV_BUFFER_MAX, TPM2_PT_VAR,
+};
 use log::{error, info};
 use mbox::MBox;
 use response_code::Result;
@@ -126,9 +132,17 @@ use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
 use std::ffi::CString;
 use std::ptr::{null, null_mut};
+use std::time::Duration;
 use tss2_esys::*;
 use utils::{
-    algorithm_specifiers::HashingAlgorithm, tickets::HashcheckTicket, Hierarchy, PcrSelections,
+    algorithm_specifiers::{EllipticCurve, HashingAlgorithm},
+    attest::Attest,
+    capabilities::{self, Algorithm, CapabilityData, HandleType},
+    command_code::CommandCode,
+    nv::NvPublic,
+    policy::ComparisonOperation,
+    tickets::{AuthTicket, CreationTicket, HashcheckTicket, VerifiedTicket},
+    DigestValues, Hierarchy, KeyCreationData, LocalityAttributes, PcrData, PcrSelections,
     PublicParmsUnion, Signature, TpmaSession, TpmaSessionBuilder, TpmsContext,
 };
 
@@ -163,6 +177,13 @@ const DEVICE: &str = "device";
 const MSSIM: &str = "mssim";
 const TABRMD: &str = "tabrmd";
 
+// Maximum number of bytes sent or requested in a single NV read or write command when the TPM
+// does not report its TPM2_PT_NV_BUFFER_MAX property.
+const NV_MAX_BUFFER_CHUNK: usize = 512;
+
+// Maximum number of times `pcr_read` starts over when the PCRs are modified while being read.
+const PCR_READ_MAX_RESTARTS: usize = 5;
+
 /// Safe abstraction over an ESYS_CONTEXT.
 ///
 /// Serves as a low-level abstraction interface to the TPM, providing a thin wrapper around the
@@ -200,6 +221,11 @@ pub struct Context {
     tcti_context: Option<MBox<TSS2_TCTI_CONTEXT>>,
     /// A set of currently open object handles that should be flushed before closing the context.
     open_handles: HashSet<ESYS_TR>,
+    /// A set of currently open handles of entities which cannot be flushed, such as NV indices and
+    /// persistent objects, that should be closed before closing the context.
+    closable_handles: HashSet<ESYS_TR>,
+    /// Maximum number of bytes in a single NV read or write, read from the TPM on first use.
+    nv_buffer_max: Option<usize>,
 }
 
 impl Context {
@@ -245,6 +271,8 @@ impl Context {
                 sessions: (ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE),
                 tcti_context,
                 open_handles: HashSet::new(),
+                closable_handles: HashSet::new(),
+                nv_buffer_max: None,
             };
             Ok(context)
         } else {
@@ -315,7 +343,35 @@ impl Context {
         self.sessions
     }
 
-    /// Create a primary key and return the handle.
+    /// Set the locality from which subsequent commands are sent to the TPM.
+    ///
+    /// Only some TCTIs support changing the locality, such as the one for the Microsoft simulator
+    /// (`Tcti::Mssim`); the others return an error. `locality` is either between 0 and 4, or an
+    /// extended locality between 32 and 255.
+    ///
+    /// # Errors
+    /// * if the TCTI does not implement locality setting, an `UnsupportedParam` wrapper error is
+    /// returned
+    pub fn set_tcti_locality(&mut self, locality: u8) -> Result<()> {
+        let tcti_context = self.tcti_context.as_mut().unwrap().as_mut_ptr(); // will only fail if called from Drop after .take()
+        let set_locality =
+            unsafe { (*(tcti_context as *mut TSS2_TCTI_CONTEXT_COMMON_V1)).setLocality };
+        let set_locality = set_locality.ok_or_else(|| {
+            error!("Error: the TCTI does not support setting the locality.");
+            Error::local_error(ErrorKind::UnsupportedParam)
+        })?;
+        let ret = unsafe { set_locality(tcti_context, locality) };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error when setting the TCTI locality: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Create a primary key and return its handle, along with the description of its creation.
     ///
     /// The authentication value, initial data, outside info and creation PCRs are passed as slices
     /// which are then converted by the method into TSS native structures.
@@ -339,7 +395,7 @@ impl Context {
         initial_data: &[u8],
         outside_info: &[u8],
         creation_pcrs: &[TPMS_PCR_SELECTION],
-    ) -> Result<ESYS_TR> {
+    ) -> Result<(ESYS_TR, KeyCreationData)> {
         let sensitive_create = TPM2B_SENSITIVE_CREATE {
             size: std::mem::size_of::<TPMS_SENSITIVE_CREATE>()
                 .try_into()
@@ -392,19 +448,24 @@ impl Context {
         if ret.is_success() {
             unsafe {
                 let _ = MBox::from_raw(outpublic);
-                let _ = MBox::from_raw(creation_data);
-                let _ = MBox::from_raw(creation_hash);
-                let _ = MBox::from_raw(creation_ticket);
             }
             let _ = self.open_handles.insert(prim_key_handle);
-            Ok(prim_key_handle)
+            let creation = unsafe {
+                key_creation_data(
+                    MBox::from_raw(creation_data),
+                    MBox::from_raw(creation_hash),
+                    MBox::from_raw(creation_ticket),
+                )
+            }?;
+            Ok((prim_key_handle, creation))
         } else {
             error!("Error in creating primary key: {}.", ret);
             Err(ret)
         }
     }
 
-    /// Create a key and return the handle.
+    /// Create a key and return its private and public parts, along with the description of its
+    /// creation.
     ///
     /// The authentication value, initial data, outside info and creation PCRs are passed as slices
     /// which are then converted by the method into TSS native structures.
@@ -428,7 +489,7 @@ impl Context {
         initial_data: &[u8],
         outside_info: &[u8],
         creation_pcrs: &[TPMS_PCR_SELECTION],
-    ) -> Result<(TPM2B_PRIVATE, TPM2B_PUBLIC)> {
+    ) -> Result<(TPM2B_PRIVATE, TPM2B_PUBLIC, KeyCreationData)> {
         let sensitive_create = TPM2B_SENSITIVE_CREATE {
             size: std::mem::size_of::<TPMS_SENSITIVE_CREATE>()
                 .try_into()
@@ -455,8 +516,8 @@ impl Context {
         let mut outpublic = null_mut();
         let mut outprivate = null_mut();
         let mut creation_data = null_mut();
-        let mut digest = null_mut();
-        let mut creation = null_mut();
+        let mut creation_hash = null_mut();
+        let mut creation_ticket = null_mut();
 
         let ret = unsafe {
             Esys_Create(
@@ -472,8 +533,8 @@ impl Context {
                 &mut outprivate,
                 &mut outpublic,
                 &mut creation_data,
-                &mut digest,
-                &mut creation,
+                &mut creation_hash,
+                &mut creation_ticket,
             )
         };
         let ret = Error::from_tss_rc(ret);
@@ -481,12 +542,14 @@ impl Context {
         if ret.is_success() {
             let outprivate = unsafe { MBox::from_raw(outprivate) };
             let outpublic = unsafe { MBox::from_raw(outpublic) };
-            unsafe {
-                let _ = MBox::from_raw(creation_data);
-                let _ = MBox::from_raw(digest);
-                let _ = MBox::from_raw(creation);
-            }
-            Ok((*outprivate, *outpublic))
+            let creation = unsafe {
+                key_creation_data(
+                    MBox::from_raw(creation_data),
+                    MBox::from_raw(creation_hash),
+                    MBox::from_raw(creation_ticket),
+                )
+            }?;
+            Ok((*outprivate, *outpublic, creation))
         } else {
             error!("Error in creating derived key: {}.", ret);
             Err(ret)
@@ -569,6 +632,7 @@ impl Context {
     /// Verify if a signature was generated by signing a given digest with a key in the TPM.
     ///
     /// The digest is passed as a sliice and converted by the method to a TSS digest structure.
+    /// The returned ticket can be used as input to `policy_authorize`.
     ///
     /// # Constraints
     /// * `digest` must be at most 64 elements long
@@ -580,7 +644,7 @@ impl Context {
         key_handle: ESYS_TR,
         digest: &[u8],
         signature: &TPMT_SIGNATURE,
-    ) -> Result<TPMT_TK_VERIFIED> {
+    ) -> Result<VerifiedTicket> {
         let mut validation = null_mut();
         let digest = wrap_buffer!(digest, TPM2B_DIGEST, 64);
         let ret = unsafe {
@@ -598,8 +662,8 @@ impl Context {
         let ret = Error::from_tss_rc(ret);
 
         if ret.is_success() {
-            let validation = unsafe { MBox::from_raw(validation) };
-            Ok(*validation)
+            let validation = unsafe { MBox::<TPMT_TK_VERIFIED>::from_raw(validation) };
+            Ok(VerifiedTicket::try_from(*validation)?)
         } else {
             error!("Error in loading: {}.", ret);
             Err(ret)
@@ -758,12 +822,100 @@ impl Context {
         }
     }
 
+    /// Make a transient object persistent, or evict a persistent object.
+    ///
+    /// If `object_handle` refers to a transient object, a copy of it is made persistent at
+    /// `persistent_handle` (e.g. `0x8100_0001`) and the object handle of the persistent copy is
+    /// returned; the transient object itself stays loaded. If `object_handle` refers to a
+    /// persistent object, `persistent_handle` must be its TPM handle: the object is evicted,
+    /// `object_handle` is no longer valid after the call succeeds and `None` is returned.
+    ///
+    /// The object handle of the persistent copy is closed, not flushed, when the context is dropped.
+    pub fn evict_control(
+        &mut self,
+        auth: ESYS_TR,
+        object_handle: ESYS_TR,
+        persistent_handle: TPM2_HANDLE,
+    ) -> Result<Option<ESYS_TR>> {
+        let mut new_object_handle = ESYS_TR_NONE;
+        let ret = unsafe {
+            Esys_EvictControl(
+                self.mut_context(),
+                auth,
+                object_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                persistent_handle,
+                &mut new_object_handle,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            if new_object_handle == ESYS_TR_NONE {
+                let _ = self.closable_handles.remove(&object_handle);
+                Ok(None)
+            } else {
+                let _ = self.closable_handles.insert(new_object_handle);
+                Ok(Some(new_object_handle))
+            }
+        } else {
+            error!("Error in evict control: {}.", ret);
+            Err(ret)
+        }
+    }
+
     /// Read values from selected PCRs
-    pub fn pcr_read(
+    ///
+    /// A single TPM2_PCR_Read command returns at most 8 digests, so the command is repeated until
+    /// the whole selection has been read. If the PCRs are modified in between two reads, the
+    /// selection is read again from the start so that all values are consistent with the
+    /// returned PCR update counter.
+    ///
+    /// PCRs not implemented by the TPM, or banks which are not allocated, are absent from the
+    /// returned `PcrData`.
+    ///
+    /// # Errors
+    /// * if the PCRs keep being modified while being read, and the selection had to be read again
+    /// 5 times, an `InconsistentParams` wrapper error is returned
+    pub fn pcr_read(&mut self, pcr_selections: PcrSelections) -> Result<(u32, PcrData)> {
+        let mut pcr_data = PcrData::new();
+        let mut remaining = pcr_selections.clone();
+        let mut pcr_update_counter: Option<u32> = None;
+        let mut restarts = 0;
+        loop {
+            let (update_counter, pcr_selection_out, pcr_values) =
+                self.pcr_read_once(remaining.clone().into())?;
+            if pcr_update_counter.is_some() && pcr_update_counter != Some(update_counter) {
+                if restarts == PCR_READ_MAX_RESTARTS {
+                    error!(
+                        "Error: the PCRs were modified while being read {} times in a row.",
+                        restarts + 1
+                    );
+                    return Err(Error::local_error(ErrorKind::InconsistentParams));
+                }
+                restarts += 1;
+                pcr_data = PcrData::new();
+                remaining = pcr_selections.clone();
+                pcr_update_counter = None;
+                continue;
+            }
+            pcr_update_counter = Some(update_counter);
+            pcr_data.add(&pcr_selection_out, &pcr_values)?;
+            let read = PcrSelections::try_from(pcr_selection_out)?;
+            remaining.subtract(&read);
+            // Stop when nothing is left, or when the TPM does not return anything more
+            if remaining.is_empty() || read.is_empty() {
+                return Ok((update_counter, pcr_data));
+            }
+        }
+    }
+
+    fn pcr_read_once(
         &mut self,
-        pcr_selection: PcrSelections,
+        tpml_pcr_selection: TPML_PCR_SELECTION,
     ) -> Result<(u32, TPML_PCR_SELECTION, TPML_DIGEST)> {
-        let tpml_pcr_selection: TPML_PCR_SELECTION = pcr_selection.into();
         let mut pcr_update_counter: u32 = 0;
         let mut pcr_selection_out = null_mut();
         let mut pcr_values = null_mut();
@@ -787,221 +939,2114 @@ impl Context {
             let pcr_values = unsafe { MBox::<TPML_DIGEST>::from_raw(pcr_values) };
             Ok((pcr_update_counter, *pcr_selection_out, *pcr_values))
         } else {
-            error!("Error in creating derived key: {}.", ret);
+            error!("Error in reading PCRs: {}.", ret);
             Err(ret)
         }
     }
 
-    /// Generate a quote on the selected PCRs
+    /// Extend a PCR with the provided digests.
     ///
-    /// # Constraints
-    /// * `qualifying_data` must be at most 64 elements long
+    /// Each bank of the PCR for which a digest is provided is extended with it; the other banks
+    /// are left unchanged. `pcr_handle` is the ESYS handle of the PCR, `ESYS_TR_PCR0` to
+    /// `ESYS_TR_PCR31`.
     ///
     /// # Errors
-    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
-    pub fn quote(
-        &mut self,
-        signing_key_handle: ESYS_TR,
-        qualifying_data: &[u8],
-        signing_scheme: TPMT_SIG_SCHEME,
-        pcr_selection: PcrSelections,
-    ) -> Result<(TPM2B_ATTEST, Signature)> {
-        let mut quoted = null_mut();
-        let mut signature = null_mut();
-        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
-
+    /// * if more than 16 digests are provided, a `WrongParamSize` wrapper error is returned
+    pub fn pcr_extend(&mut self, pcr_handle: ESYS_TR, digests: DigestValues) -> Result<()> {
+        let digests = TPML_DIGEST_VALUES::try_from(digests)?;
         let ret = unsafe {
-            Esys_Quote(
+            Esys_PCR_Extend(
                 self.mut_context(),
-                signing_key_handle,
+                pcr_handle,
                 self.sessions.0,
                 self.sessions.1,
                 self.sessions.2,
-                &qualifying_data,
-                &signing_scheme,
-                &pcr_selection.into(),
-                &mut quoted,
-                &mut signature,
+                &digests,
             )
         };
         let ret = Error::from_tss_rc(ret);
 
         if ret.is_success() {
-            let quoted = unsafe { MBox::<TPM2B_ATTEST>::from_raw(quoted) };
-            let signature = unsafe { MBox::from_raw(signature) };
-            Ok((*quoted, unsafe { Signature::try_from(*signature)? }))
+            Ok(())
         } else {
-            error!("Error in quoting PCR: {}", ret);
+            error!("Error in extending PCR: {}.", ret);
             Err(ret)
         }
     }
 
-    // TODO: Should we really keep `num_bytes` as `u16`?
-    /// Get a number of random bytes from the TPM and return them.
+    /// Hash the event data with the algorithm of every implemented bank and extend a PCR with the
+    /// digests obtained.
+    ///
+    /// The digests computed by the TPM are returned. If `pcr_handle` is `ESYS_TR_NONE`, the data
+    /// is hashed but no PCR is extended.
+    ///
+    /// # Constraints
+    /// * `event_data` must be at most 1024 elements long
     ///
     /// # Errors
-    /// * if converting `num_bytes` to `u16` fails, a `WrongParamSize` will be returned
-    pub fn get_random(&mut self, num_bytes: usize) -> Result<Vec<u8>> {
-        let mut buffer = null_mut();
+    /// * if `event_data` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn pcr_event(&mut self, pcr_handle: ESYS_TR, event_data: &[u8]) -> Result<DigestValues> {
+        let event_data = wrap_buffer!(event_data, TPM2B_EVENT, 1024);
+        let mut digests = null_mut();
         let ret = unsafe {
-            Esys_GetRandom(
+            Esys_PCR_Event(
                 self.mut_context(),
+                pcr_handle,
                 self.sessions.0,
                 self.sessions.1,
                 self.sessions.2,
-                num_bytes
-                    .try_into()
-                    .or_else(|_| Err(Error::local_error(ErrorKind::WrongParamSize)))?,
-                &mut buffer,
+                &event_data,
+                &mut digests,
             )
         };
-
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
-            let buffer = unsafe { MBox::from_raw(buffer) };
-            let mut random = buffer.buffer.to_vec();
-            random.truncate(buffer.size.try_into().unwrap()); // should not panic given the TryInto above
-            Ok(random)
+            let digests = unsafe { MBox::<TPML_DIGEST_VALUES>::from_raw(digests) };
+            DigestValues::try_from(*digests)
         } else {
-            error!("Error in flushing context: {}.", ret);
+            error!("Error in PCR event: {}.", ret);
             Err(ret)
         }
     }
 
-    /// Test if the given parameters are supported by the TPM.
+    /// Reset a PCR to its default initial value.
     ///
-    /// # Errors
-    /// * if any of the public parameters is not compatible with the TPM,
-    /// an `Err` containing the specific unmarshalling error will be returned.
-    pub fn test_parms(&mut self, parms: PublicParmsUnion) -> Result<()> {
-        let public_parms = TPMT_PUBLIC_PARMS {
-            type_: parms.object_type(),
-            parameters: parms.into(),
-        };
+    /// Only the PCRs which the platform specification allows to be reset from the current
+    /// locality can be reset.
+    pub fn pcr_reset(&mut self, pcr_handle: ESYS_TR) -> Result<()> {
         let ret = unsafe {
-            Esys_TestParms(
+            Esys_PCR_Reset(
                 self.mut_context(),
+                pcr_handle,
                 self.sessions.0,
                 self.sessions.1,
                 self.sessions.2,
-                &public_parms,
             )
         };
-
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
             Ok(())
         } else {
-            error!("Error while testing parameters: {}.", ret);
+            error!("Error in resetting PCR: {}.", ret);
             Err(ret)
         }
     }
 
-    /// Function for invoking TPM2_Hash command.
+    /// Set the PCR banks to be allocated after the next TPM reset.
     ///
-    pub fn hash(
+    /// `auth_handle` must be `ESYS_TR_RH_PLATFORM`. Banks absent from `pcr_allocation` are left
+    /// unchanged; a bank selected without any PCR is deallocated.
+    ///
+    /// The returned tuple contains, in order: whether the allocation succeeded, the maximum
+    /// number of PCRs per bank, and the NV memory sizes needed for and available to the
+    /// requested allocation.
+    pub fn pcr_allocate(
         &mut self,
-        data: &[u8],
-        hashing_algorithm: HashingAlgorithm,
-        hierarchy: Hierarchy,
-    ) -> Result<(Vec<u8>, HashcheckTicket)> {
-        let data = wrap_buffer!(data, TPM2B_MAX_BUFFER, 1024);
-        let mut out_hash_ptr = null_mut();
-        let mut validation_ptr = null_mut();
+        auth_handle: ESYS_TR,
+        pcr_allocation: PcrSelections,
+    ) -> Result<(bool, u32, u32, u32)> {
+        let mut allocation_success: TPMI_YES_NO = 0;
+        let mut max_pcr: u32 = 0;
+        let mut size_needed: u32 = 0;
+        let mut size_available: u32 = 0;
         let ret = unsafe {
-            Esys_Hash(
+            Esys_PCR_Allocate(
                 self.mut_context(),
+                auth_handle,
                 self.sessions.0,
                 self.sessions.1,
                 self.sessions.2,
-                &data,
-                hashing_algorithm.into(),
-                hierarchy.rh(),
-                &mut out_hash_ptr,
-                &mut validation_ptr,
+                &pcr_allocation.into(),
+                &mut allocation_success,
+                &mut max_pcr,
+                &mut size_needed,
+                &mut size_available,
             )
         };
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
-            let out_hash = unsafe { MBox::<TPM2B_DIGEST>::from_raw(out_hash_ptr) };
-            let validation = unsafe { MBox::<TPMT_TK_HASHCHECK>::from_raw(validation_ptr) };
             Ok((
-                out_hash.buffer[..out_hash.size as usize].to_vec(),
-                HashcheckTicket::try_from(*validation)?,
+                allocation_success != 0,
+                max_pcr,
+                size_needed,
+                size_available,
             ))
         } else {
-            error!("Error failed to peform hash operation: {}.", ret);
+            error!("Error in allocating PCR banks: {}.", ret);
             Err(ret)
         }
     }
 
-    ///////////////////////////////////////////////////////////////////////////
-    /// TPM Resource Section
-    ///////////////////////////////////////////////////////////////////////////
-
-    /// Set the authentication value for a given object handle in the ESYS context.
+    /// Generate a quote on the selected PCRs
     ///
     /// # Constraints
-    /// * `auth_value` must be at most 64 elements long
+    /// * `qualifying_data` must be at most 64 elements long
     ///
     /// # Errors
-    /// * if `auth_value` is larger than the limit, a `WrongParamSize` wrapper error is returned
-    pub fn tr_set_auth(&mut self, handle: ESYS_TR, auth_value: &[u8]) -> Result<()> {
-        let auth = wrap_buffer!(auth_value, TPM2B_AUTH, 64);
-        let ret = unsafe { Esys_TR_SetAuth(self.mut_context(), handle, &auth) };
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn quote(
+        &mut self,
+        signing_key_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+        pcr_selection: PcrSelections,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut quoted = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_Quote(
+                self.mut_context(),
+                signing_key_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                &pcr_selection.into(),
+                &mut quoted,
+                &mut signature,
+            )
+        };
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
-            Ok(())
+            let quoted = unsafe { MBox::<TPM2B_ATTEST>::from_raw(quoted) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*quoted, unsafe { Signature::try_from(*signature)? }))
         } else {
+            error!("Error in quoting PCR: {}", ret);
             Err(ret)
         }
     }
 
-    /// Retrieve the name of an object from the object handle
-    pub fn tr_get_name(&mut self, handle: ESYS_TR) -> Result<TPM2B_NAME> {
-        let mut name = null_mut();
-        let ret = unsafe { Esys_TR_GetName(self.mut_context(), handle, &mut name) };
+    /// Certify that an object is loaded in the TPM, signing its name with a key.
+    ///
+    /// Both the object and the signing key need to be authorized, with the first and second
+    /// sessions respectively. The returned `TPM2B_ATTEST` can be parsed as an `Attest` holding
+    /// an `AttestInfo::Certify`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn certify(
+        &mut self,
+        object_handle: ESYS_TR,
+        signing_key_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut certify_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_Certify(
+                self.mut_context(),
+                object_handle,
+                signing_key_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                &mut certify_info,
+                &mut signature,
+            )
+        };
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
-            let name = unsafe { MBox::<TPM2B_NAME>::from_raw(name) };
-            Ok(*name)
+            let certify_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(certify_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*certify_info, unsafe { Signature::try_from(*signature)? }))
         } else {
-            error!("Error in getting name: {}.", ret);
+            error!("Error in certifying object: {}", ret);
             Err(ret)
         }
     }
 
-    /// Set the given attributes on a given session.
-    pub fn tr_sess_set_attributes(
+    /// Certify that an object was created by the TPM, with the creation data described by
+    /// `creation_hash`.
+    ///
+    /// The creation hash and ticket are the ones returned, in a `KeyCreationData`, when the object
+    /// was created. The returned `TPM2B_ATTEST` can be parsed as an `Attest` holding an
+    /// `AttestInfo::Creation`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    /// * `creation_hash` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data or creation hash provided is too long, a `WrongParamSize` wrapper
+    /// error will be returned
+    pub fn certify_creation(
         &mut self,
-        handle: ESYS_TR,
-        attributes: TpmaSession,
-    ) -> Result<()> {
+        signing_key_handle: ESYS_TR,
+        object_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        creation_hash: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+        creation_ticket: CreationTicket,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut certify_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+        let creation_hash = wrap_buffer!(creation_hash, TPM2B_DIGEST, 64);
+        let creation_ticket = TPMT_TK_CREATION::try_from(creation_ticket)?;
+
         let ret = unsafe {
-            Esys_TRSess_SetAttributes(
+            Esys_CertifyCreation(
                 self.mut_context(),
-                handle,
-                attributes.flags(),
-                attributes.mask(),
+                signing_key_handle,
+                object_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &creation_hash,
+                &signing_scheme,
+                &creation_ticket,
+                &mut certify_info,
+                &mut signature,
             )
         };
         let ret = Error::from_tss_rc(ret);
+
         if ret.is_success() {
-            Ok(())
+            let certify_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(certify_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*certify_info, unsafe { Signature::try_from(*signature)? }))
         } else {
+            error!("Error in certifying object creation: {}", ret);
             Err(ret)
         }
     }
 
-    /// Get session attribute flags.
-    pub fn tr_sess_get_attributes(&mut self, handle: ESYS_TR) -> Result<TpmaSession> {
-        let mut flags: TPMA_SESSION = 0;
-        let ret = unsafe { Esys_TRSess_GetAttributes(self.mut_context(), handle, &mut flags) };
-        let ret = Error::from_tss_rc(ret);
-        if ret.is_success() {
-            Ok(TpmaSessionBuilder::new().with_flag(flags).build())
-        } else {
-            Err(ret)
+    /// Get a signed attestation of the current time and clock of the TPM.
+    ///
+    /// Both the privacy administrator, `ESYS_TR_RH_ENDORSEMENT`, and the signing key need to be
+    /// authorized, with the first and second sessions respectively. The returned `TPM2B_ATTEST`
+    /// can be parsed as an `Attest` holding an `AttestInfo::Time`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn get_time(
+        &mut self,
+        signing_key_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut time_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_GetTime(
+                self.mut_context(),
+                ESYS_TR_RH_ENDORSEMENT,
+                signing_key_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                &mut time_info,
+                &mut signature,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let time_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(time_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*time_info, unsafe { Signature::try_from(*signature)? }))
+        } else {
+            error!("Error in getting the attested time: {}", ret);
+            Err(ret)
+        }
+    }
+
+    /// Get a signed attestation of the audit digest of a session.
+    ///
+    /// Both the privacy administrator, `ESYS_TR_RH_ENDORSEMENT`, and the signing key need to be
+    /// authorized, with the first and second sessions respectively. The returned `TPM2B_ATTEST`
+    /// can be parsed as an `Attest` holding an `AttestInfo::SessionAudit`, whose digest can be
+    /// checked with an `AuditDigestCalculator`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn get_session_audit_digest(
+        &mut self,
+        signing_key_handle: ESYS_TR,
+        audit_session: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut audit_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_GetSessionAuditDigest(
+                self.mut_context(),
+                ESYS_TR_RH_ENDORSEMENT,
+                signing_key_handle,
+                audit_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                &mut audit_info,
+                &mut signature,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let audit_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(audit_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*audit_info, unsafe { Signature::try_from(*signature)? }))
+        } else {
+            error!("Error in getting the session audit digest: {}", ret);
+            Err(ret)
+        }
+    }
+
+    /// Get a signed attestation of the command audit digest, and reset it.
+    ///
+    /// Both the privacy administrator, `ESYS_TR_RH_ENDORSEMENT`, and the signing key need to be
+    /// authorized, with the first and second sessions respectively. The returned `TPM2B_ATTEST`
+    /// can be parsed as an `Attest` holding an `AttestInfo::CommandAudit`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn get_command_audit_digest(
+        &mut self,
+        signing_key_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+    ) -> Result<(TPM2B_ATTEST, Signature)> {
+        let mut audit_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_GetCommandAuditDigest(
+                self.mut_context(),
+                ESYS_TR_RH_ENDORSEMENT,
+                signing_key_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                &mut audit_info,
+                &mut signature,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let audit_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(audit_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*audit_info, unsafe { Signature::try_from(*signature)? }))
+        } else {
+            error!("Error in getting the command audit digest: {}", ret);
+            Err(ret)
+        }
+    }
+
+    /// Change the list of commands audited by the TPM, or the hashing algorithm of the command
+    /// audit digest.
+    ///
+    /// `auth_handle` must be `ESYS_TR_RH_OWNER` or `ESYS_TR_RH_PLATFORM`. When `audit_alg` is
+    /// provided, the lists of commands are ignored by the TPM: the algorithm and the commands
+    /// must be changed by separate calls.
+    ///
+    /// # Constraints
+    /// * `set_list` and `clear_list` must be at most 256 elements long
+    ///
+    /// # Errors
+    /// * if either list is too long, a `WrongParamSize` wrapper error will be returned
+    pub fn set_command_code_audit_status(
+        &mut self,
+        auth_handle: ESYS_TR,
+        audit_alg: Option<HashingAlgorithm>,
+        set_list: &[CommandCode],
+        clear_list: &[CommandCode],
+    ) -> Result<()> {
+        let audit_alg = match audit_alg {
+            Some(audit_alg) => audit_alg.into(),
+            None => constants::TPM2_ALG_NULL,
+        };
+        let set_list = wrap_command_codes(set_list)?;
+        let clear_list = wrap_command_codes(clear_list)?;
+
+        let ret = unsafe {
+            Esys_SetCommandCodeAuditStatus(
+                self.mut_context(),
+                auth_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                audit_alg,
+                &set_list,
+                &clear_list,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in setting the command audit status: {}", ret);
+            Err(ret)
+        }
+    }
+
+    // TODO: Should we really keep `num_bytes` as `u16`?
+    /// Get a number of random bytes from the TPM and return them.
+    ///
+    /// # Errors
+    /// * if converting `num_bytes` to `u16` fails, a `WrongParamSize` will be returned
+    pub fn get_random(&mut self, num_bytes: usize) -> Result<Vec<u8>> {
+        let mut buffer = null_mut();
+        let ret = unsafe {
+            Esys_GetRandom(
+                self.mut_context(),
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                num_bytes
+                    .try_into()
+                    .or_else(|_| Err(Error::local_error(ErrorKind::WrongParamSize)))?,
+                &mut buffer,
+            )
+        };
+
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let buffer = unsafe { MBox::from_raw(buffer) };
+            let mut random = buffer.buffer.to_vec();
+            random.truncate(buffer.size.try_into().unwrap()); // should not panic given the TryInto above
+            Ok(random)
+        } else {
+            error!("Error in flushing context: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Test if the given parameters are supported by the TPM.
+    ///
+    /// # Errors
+    /// * if any of the public parameters is not compatible with the TPM,
+    /// an `Err` containing the specific unmarshalling error will be returned.
+    pub fn test_parms(&mut self, parms: PublicParmsUnion) -> Result<()> {
+        let public_parms = TPMT_PUBLIC_PARMS {
+            type_: parms.object_type(),
+            parameters: parms.into(),
+        };
+        let ret = unsafe {
+            Esys_TestParms(
+                self.mut_context(),
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &public_parms,
+            )
+        };
+
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error while testing parameters: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Get a single page of the data of a capability.
+    ///
+    /// At most `property_count` items are returned, starting from `property`; the boolean returned
+    /// along with the data indicates whether the TPM has more data to report (`moreData`). The
+    /// typed methods below (e.g. `get_algorithms`) should be preferred as they take care of the
+    /// paging.
+    ///
+    /// # Errors
+    /// * if the TPM returns a capability which cannot be represented as `CapabilityData`, an
+    /// `UnsupportedParam` wrapper error is returned
+    pub fn get_capability(
+        &mut self,
+        capability: TPM2_CAP,
+        property: u32,
+        property_count: u32,
+    ) -> Result<(CapabilityData, bool)> {
+        let (capability_data, more_data) =
+            self.get_capability_page(capability, property, property_count)?;
+        Ok((CapabilityData::try_from(capability_data)?, more_data))
+    }
+
+    /// Get the algorithms implemented by the TPM.
+    pub fn get_algorithms(&mut self) -> Result<Vec<Algorithm>> {
+        match self.get_all_capability(
+            TPM2_CAP_ALGS,
+            capabilities::first_property(TPM2_CAP_ALGS),
+            TPM2_ALG_LAST.into(),
+        )? {
+            CapabilityData::Algorithms(algorithms) => Ok(algorithms),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    /// Get the handles of a given type currently present in the TPM.
+    pub fn get_handles(&mut self, handle_type: HandleType) -> Result<Vec<TPM2_HANDLE>> {
+        match self.get_all_capability(
+            TPM2_CAP_HANDLES,
+            handle_type.first_handle(),
+            handle_type.last_handle(),
+        )? {
+            CapabilityData::Handles(handles) => Ok(handles
+                .into_iter()
+                .filter(|handle| *handle <= handle_type.last_handle())
+                .collect()),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    /// Get the commands implemented by the TPM.
+    ///
+    /// Vendor-specific commands are not reported.
+    pub fn get_commands(&mut self) -> Result<Vec<CommandCode>> {
+        match self.get_all_capability(
+            TPM2_CAP_COMMANDS,
+            capabilities::first_property(TPM2_CAP_COMMANDS),
+            TPM2_CC_LAST,
+        )? {
+            CapabilityData::Commands(commands) => Ok(commands),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    /// Get the allocated PCR banks, along with the PCRs which are active in each of them.
+    pub fn get_pcr_banks(&mut self) -> Result<PcrSelections> {
+        match self.get_all_capability(TPM2_CAP_PCRS, 0, 0)? {
+            CapabilityData::AssignedPcrs(pcr_selections) => Ok(pcr_selections),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    /// Get the fixed properties of the TPM (`TPM2_PT_FIXED` group), as `(property, value)`
+    /// pairs.
+    pub fn get_fixed_properties(&mut self) -> Result<Vec<(TPM2_PT, u32)>> {
+        self.get_tpm_properties(TPM2_PT_FIXED)
+    }
+
+    /// Get the variable properties of the TPM (`TPM2_PT_VAR` group), as `(property, value)`
+    /// pairs.
+    pub fn get_variable_properties(&mut self) -> Result<Vec<(TPM2_PT, u32)>> {
+        self.get_tpm_properties(TPM2_PT_VAR)
+    }
+
+    /// Get the elliptic curves implemented by the TPM.
+    pub fn get_ecc_curves(&mut self) -> Result<Vec<EllipticCurve>> {
+        match self.get_all_capability(
+            TPM2_CAP_ECC_CURVES,
+            capabilities::first_property(TPM2_CAP_ECC_CURVES),
+            0xffff,
+        )? {
+            CapabilityData::EccCurves(curves) => Ok(curves),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    /// Function for invoking TPM2_Hash command.
+    ///
+    pub fn hash(
+        &mut self,
+        data: &[u8],
+        hashing_algorithm: HashingAlgorithm,
+        hierarchy: Hierarchy,
+    ) -> Result<(Vec<u8>, HashcheckTicket)> {
+        let data = wrap_buffer!(data, TPM2B_MAX_BUFFER, 1024);
+        let mut out_hash_ptr = null_mut();
+        let mut validation_ptr = null_mut();
+        let ret = unsafe {
+            Esys_Hash(
+                self.mut_context(),
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &data,
+                hashing_algorithm.into(),
+                hierarchy.rh(),
+                &mut out_hash_ptr,
+                &mut validation_ptr,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let out_hash = unsafe { MBox::<TPM2B_DIGEST>::from_raw(out_hash_ptr) };
+            let validation = unsafe { MBox::<TPMT_TK_HASHCHECK>::from_raw(validation_ptr) };
+            Ok((
+                out_hash.buffer[..out_hash.size as usize].to_vec(),
+                HashcheckTicket::try_from(*validation)?,
+            ))
+        } else {
+            error!("Error failed to peform hash operation: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Define an NV index in the TPM and return its object handle.
+    ///
+    /// The NV index is defined under the hierarchy given by `auth_handle` (e.g.
+    /// `ESYS_TR_RH_OWNER`) and gets `auth` as its authorization value. The index stays defined
+    /// after the context is dropped, but its object handle is then closed.
+    ///
+    /// # Constraints
+    /// * `auth` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `auth` is too long, a `WrongParamSize` wrapper error is returned
+    /// * if `public_info` cannot be converted to a `TPM2B_NV_PUBLIC`, a `WrongParamSize` wrapper
+    /// error is returned
+    pub fn nv_define_space(
+        &mut self,
+        auth_handle: ESYS_TR,
+        auth: &[u8],
+        public_info: &NvPublic,
+    ) -> Result<ESYS_TR> {
+        let auth = wrap_buffer!(auth, TPM2B_AUTH, 64);
+        let public_info = TPM2B_NV_PUBLIC::try_from(public_info.clone())?;
+        let mut nv_handle = ESYS_TR_NONE;
+        let ret = unsafe {
+            Esys_NV_DefineSpace(
+                self.mut_context(),
+                auth_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &auth,
+                &public_info,
+                &mut nv_handle,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let _ = self.closable_handles.insert(nv_handle);
+            Ok(nv_handle)
+        } else {
+            error!("Error in defining NV space: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Remove an NV index from the TPM.
+    ///
+    /// The object handle of the index is no longer valid after the call succeeds.
+    pub fn nv_undefine_space(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_UndefineSpace(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let _ = self.closable_handles.remove(&nv_index_handle);
+            Ok(())
+        } else {
+            error!("Error in undefining NV space: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Read the public area of an NV index.
+    ///
+    /// # Errors
+    /// * if the public area returned by the TPM cannot be converted to an `NvPublic`, an
+    /// `InvalidParam` wrapper error is returned
+    pub fn nv_read_public(&mut self, nv_index_handle: ESYS_TR) -> Result<NvPublic> {
+        let mut nv_public = null_mut();
+        let mut nv_name = null_mut();
+        let ret = unsafe {
+            Esys_NV_ReadPublic(
+                self.mut_context(),
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &mut nv_public,
+                &mut nv_name,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let _ = unsafe { MBox::<TPM2B_NAME>::from_raw(nv_name) };
+            let nv_public = unsafe { MBox::<TPM2B_NV_PUBLIC>::from_raw(nv_public) };
+            Ok(NvPublic::try_from(*nv_public)?)
+        } else {
+            error!("Error in reading NV public area: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Write data to an NV index, starting at `offset`.
+    ///
+    /// The data is split into chunks no larger than the TPM accepts (its TPM2_PT_NV_BUFFER_MAX
+    /// property), each chunk being written with a separate TPM2_NV_Write command. The
+    /// authorization for `auth_handle` (either the index itself or the owner/platform hierarchy,
+    /// depending on the attributes of the index) is taken from the sessions set on the context.
+    ///
+    /// # Errors
+    /// * if the data written would go past offset `0xFFFF`, a `WrongParamSize` wrapper error is
+    /// returned
+    pub fn nv_write(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        data: &[u8],
+        offset: u16,
+    ) -> Result<()> {
+        let _ = u16::try_from(usize::from(offset) + data.len())
+            .map_err(|_| Error::local_error(ErrorKind::WrongParamSize))?;
+        let mut offset = offset;
+        for chunk in data.chunks(self.nv_buffer_max()) {
+            let chunk_buffer =
+                wrap_buffer!(chunk, TPM2B_MAX_NV_BUFFER, TPM2_MAX_NV_BUFFER_SIZE as usize);
+            let ret = unsafe {
+                Esys_NV_Write(
+                    self.mut_context(),
+                    auth_handle,
+                    nv_index_handle,
+                    self.sessions.0,
+                    self.sessions.1,
+                    self.sessions.2,
+                    &chunk_buffer,
+                    offset,
+                )
+            };
+            let ret = Error::from_tss_rc(ret);
+            if !ret.is_success() {
+                error!("Error in writing NV index: {}.", ret);
+                return Err(ret);
+            }
+            offset += chunk_buffer.size; // cannot overflow given the check above
+        }
+        Ok(())
+    }
+
+    /// Read `size` bytes from an NV index, starting at `offset`.
+    ///
+    /// The read is split into as many TPM2_NV_Read commands as needed to stay within the limits
+    /// of the TPM (its TPM2_PT_NV_BUFFER_MAX property). The authorization for `auth_handle` is
+    /// taken from the sessions set on the context.
+    ///
+    /// # Errors
+    /// * if the data read would go past offset `0xFFFF`, a `WrongParamSize` wrapper error is
+    /// returned
+    pub fn nv_read(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        size: u16,
+        offset: u16,
+    ) -> Result<Vec<u8>> {
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| Error::local_error(ErrorKind::WrongParamSize))?;
+        let mut data = Vec::with_capacity(size.into());
+        let mut offset = offset;
+        // Cannot fail as the value is at most TPM2_MAX_NV_BUFFER_SIZE
+        let nv_buffer_max = u16::try_from(self.nv_buffer_max()).unwrap();
+        while offset < end {
+            let chunk_size = std::cmp::min(end - offset, nv_buffer_max);
+            let mut chunk = null_mut();
+            let ret = unsafe {
+                Esys_NV_Read(
+                    self.mut_context(),
+                    auth_handle,
+                    nv_index_handle,
+                    self.sessions.0,
+                    self.sessions.1,
+                    self.sessions.2,
+                    chunk_size,
+                    offset,
+                    &mut chunk,
+                )
+            };
+            let ret = Error::from_tss_rc(ret);
+            if !ret.is_success() {
+                error!("Error in reading NV index: {}.", ret);
+                return Err(ret);
+            }
+            let chunk = unsafe { MBox::<TPM2B_MAX_NV_BUFFER>::from_raw(chunk) };
+            let chunk_len = std::cmp::min(chunk.size, chunk_size);
+            if chunk_len == 0 {
+                // the TPM returned less data than requested, avoid looping forever
+                error!("Error in reading NV index: empty buffer returned.");
+                return Err(Error::local_error(ErrorKind::WrongParamSize));
+            }
+            data.extend_from_slice(&chunk.buffer[..usize::from(chunk_len)]);
+            offset += chunk_len;
+        }
+        Ok(data)
+    }
+
+    /// Increment the value of an NV counter index.
+    pub fn nv_increment(&mut self, auth_handle: ESYS_TR, nv_index_handle: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_Increment(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in incrementing NV counter: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// OR the given bits into the value of an NV bit field index.
+    pub fn nv_set_bits(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        bits: u64,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_SetBits(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                bits,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in setting NV bits: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Extend the value of an NV extend index with the given data.
+    ///
+    /// # Errors
+    /// * if `data` does not fit into a `TPM2B_MAX_NV_BUFFER`, a `WrongParamSize` wrapper error is
+    /// returned
+    pub fn nv_extend(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        data: &[u8],
+    ) -> Result<()> {
+        let data = wrap_buffer!(data, TPM2B_MAX_NV_BUFFER, TPM2_MAX_NV_BUFFER_SIZE as usize);
+        let ret = unsafe {
+            Esys_NV_Extend(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &data,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in extending NV index: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Prevent further writes to an NV index until the next TPM Reset or TPM Restart.
+    ///
+    /// The index must have either the `write_stclear` or the `write_define` attribute set.
+    pub fn nv_write_lock(&mut self, auth_handle: ESYS_TR, nv_index_handle: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_WriteLock(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in write locking NV index: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Prevent further reads of an NV index until the next TPM Reset or TPM Restart.
+    ///
+    /// The index must have the `read_stclear` attribute set.
+    pub fn nv_read_lock(&mut self, auth_handle: ESYS_TR, nv_index_handle: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_ReadLock(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in read locking NV index: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Prevent further writes to all NV indices that have the `global_lock` attribute set, until
+    /// the next TPM Reset or TPM Restart.
+    ///
+    /// `auth_handle` must be either `ESYS_TR_RH_OWNER` or `ESYS_TR_RH_PLATFORM`.
+    pub fn nv_global_write_lock(&mut self, auth_handle: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_NV_GlobalWriteLock(
+                self.mut_context(),
+                auth_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in global write locking NV: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Change the authorization value of an NV index.
+    ///
+    /// This command requires ADMIN role authorization, meaning that it can only be authorized
+    /// with a policy session satisfying the policy of the index.
+    ///
+    /// # Constraints
+    /// * `new_auth` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `new_auth` is too long, a `WrongParamSize` wrapper error is returned
+    pub fn nv_change_auth(&mut self, nv_index_handle: ESYS_TR, new_auth: &[u8]) -> Result<()> {
+        let new_auth = wrap_buffer!(new_auth, TPM2B_AUTH, 64);
+        let ret = unsafe {
+            Esys_NV_ChangeAuth(
+                self.mut_context(),
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &new_auth,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in changing NV index auth: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Certify the contents of an NV index.
+    ///
+    /// `size` bytes of the index, starting at `offset`, are included in an attestation structure
+    /// signed with the key given by `signing_key_handle`. Both the signing key and the
+    /// `auth_handle` need to be authorized, so two sessions have to be set on the context. The
+    /// attestation is returned both as the raw `TPM2B_ATTEST` that was signed, for verifying the
+    /// signature, and parsed as an `Attest`.
+    ///
+    /// # Constraints
+    /// * `qualifying_data` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the qualifying data provided is too long, a `WrongParamSize` wrapper error will be returned
+    /// * if the returned attestation cannot be parsed, an `InvalidParam` wrapper error is returned
+    #[allow(clippy::too_many_arguments)]
+    pub fn nv_certify(
+        &mut self,
+        signing_key_handle: ESYS_TR,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        qualifying_data: &[u8],
+        signing_scheme: TPMT_SIG_SCHEME,
+        size: u16,
+        offset: u16,
+    ) -> Result<(TPM2B_ATTEST, Attest, Signature)> {
+        let mut certify_info = null_mut();
+        let mut signature = null_mut();
+        let qualifying_data = wrap_buffer!(qualifying_data, TPM2B_DATA, 64);
+
+        let ret = unsafe {
+            Esys_NV_Certify(
+                self.mut_context(),
+                signing_key_handle,
+                auth_handle,
+                nv_index_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &qualifying_data,
+                &signing_scheme,
+                size,
+                offset,
+                &mut certify_info,
+                &mut signature,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let certify_info = unsafe { MBox::<TPM2B_ATTEST>::from_raw(certify_info) };
+            let signature = unsafe { MBox::from_raw(signature) };
+            Ok((*certify_info, Attest::try_from(*certify_info)?, unsafe {
+                Signature::try_from(*signature)?
+            }))
+        } else {
+            error!("Error in certifying NV index: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Cause conditional gating of a policy based on the values of the selected PCRs.
+    ///
+    /// `pcr_digest` is the digest of the selected PCR values; if empty, the TPM uses the digest
+    /// of the current values of the PCRs instead, in trial and policy sessions alike.
+    ///
+    /// # Constraints
+    /// * `pcr_digest` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the PCR digest is too long, a `WrongParamSize` wrapper error is returned
+    pub fn policy_pcr(
+        &mut self,
+        policy_session: ESYS_TR,
+        pcr_digest: &[u8],
+        pcr_selections: PcrSelections,
+    ) -> Result<()> {
+        let pcr_digest = wrap_buffer!(pcr_digest, TPM2B_DIGEST, 64);
+        let ret = unsafe {
+            Esys_PolicyPCR(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &pcr_digest,
+                &pcr_selections.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy PCR: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Bind the policy to the authorization value of the authorized entity, to be provided
+    /// through an HMAC computed with the session key.
+    pub fn policy_auth_value(&mut self, policy_session: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyAuthValue(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy auth value: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Bind the policy to the authorization value of the authorized entity, to be provided in
+    /// clear text.
+    ///
+    /// The resulting policy digest is the same as the one produced by `policy_auth_value`.
+    pub fn policy_password(&mut self, policy_session: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyPassword(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy password: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Get the current policy digest of the session.
+    pub fn policy_get_digest(&mut self, policy_session: ESYS_TR) -> Result<Vec<u8>> {
+        let mut policy_digest = null_mut();
+        let ret = unsafe {
+            Esys_PolicyGetDigest(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &mut policy_digest,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let policy_digest = unsafe { MBox::<TPM2B_DIGEST>::from_raw(policy_digest) };
+            Ok(policy_digest.buffer[..policy_digest.size as usize].to_vec())
+        } else {
+            error!("Error in getting policy digest: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Reset the policy digest of the session to its initial state.
+    pub fn policy_restart(&mut self, policy_session: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyRestart(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in restarting policy: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Include a signed authorization in the policy.
+    ///
+    /// The signature in `auth` must be computed by the owner of the key loaded at `auth_object`
+    /// over the digest of `nonce_tpm || expiration || cp_hash || policy_ref`, with `expiration`
+    /// encoded as the negated number of seconds of the validity period (or zero).
+    ///
+    /// If an `expiration` is provided, the authorization is only valid for that amount of time
+    /// and a ticket is requested, which can later be used with `policy_ticket`. The returned tuple
+    /// contains the timeout value computed by the TPM along with that ticket.
+    ///
+    /// # Constraints
+    /// * `nonce_tpm`, `cp_hash` and `policy_ref` must be at most 64 elements long
+    /// * `expiration` must be at least one second and at most `i32::max_value()` seconds; it is
+    /// rounded down to a whole number of seconds
+    ///
+    /// # Errors
+    /// * if any of the parameters above is too large, or `expiration` too short, a
+    /// `WrongParamSize` wrapper error is returned
+    /// * if the signature cannot be converted to a `TPMT_SIGNATURE`, an error is returned
+    #[allow(clippy::too_many_arguments)]
+    pub fn policy_signed(
+        &mut self,
+        auth_object: ESYS_TR,
+        policy_session: ESYS_TR,
+        nonce_tpm: &[u8],
+        cp_hash: &[u8],
+        policy_ref: &[u8],
+        expiration: Option<Duration>,
+        auth: Signature,
+    ) -> Result<(Vec<u8>, Option<AuthTicket>)> {
+        let nonce_tpm = wrap_buffer!(nonce_tpm, TPM2B_NONCE, 64);
+        let cp_hash = wrap_buffer!(cp_hash, TPM2B_DIGEST, 64);
+        let policy_ref = wrap_buffer!(policy_ref, TPM2B_NONCE, 64);
+        let expiration = policy_expiration(expiration)?;
+        let auth = TPMT_SIGNATURE::try_from(auth)?;
+        let mut timeout = null_mut();
+        let mut policy_ticket = null_mut();
+        let ret = unsafe {
+            Esys_PolicySigned(
+                self.mut_context(),
+                auth_object,
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &nonce_tpm,
+                &cp_hash,
+                &policy_ref,
+                expiration,
+                &auth,
+                &mut timeout,
+                &mut policy_ticket,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let timeout = unsafe { MBox::<TPM2B_TIMEOUT>::from_raw(timeout) };
+            let policy_ticket = unsafe { MBox::<TPMT_TK_AUTH>::from_raw(policy_ticket) };
+            Ok((
+                timeout.buffer[..timeout.size as usize].to_vec(),
+                policy_auth_ticket(*policy_ticket)?,
+            ))
+        } else {
+            error!("Error in policy signed: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Include the authorization of another entity in the policy.
+    ///
+    /// The authorization for `auth_handle` is provided through the sessions set on the context.
+    ///
+    /// If an `expiration` is provided, the authorization is only valid for that amount of time
+    /// and a ticket is requested, which can later be used with `policy_ticket`. The returned tuple
+    /// contains the timeout value computed by the TPM along with that ticket.
+    ///
+    /// # Constraints
+    /// * `nonce_tpm`, `cp_hash` and `policy_ref` must be at most 64 elements long
+    /// * `expiration` must be at least one second and at most `i32::max_value()` seconds; it is
+    /// rounded down to a whole number of seconds
+    ///
+    /// # Errors
+    /// * if any of the parameters above is too large, or `expiration` too short, a
+    /// `WrongParamSize` wrapper error is returned
+    #[allow(clippy::too_many_arguments)]
+    pub fn policy_secret(
+        &mut self,
+        auth_handle: ESYS_TR,
+        policy_session: ESYS_TR,
+        nonce_tpm: &[u8],
+        cp_hash: &[u8],
+        policy_ref: &[u8],
+        expiration: Option<Duration>,
+    ) -> Result<(Vec<u8>, Option<AuthTicket>)> {
+        let nonce_tpm = wrap_buffer!(nonce_tpm, TPM2B_NONCE, 64);
+        let cp_hash = wrap_buffer!(cp_hash, TPM2B_DIGEST, 64);
+        let policy_ref = wrap_buffer!(policy_ref, TPM2B_NONCE, 64);
+        let expiration = policy_expiration(expiration)?;
+        let mut timeout = null_mut();
+        let mut policy_ticket = null_mut();
+        let ret = unsafe {
+            Esys_PolicySecret(
+                self.mut_context(),
+                auth_handle,
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &nonce_tpm,
+                &cp_hash,
+                &policy_ref,
+                expiration,
+                &mut timeout,
+                &mut policy_ticket,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let timeout = unsafe { MBox::<TPM2B_TIMEOUT>::from_raw(timeout) };
+            let policy_ticket = unsafe { MBox::<TPMT_TK_AUTH>::from_raw(policy_ticket) };
+            Ok((
+                timeout.buffer[..timeout.size as usize].to_vec(),
+                policy_auth_ticket(*policy_ticket)?,
+            ))
+        } else {
+            error!("Error in policy secret: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Include a previously obtained signed or secret authorization in the policy, by means of
+    /// the ticket returned by `policy_signed` or `policy_secret`.
+    ///
+    /// `timeout`, `cp_hash` and `policy_ref` must be the values used when the ticket was
+    /// produced, and `auth_name` is the name of the object which provided the authorization.
+    ///
+    /// # Constraints
+    /// * `timeout`, `cp_hash` and `policy_ref` must be at most 64 elements long
+    /// * `auth_name` must be at most 68 elements long
+    ///
+    /// # Errors
+    /// * if any of the parameters above is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_ticket(
+        &mut self,
+        policy_session: ESYS_TR,
+        timeout: &[u8],
+        cp_hash: &[u8],
+        policy_ref: &[u8],
+        auth_name: &[u8],
+        ticket: AuthTicket,
+    ) -> Result<()> {
+        let timeout = wrap_buffer!(timeout, TPM2B_TIMEOUT, 64);
+        let cp_hash = wrap_buffer!(cp_hash, TPM2B_DIGEST, 64);
+        let policy_ref = wrap_buffer!(policy_ref, TPM2B_NONCE, 64);
+        let auth_name = wrap_name(auth_name)?;
+        let ticket = TPMT_TK_AUTH::try_from(ticket)?;
+        let ret = unsafe {
+            Esys_PolicyTicket(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &timeout,
+                &cp_hash,
+                &policy_ref,
+                &auth_name,
+                &ticket,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy ticket: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Allow the policy to be satisfied by any one of several alternative policies.
+    ///
+    /// If the current policy digest of the session is one of `digests`, it is replaced by the
+    /// digest of the list; a trial session accepts any current digest.
+    ///
+    /// # Constraints
+    /// * `digests` must contain between 2 and 8 digests
+    /// * each digest must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if the number or the size of the digests is outside the limits above, a `WrongParamSize`
+    /// wrapper error is returned
+    pub fn policy_or(&mut self, policy_session: ESYS_TR, digests: &[&[u8]]) -> Result<()> {
+        if digests.len() < 2 || digests.len() > 8 {
+            error!("Error: PolicyOR requires between 2 and 8 digests.");
+            return Err(Error::local_error(ErrorKind::WrongParamSize));
+        }
+        let mut digest_list: TPML_DIGEST = Default::default();
+        for (index, digest) in digests.iter().enumerate() {
+            digest_list.digests[index] = wrap_buffer!(digest, TPM2B_DIGEST, 64);
+        }
+        digest_list.count = digests.len().try_into().unwrap(); // should not fail given the check above
+        let ret = unsafe {
+            Esys_PolicyOR(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &digest_list,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy OR: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Replace the current policy digest of the session with one authorized by the owner of a
+    /// signing key.
+    ///
+    /// The current policy digest must be `approved_policy`, and `check_ticket` must be the ticket
+    /// returned by `verify_signature` for a signature made by the key named `key_sign` over the
+    /// digest of `approved_policy || policy_ref`. A trial session does not check the ticket.
+    ///
+    /// # Constraints
+    /// * `approved_policy` and `policy_ref` must be at most 64 elements long
+    /// * `key_sign` must be at most 68 elements long
+    ///
+    /// # Errors
+    /// * if any of the parameters above is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_authorize(
+        &mut self,
+        policy_session: ESYS_TR,
+        approved_policy: &[u8],
+        policy_ref: &[u8],
+        key_sign: &[u8],
+        check_ticket: VerifiedTicket,
+    ) -> Result<()> {
+        let approved_policy = wrap_buffer!(approved_policy, TPM2B_DIGEST, 64);
+        let policy_ref = wrap_buffer!(policy_ref, TPM2B_NONCE, 64);
+        let key_sign = wrap_name(key_sign)?;
+        let check_ticket = TPMT_TK_VERIFIED::try_from(check_ticket)?;
+        let ret = unsafe {
+            Esys_PolicyAuthorize(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &approved_policy,
+                &policy_ref,
+                &key_sign,
+                &check_ticket,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy authorize: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Cause conditional gating of a policy based on the content of an NV index.
+    ///
+    /// The policy is satisfied if `operation` holds between the data of the index starting at
+    /// `offset` (operand A) and `operand_b`. The index is read with the authorization of
+    /// `auth_handle`, which can be the index itself or a hierarchy.
+    ///
+    /// # Constraints
+    /// * `operand_b` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `operand_b` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_nv(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        policy_session: ESYS_TR,
+        operand_b: &[u8],
+        offset: u16,
+        operation: ComparisonOperation,
+    ) -> Result<()> {
+        let operand_b = wrap_buffer!(operand_b, TPM2B_OPERAND, 64);
+        let ret = unsafe {
+            Esys_PolicyNV(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &operand_b,
+                offset,
+                operation.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy NV: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Cause conditional gating of a policy based on the TPM clock and counters.
+    ///
+    /// The policy is satisfied if `operation` holds between the marshalled `TPMS_TIME_INFO`
+    /// structure of the TPM starting at `offset` (operand A) and `operand_b`. The fields of the
+    /// structure are found at the following offsets: `time` at 0, `clock` at 8, `resetCount` at
+    /// 16, `restartCount` at 20 and `safe` at 24.
+    ///
+    /// # Constraints
+    /// * `operand_b` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `operand_b` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_counter_timer(
+        &mut self,
+        policy_session: ESYS_TR,
+        operand_b: &[u8],
+        offset: u16,
+        operation: ComparisonOperation,
+    ) -> Result<()> {
+        let operand_b = wrap_buffer!(operand_b, TPM2B_OPERAND, 64);
+        let ret = unsafe {
+            Esys_PolicyCounterTimer(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &operand_b,
+                offset,
+                operation.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy counter timer: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Cause conditional gating of a policy based on whether the NV index it authorizes has been
+    /// written.
+    pub fn policy_nv_written(&mut self, policy_session: ESYS_TR, written_set: bool) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyNvWritten(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                written_set.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy NV written: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Replace the current policy digest of the session with the policy stored in an NV index.
+    ///
+    /// The current policy digest must match the digest stored in the index, preceded by its
+    /// hashing algorithm identifier. The index is read with the authorization of `auth_handle`,
+    /// which can be the index itself or a hierarchy.
+    pub fn policy_authorize_nv(
+        &mut self,
+        auth_handle: ESYS_TR,
+        nv_index_handle: ESYS_TR,
+        policy_session: ESYS_TR,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyAuthorizeNV(
+                self.mut_context(),
+                auth_handle,
+                nv_index_handle,
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy authorize NV: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to the authorization of a single command.
+    pub fn policy_command_code(
+        &mut self,
+        policy_session: ESYS_TR,
+        code: CommandCode,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyCommandCode(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                code.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy command code: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to commands issued from one of the selected localities.
+    pub fn policy_locality(
+        &mut self,
+        policy_session: ESYS_TR,
+        locality: LocalityAttributes,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyLocality(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                locality.0,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy locality: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Require physical presence to be asserted when the policy is used.
+    pub fn policy_physical_presence(&mut self, policy_session: ESYS_TR) -> Result<()> {
+        let ret = unsafe {
+            Esys_PolicyPhysicalPresence(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy physical presence: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to the authorization of a command with specific parameters.
+    ///
+    /// `cp_hash` is the digest of the command code, the names of its handles and its parameters,
+    /// which can be computed with `utils::policy::cp_hash`.
+    ///
+    /// # Constraints
+    /// * `cp_hash` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `cp_hash` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_cp_hash(&mut self, policy_session: ESYS_TR, cp_hash: &[u8]) -> Result<()> {
+        let cp_hash = wrap_buffer!(cp_hash, TPM2B_DIGEST, 64);
+        let ret = unsafe {
+            Esys_PolicyCpHash(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &cp_hash,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy cpHash: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to the authorization of a command on specific entities.
+    ///
+    /// `name_hash` is the digest of the names of the handles of the command, which can be
+    /// computed with `utils::policy::name_hash`.
+    ///
+    /// # Constraints
+    /// * `name_hash` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `name_hash` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_name_hash(&mut self, policy_session: ESYS_TR, name_hash: &[u8]) -> Result<()> {
+        let name_hash = wrap_buffer!(name_hash, TPM2B_DIGEST, 64);
+        let ret = unsafe {
+            Esys_PolicyNameHash(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &name_hash,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy nameHash: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to the duplication of an object to a specific new parent.
+    ///
+    /// If `include_object` is set, the policy digest is also bound to the name of the duplicated
+    /// object; otherwise a policy computed with it can be used to duplicate any object to the new
+    /// parent.
+    ///
+    /// # Constraints
+    /// * `object_name` and `new_parent_name` must be at most 68 elements long
+    ///
+    /// # Errors
+    /// * if any of the names is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_duplication_select(
+        &mut self,
+        policy_session: ESYS_TR,
+        object_name: &[u8],
+        new_parent_name: &[u8],
+        include_object: bool,
+    ) -> Result<()> {
+        let object_name = wrap_name(object_name)?;
+        let new_parent_name = wrap_name(new_parent_name)?;
+        let ret = unsafe {
+            Esys_PolicyDuplicationSelect(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &object_name,
+                &new_parent_name,
+                include_object.into(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy duplication select: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Restrict the policy to the creation of objects with a specific template.
+    ///
+    /// `template_hash` is the digest of the marshalled public area used as template.
+    ///
+    /// # Constraints
+    /// * `template_hash` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `template_hash` is too large, a `WrongParamSize` wrapper error is returned
+    pub fn policy_template(&mut self, policy_session: ESYS_TR, template_hash: &[u8]) -> Result<()> {
+        let template_hash = wrap_buffer!(template_hash, TPM2B_DIGEST, 64);
+        let ret = unsafe {
+            Esys_PolicyTemplate(
+                self.mut_context(),
+                policy_session,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &template_hash,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            Ok(())
+        } else {
+            error!("Error in policy template: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    ///////////////////////////////////////////////////////////////////////////
+    /// TPM Resource Section
+    ///////////////////////////////////////////////////////////////////////////
+
+    /// Set the authentication value for a given object handle in the ESYS context.
+    ///
+    /// # Constraints
+    /// * `auth_value` must be at most 64 elements long
+    ///
+    /// # Errors
+    /// * if `auth_value` is larger than the limit, a `WrongParamSize` wrapper error is returned
+    pub fn tr_set_auth(&mut self, handle: ESYS_TR, auth_value: &[u8]) -> Result<()> {
+        let auth = wrap_buffer!(auth_value, TPM2B_AUTH, 64);
+        let ret = unsafe { Esys_TR_SetAuth(self.mut_context(), handle, &auth) };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            Ok(())
+        } else {
+            Err(ret)
+        }
+    }
+
+    /// Retrieve the name of an object from the object handle
+    pub fn tr_get_name(&mut self, handle: ESYS_TR) -> Result<TPM2B_NAME> {
+        let mut name = null_mut();
+        let ret = unsafe { Esys_TR_GetName(self.mut_context(), handle, &mut name) };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let name = unsafe { MBox::<TPM2B_NAME>::from_raw(name) };
+            Ok(*name)
+        } else {
+            error!("Error in getting name: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Set the given attributes on a given session.
+    pub fn tr_sess_set_attributes(
+        &mut self,
+        handle: ESYS_TR,
+        attributes: TpmaSession,
+    ) -> Result<()> {
+        let ret = unsafe {
+            Esys_TRSess_SetAttributes(
+                self.mut_context(),
+                handle,
+                attributes.flags(),
+                attributes.mask(),
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            Ok(())
+        } else {
+            Err(ret)
+        }
+    }
+
+    /// Get session attribute flags.
+    pub fn tr_sess_get_attributes(&mut self, handle: ESYS_TR) -> Result<TpmaSession> {
+        let mut flags: TPMA_SESSION = 0;
+        let ret = unsafe { Esys_TRSess_GetAttributes(self.mut_context(), handle, &mut flags) };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            Ok(TpmaSessionBuilder::new().with_flag(flags).build())
+        } else {
+            Err(ret)
+        }
+    }
+
+    /// Get the most recent nonce generated by the TPM for a session.
+    ///
+    /// This value is needed, for example, to compute the authorization signed for
+    /// `policy_signed`.
+    pub fn tr_sess_get_nonce_tpm(&mut self, handle: ESYS_TR) -> Result<Vec<u8>> {
+        let mut nonce = null_mut();
+        let ret = unsafe { Esys_TRSess_GetNonceTPM(self.mut_context(), handle, &mut nonce) };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let nonce = unsafe { MBox::<TPM2B_NONCE>::from_raw(nonce) };
+            Ok(nonce.buffer[..nonce.size as usize].to_vec())
+        } else {
+            error!("Error in getting TPM nonce: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Create an ESYS object handle for an entity already present in the TPM, given its TPM
+    /// handle: a persistent object (e.g. `0x8100_0001`), an NV index or a PCR.
+    ///
+    /// The entity is not flushed when the context is dropped, only its object handle is closed.
+    pub fn tr_from_tpm_public(&mut self, tpm_handle: TPM2_HANDLE) -> Result<ESYS_TR> {
+        let mut object = ESYS_TR_NONE;
+        let ret = unsafe {
+            Esys_TR_FromTPMPublic(
+                self.mut_context(),
+                tpm_handle,
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                &mut object,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let _ = self.closable_handles.insert(object);
+            Ok(object)
+        } else {
+            error!("Error in creating handle from TPM public: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    /// Release an ESYS object handle without affecting the entity it refers to in the TPM.
+    ///
+    /// The handle is no longer valid after the call succeeds.
+    pub fn tr_close(&mut self, handle: ESYS_TR) -> Result<()> {
+        let mut esys_handle = handle;
+        let ret = unsafe { Esys_TR_Close(self.mut_context(), &mut esys_handle) };
+        let ret = Error::from_tss_rc(ret);
+        if ret.is_success() {
+            let _ = self.open_handles.remove(&handle);
+            let _ = self.closable_handles.remove(&handle);
+            Ok(())
+        } else {
+            error!("Error in closing handle: {}.", ret);
+            Err(ret)
+        }
+    }
+
+    // Get the properties of a group of TPM properties, starting at `group`.
+    fn get_tpm_properties(&mut self, group: TPM2_PT) -> Result<Vec<(TPM2_PT, u32)>> {
+        let last_property = group + TPM2_PT_GROUP - 1;
+        match self.get_all_capability(TPM2_CAP_TPM_PROPERTIES, group, last_property)? {
+            CapabilityData::TpmProperties(properties) => Ok(properties
+                .into_iter()
+                .filter(|(property, _)| *property <= last_property)
+                .collect()),
+            _ => Err(unexpected_capability()),
+        }
+    }
+
+    // Get the maximum number of bytes in a single NV read or write: the TPM2_PT_NV_BUFFER_MAX
+    // property of the TPM, capped to the size of a `TPM2B_MAX_NV_BUFFER`. The property is only
+    // read once; `NV_MAX_BUFFER_CHUNK` is used if the TPM does not report it.
+    fn nv_buffer_max(&mut self) -> usize {
+        if let Some(nv_buffer_max) = self.nv_buffer_max {
+            return nv_buffer_max;
+        }
+        // TPM2_GetCapability does not take the authorization sessions set for the NV commands
+        let sessions = self.sessions;
+        self.sessions = (ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE);
+        let capability_data =
+            self.get_capability_page(TPM2_CAP_TPM_PROPERTIES, TPM2_PT_NV_BUFFER_MAX, 1);
+        self.sessions = sessions;
+        let property = match capability_data.and_then(|(data, _)| CapabilityData::try_from(data)) {
+            Ok(CapabilityData::TpmProperties(properties)) => properties
+                .into_iter()
+                .find(|(property, value)| *property == TPM2_PT_NV_BUFFER_MAX && *value > 0)
+                .map(|(_, value)| value),
+            _ => None,
+        };
+        let nv_buffer_max = match property {
+            Some(value) => std::cmp::min(value as usize, TPM2_MAX_NV_BUFFER_SIZE as usize),
+            None => NV_MAX_BUFFER_CHUNK,
+        };
+        self.nv_buffer_max = Some(nv_buffer_max);
+        nv_buffer_max
+    }
+
+    // Get the data of a capability from `first_property` onwards, reading as many pages as the
+    // TPM reports, until `last_property` is reached.
+    fn get_all_capability(
+        &mut self,
+        capability: TPM2_CAP,
+        first_property: u32,
+        last_property: u32,
+    ) -> Result<CapabilityData> {
+        let property_count = capabilities::max_property_count(capability);
+        let (capability_data, mut more_data) =
+            self.get_capability_page(capability, first_property, property_count)?;
+        let mut next_property = capabilities::next_property(&capability_data)?;
+        let mut all_data = CapabilityData::try_from(capability_data)?;
+        while more_data {
+            let property = match next_property {
+                Some(property) if property <= last_property => property,
+                _ => break,
+            };
+            let (capability_data, more) =
+                self.get_capability_page(capability, property, property_count)?;
+            next_property = capabilities::next_property(&capability_data)?;
+            more_data = more;
+            all_data.append(CapabilityData::try_from(capability_data)?)?;
+        }
+        Ok(all_data)
+    }
+
+    fn get_capability_page(
+        &mut self,
+        capability: TPM2_CAP,
+        property: u32,
+        property_count: u32,
+    ) -> Result<(TPMS_CAPABILITY_DATA, bool)> {
+        let mut more_data = 0;
+        let mut capability_data = null_mut();
+        let ret = unsafe {
+            Esys_GetCapability(
+                self.mut_context(),
+                self.sessions.0,
+                self.sessions.1,
+                self.sessions.2,
+                capability,
+                property,
+                property_count,
+                &mut more_data,
+                &mut capability_data,
+            )
+        };
+        let ret = Error::from_tss_rc(ret);
+
+        if ret.is_success() {
+            let capability_data = unsafe { MBox::from_raw(capability_data) };
+            Ok((*capability_data, more_data != 0))
+        } else {
+            error!("Error in getting capability: {}", ret);
+            Err(ret)
         }
     }
 
@@ -1011,6 +3056,80 @@ impl Context {
     }
 }
 
+// Convert the expiration of a policy authorization to the value expected by the TPM; a negative
+// value is used in order to also get a ticket back. Zero stands for no expiration, so durations
+// shorter than a second are rejected.
+fn policy_expiration(expiration: Option<Duration>) -> Result<INT32> {
+    match expiration {
+        None => Ok(0),
+        Some(expiration) => {
+            let seconds: INT32 = expiration
+                .as_secs()
+                .try_into()
+                .map_err(|_| Error::local_error(ErrorKind::WrongParamSize))?;
+            if seconds == 0 {
+                error!("Error: policy expiration shorter than one second.");
+                return Err(Error::local_error(ErrorKind::WrongParamSize));
+            }
+            Ok(-seconds)
+        }
+    }
+}
+
+// The TPM returns a NULL ticket when none was requested.
+fn policy_auth_ticket(ticket: TPMT_TK_AUTH) -> Result<Option<AuthTicket>> {
+    if ticket.digest.size == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(AuthTicket::try_from(ticket)?))
+    }
+}
+
+// Gather the creation outputs of TPM2_Create and TPM2_CreatePrimary.
+fn key_creation_data(
+    creation_data: MBox<TPM2B_CREATION_DATA>,
+    creation_hash: MBox<TPM2B_DIGEST>,
+    creation_ticket: MBox<TPMT_TK_CREATION>,
+) -> Result<KeyCreationData> {
+    Ok(KeyCreationData {
+        creation_data: *creation_data,
+        creation_hash: creation_hash.buffer[..creation_hash.size as usize].to_vec(),
+        creation_ticket: CreationTicket::try_from(*creation_ticket)?,
+    })
+}
+
+// The TPM returned the data of another capability than the one requested.
+fn unexpected_capability() -> Error {
+    error!("Error: the TPM returned a different capability than requested.");
+    Error::local_error(ErrorKind::InconsistentParams)
+}
+
+fn wrap_command_codes(command_codes: &[CommandCode]) -> Result<TPML_CC> {
+    let mut tss_command_codes: TPML_CC = Default::default();
+    if command_codes.len() > tss_command_codes.commandCodes.len() {
+        return Err(Error::local_error(ErrorKind::WrongParamSize));
+    }
+    for (tss_command_code, command_code) in tss_command_codes
+        .commandCodes
+        .iter_mut()
+        .zip(command_codes.iter())
+    {
+        *tss_command_code = TPM2_CC::from(*command_code);
+    }
+    tss_command_codes.count = command_codes.len().try_into().unwrap(); // should not fail given the check above
+    Ok(tss_command_codes)
+}
+
+fn wrap_name(name: &[u8]) -> Result<TPM2B_NAME> {
+    let mut tss_name: TPM2B_NAME = Default::default();
+    if name.len() > tss_name.name.len() {
+        return Err(Error::local_error(ErrorKind::WrongParamSize));
+    }
+    tss_name.name[..name.len()].clone_from_slice(name);
+    tss_name.size = name.len().try_into().unwrap(); // should not fail given the check above
+    Ok(tss_name)
+}
+
 impl Drop for Context {
     fn drop(&mut self) {
         info!("Closing context.");
@@ -1023,6 +3142,14 @@ impl Drop for Context {
             }
         });
 
+        // Close the handles of entities that cannot be flushed.
+        self.closable_handles.clone().iter().for_each(|handle| {
+            info!("Closing handle {}", *handle);
+            if let Err(e) = self.tr_close(*handle) {
+                error!("Error when dropping the context: {}.", e);
+            }
+        });
+
         let esys_context = self.esys_context.take().unwrap(); // should not fail based on how the context is initialised/used
         let tcti_context = self.tcti_context.take().unwrap(); // should not fail based on how the context is initialised/used
 
@@ -212,6 +212,18 @@ pub enum HashingAlgorithm {
     Sha3_512,
 }
 
+impl HashingAlgorithm {
+    /// Returns the size in bytes of the digests produced by the algorithm.
+    pub fn digest_size(self) -> usize {
+        match self {
+            HashingAlgorithm::Sha1 => 20,
+            HashingAlgorithm::Sha256 | HashingAlgorithm::Sm3_256 | HashingAlgorithm::Sha3_256 => 32,
+            HashingAlgorithm::Sha384 | HashingAlgorithm::Sha3_384 => 48,
+            HashingAlgorithm::Sha512 | HashingAlgorithm::Sha3_512 => 64,
+        }
+    }
+}
+
 impl From<HashingAlgorithm> for TPM2_ALG_ID {
     fn from(hashing_algorithm: HashingAlgorithm) -> Self {
         match hashing_algorithm {
@@ -0,0 +1,363 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Attestation structures
+//!
+//! The TPM returns attestation data (`TPMS_ATTEST`) in its marshalled form, wrapped in a
+//! `TPM2B_ATTEST`, as this is exactly what the signature is computed over. The types in this
+//! module unmarshal such a buffer into Rust-native structures.
+use crate::constants::TPM2_GENERATED_VALUE;
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::{TPM2B_ATTEST, TPML_PCR_SELECTION};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use crate::utils::tags::StructureTag;
+use crate::utils::PcrSelections;
+use log::error;
+use std::convert::{TryFrom, TryInto};
+
+/// Rust native representation of `TPMS_CLOCK_INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockInfo {
+    /// Time in milliseconds during which the TPM has been powered.
+    pub clock: u64,
+    /// Number of occurrences of TPM Reset since the last TPM2_Clear.
+    pub reset_count: u32,
+    /// Number of times that TPM2_Shutdown or _TPM_Hash_Start have occurred since the last TPM
+    /// Reset or TPM2_Clear.
+    pub restart_count: u32,
+    /// Whether the value reported in `clock` is guaranteed to be unique for the current owner.
+    pub safe: bool,
+}
+
+/// Rust native representation of `TPMS_TIME_INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeInfo {
+    /// Time in milliseconds since the last TPM Reset or TPM Restart.
+    pub time: u64,
+    /// Clock information of the TPM.
+    pub clock_info: ClockInfo,
+}
+
+/// Rust native representation of `TPMS_CERTIFY_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertifyInfo {
+    /// Name of the certified object.
+    pub name: Vec<u8>,
+    /// Qualified name of the certified object.
+    pub qualified_name: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_QUOTE_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteInfo {
+    /// PCRs included in the digest.
+    pub pcr_selections: PcrSelections,
+    /// Digest of the selected PCRs, computed with the hashing algorithm of the signing scheme.
+    pub pcr_digest: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_COMMAND_AUDIT_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandAuditInfo {
+    /// Monotonic audit counter.
+    pub audit_counter: u64,
+    /// Hashing algorithm of the command audit digest.
+    pub digest_algorithm: HashingAlgorithm,
+    /// Current value of the command audit digest.
+    pub audit_digest: Vec<u8>,
+    /// Digest of the command codes being audited, computed with `digest_algorithm`.
+    pub command_digest: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_SESSION_AUDIT_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionAuditInfo {
+    /// Whether all the commands recorded in the digest were executed without any other command
+    /// being executed in between.
+    pub exclusive_session: bool,
+    /// Current value of the session audit digest.
+    pub session_digest: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_CREATION_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreationInfo {
+    /// Name of the object.
+    pub object_name: Vec<u8>,
+    /// Digest of the creation data of the object.
+    pub creation_hash: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_TIME_ATTEST_INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeAttestInfo {
+    /// Time information of the TPM.
+    pub time: TimeInfo,
+    /// Firmware version of the TPM.
+    pub firmware_version: u64,
+}
+
+/// Rust native representation of `TPMS_NV_CERTIFY_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NvCertifyInfo {
+    /// Name of the NV index.
+    pub index_name: Vec<u8>,
+    /// Offset of the certified data within the index.
+    pub offset: u16,
+    /// Certified contents of the index.
+    pub nv_contents: Vec<u8>,
+}
+
+/// Rust native representation of `TPMS_NV_DIGEST_CERTIFY_INFO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NvDigestCertifyInfo {
+    /// Name of the NV index.
+    pub index_name: Vec<u8>,
+    /// Digest of the contents of the index.
+    pub nv_digest: Vec<u8>,
+}
+
+/// Type-specific part of an attestation structure (`TPMU_ATTEST`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttestInfo {
+    Certify(CertifyInfo),
+    Quote(QuoteInfo),
+    CommandAudit(CommandAuditInfo),
+    SessionAudit(SessionAuditInfo),
+    Time(TimeAttestInfo),
+    Creation(CreationInfo),
+    Nv(NvCertifyInfo),
+    NvDigest(NvDigestCertifyInfo),
+}
+
+/// Rust native representation of `TPMS_ATTEST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attest {
+    magic: u32,
+    attestation_type: StructureTag,
+    qualified_signer: Vec<u8>,
+    extra_data: Vec<u8>,
+    clock_info: ClockInfo,
+    firmware_version: u64,
+    attested: AttestInfo,
+}
+
+impl Attest {
+    /// Unmarshal an attestation structure from its TPM wire format.
+    ///
+    /// # Errors
+    /// * if the buffer is truncated, contains trailing bytes, or if its magic value is not
+    /// `TPM2_GENERATED_VALUE`, an `InvalidParam` wrapper error is returned
+    /// * if the type of the attestation is not an attestation structure tag, an `InvalidParam`
+    /// wrapper error is returned
+    pub fn unmarshal(buffer: &[u8]) -> Result<Self> {
+        let mut unmarshaller = Unmarshaller::new(buffer);
+        let magic = unmarshaller.read_u32()?;
+        if magic != TPM2_GENERATED_VALUE {
+            error!(
+                "Error: invalid magic value in attestation: 0x{:08x}.",
+                magic
+            );
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let attestation_type = StructureTag::try_from(unmarshaller.read_u16()?)?;
+        let qualified_signer = unmarshaller.read_tpm2b()?;
+        let extra_data = unmarshaller.read_tpm2b()?;
+        let clock_info = unmarshaller.read_clock_info()?;
+        let firmware_version = unmarshaller.read_u64()?;
+        let attested = match attestation_type {
+            StructureTag::AttestCertify => AttestInfo::Certify(CertifyInfo {
+                name: unmarshaller.read_tpm2b()?,
+                qualified_name: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestQuote => AttestInfo::Quote(QuoteInfo {
+                pcr_selections: unmarshaller.read_pcr_selections()?,
+                pcr_digest: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestCommandAudit => AttestInfo::CommandAudit(CommandAuditInfo {
+                audit_counter: unmarshaller.read_u64()?,
+                digest_algorithm: HashingAlgorithm::try_from(unmarshaller.read_u16()?)?,
+                audit_digest: unmarshaller.read_tpm2b()?,
+                command_digest: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestSessionAudit => AttestInfo::SessionAudit(SessionAuditInfo {
+                exclusive_session: unmarshaller.read_u8()? != 0,
+                session_digest: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestTime => AttestInfo::Time(TimeAttestInfo {
+                time: TimeInfo {
+                    time: unmarshaller.read_u64()?,
+                    clock_info: unmarshaller.read_clock_info()?,
+                },
+                firmware_version: unmarshaller.read_u64()?,
+            }),
+            StructureTag::AttestCreation => AttestInfo::Creation(CreationInfo {
+                object_name: unmarshaller.read_tpm2b()?,
+                creation_hash: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestNv => AttestInfo::Nv(NvCertifyInfo {
+                index_name: unmarshaller.read_tpm2b()?,
+                offset: unmarshaller.read_u16()?,
+                nv_contents: unmarshaller.read_tpm2b()?,
+            }),
+            StructureTag::AttestNvDigest => AttestInfo::NvDigest(NvDigestCertifyInfo {
+                index_name: unmarshaller.read_tpm2b()?,
+                nv_digest: unmarshaller.read_tpm2b()?,
+            }),
+            _ => {
+                error!(
+                    "Error: {:?} is not an attestation structure tag.",
+                    attestation_type
+                );
+                return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+            }
+        };
+        unmarshaller.finish()?;
+
+        Ok(Attest {
+            magic,
+            attestation_type,
+            qualified_signer,
+            extra_data,
+            clock_info,
+            firmware_version,
+            attested,
+        })
+    }
+
+    /// Get the magic value of the structure, always `TPM2_GENERATED_VALUE`.
+    pub fn magic(&self) -> u32 {
+        self.magic
+    }
+
+    /// Get the type of the attestation.
+    pub fn attestation_type(&self) -> StructureTag {
+        self.attestation_type
+    }
+
+    /// Get the qualified name of the signing key.
+    pub fn qualified_signer(&self) -> &[u8] {
+        &self.qualified_signer
+    }
+
+    /// Get the external data provided by the caller (e.g. a nonce).
+    pub fn extra_data(&self) -> &[u8] {
+        &self.extra_data
+    }
+
+    /// Get the clock information of the TPM at the time of the attestation.
+    pub fn clock_info(&self) -> ClockInfo {
+        self.clock_info
+    }
+
+    /// Get the firmware version of the TPM.
+    pub fn firmware_version(&self) -> u64 {
+        self.firmware_version
+    }
+
+    /// Get the type-specific part of the attestation.
+    pub fn attested(&self) -> &AttestInfo {
+        &self.attested
+    }
+}
+
+impl TryFrom<TPM2B_ATTEST> for Attest {
+    type Error = Error;
+
+    fn try_from(tss_attest: TPM2B_ATTEST) -> Result<Self> {
+        let size = usize::from(tss_attest.size);
+        if size > tss_attest.attestationData.len() {
+            error!(
+                "Error: Invalid attestation size.(Size: {0} > Buffer size: {1})",
+                size,
+                tss_attest.attestationData.len(),
+            );
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        Attest::unmarshal(&tss_attest.attestationData[..size])
+    }
+}
+
+// Reader for big-endian TPM wire format values.
+#[derive(Debug)]
+struct Unmarshaller<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Unmarshaller<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Unmarshaller { buffer }
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.buffer.len() {
+            error!("Error: attestation buffer is truncated.");
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let (bytes, rest) = self.buffer.split_at(len);
+        self.buffer = rest;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.read_bytes(2)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.read_bytes(4)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.read_bytes(8)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    fn read_tpm2b(&mut self) -> Result<Vec<u8>> {
+        let size = self.read_u16()?;
+        Ok(self.read_bytes(size.into())?.to_vec())
+    }
+
+    fn read_clock_info(&mut self) -> Result<ClockInfo> {
+        Ok(ClockInfo {
+            clock: self.read_u64()?,
+            reset_count: self.read_u32()?,
+            restart_count: self.read_u32()?,
+            safe: self.read_u8()? != 0,
+        })
+    }
+
+    fn read_pcr_selections(&mut self) -> Result<PcrSelections> {
+        let mut tpml_pcr_selection: TPML_PCR_SELECTION = Default::default();
+        let count = self.read_u32()?;
+        if count as usize > tpml_pcr_selection.pcrSelections.len() {
+            error!("Error: too many PCR selections in attestation: {}.", count);
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        tpml_pcr_selection.count = count;
+        for selection in tpml_pcr_selection.pcrSelections[..count as usize].iter_mut() {
+            selection.hash = self.read_u16()?;
+            selection.sizeofSelect = self.read_u8()?;
+            let size_of_select = usize::from(selection.sizeofSelect);
+            if size_of_select > selection.pcrSelect.len() {
+                error!(
+                    "Error: unsupported sizeofSelect in attestation: {}.",
+                    size_of_select
+                );
+                return Err(Error::local_error(WrapperErrorKind::UnsupportedParam));
+            }
+            selection.pcrSelect[..size_of_select].copy_from_slice(self.read_bytes(size_of_select)?);
+        }
+        PcrSelections::try_from(tpml_pcr_selection)
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            error!("Error: trailing bytes in attestation buffer.");
+            Err(Error::local_error(WrapperErrorKind::InvalidParam))
+        }
+    }
+}
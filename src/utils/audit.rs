@@ -0,0 +1,182 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Software computation of audit digests
+//!
+//! An `AuditDigestCalculator` replays the extension of an audit digest performed by the TPM for
+//! every audited command, so that the digest reported by `Context::get_session_audit_digest` or
+//! `Context::get_command_audit_digest` can be checked against a recorded list of commands and
+//! their responses. For each command, the audit digest is extended as
+//! `auditDigest := H(auditDigest || cpHash || rpHash)`.
+use crate::constants::TPM2_RC_SUCCESS;
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::TPM2_CC;
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use crate::utils::command_code::CommandCode;
+use crate::utils::hash::{self, Hasher};
+use crate::utils::policy;
+use log::error;
+
+/// Record of a successful command execution, with its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditedCommand {
+    /// Command code of the command.
+    pub command_code: CommandCode,
+    /// Names of the entities referenced by the handles of the command, in order.
+    pub handle_names: Vec<Vec<u8>>,
+    /// Marshalled parameter area of the command.
+    pub command_parameters: Vec<u8>,
+    /// Marshalled parameter area of the response.
+    pub response_parameters: Vec<u8>,
+}
+
+impl AuditedCommand {
+    /// Compute the cpHash of the command.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn cp_hash(&self, hashing_algorithm: HashingAlgorithm) -> Result<Vec<u8>> {
+        let handle_names: Vec<&[u8]> = self.handle_names.iter().map(Vec::as_slice).collect();
+        policy::cp_hash(
+            hashing_algorithm,
+            self.command_code,
+            &handle_names,
+            &self.command_parameters,
+        )
+    }
+
+    /// Compute the rpHash of the response.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn rp_hash(&self, hashing_algorithm: HashingAlgorithm) -> Result<Vec<u8>> {
+        rp_hash(
+            hashing_algorithm,
+            self.command_code,
+            &self.response_parameters,
+        )
+    }
+}
+
+/// Calculator of audit digests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditDigestCalculator {
+    hashing_algorithm: HashingAlgorithm,
+    digest: Vec<u8>,
+}
+
+impl AuditDigestCalculator {
+    /// Create a calculator with the initial audit digest (all-zero digest).
+    ///
+    /// This is the value the audit digest of a session takes when the session is first used for
+    /// audit, or when the `auditReset` attribute is set, and the value the command audit digest
+    /// takes after having been cleared.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn new(hashing_algorithm: HashingAlgorithm) -> Result<Self> {
+        let _ = Hasher::new(hashing_algorithm)?;
+        Ok(AuditDigestCalculator {
+            hashing_algorithm,
+            digest: vec![0; hashing_algorithm.digest_size()],
+        })
+    }
+
+    /// Create a calculator continuing from a known audit digest.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    /// * if the size of `digest` does not match the algorithm, a `WrongParamSize` wrapper error
+    /// is returned
+    pub fn with_digest(hashing_algorithm: HashingAlgorithm, digest: &[u8]) -> Result<Self> {
+        let mut calculator = AuditDigestCalculator::new(hashing_algorithm)?;
+        calculator.check_digest_size(digest)?;
+        calculator.digest = digest.to_vec();
+        Ok(calculator)
+    }
+
+    /// Get the hashing algorithm of the audit digest.
+    pub fn hashing_algorithm(&self) -> HashingAlgorithm {
+        self.hashing_algorithm
+    }
+
+    /// Get the current audit digest.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Extend the audit digest with the cpHash and rpHash of a command.
+    ///
+    /// # Errors
+    /// * if the size of either hash does not match the algorithm of the audit digest, a
+    /// `WrongParamSize` wrapper error is returned
+    pub fn extend(&mut self, cp_hash: &[u8], rp_hash: &[u8]) -> Result<()> {
+        self.check_digest_size(cp_hash)?;
+        self.check_digest_size(rp_hash)?;
+        self.digest = hash::hash(self.hashing_algorithm, &[&self.digest, cp_hash, rp_hash])?;
+        Ok(())
+    }
+
+    /// Extend the audit digest with a recorded command.
+    pub fn audit_command(&mut self, command: &AuditedCommand) -> Result<()> {
+        let cp_hash = command.cp_hash(self.hashing_algorithm)?;
+        let rp_hash = command.rp_hash(self.hashing_algorithm)?;
+        self.extend(&cp_hash, &rp_hash)
+    }
+
+    fn check_digest_size(&self, digest: &[u8]) -> Result<()> {
+        if digest.len() != self.hashing_algorithm.digest_size() {
+            error!(
+                "Error: digest of size {} provided, {:?} digests are {} bytes long.",
+                digest.len(),
+                self.hashing_algorithm,
+                self.hashing_algorithm.digest_size()
+            );
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        Ok(())
+    }
+}
+
+/// Compute the audit digest expected after the execution of `commands`, starting from the
+/// initial (all-zero) audit digest.
+///
+/// # Errors
+/// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper error
+/// is returned
+pub fn audit_digest(
+    hashing_algorithm: HashingAlgorithm,
+    commands: &[AuditedCommand],
+) -> Result<Vec<u8>> {
+    let mut calculator = AuditDigestCalculator::new(hashing_algorithm)?;
+    for command in commands {
+        calculator.audit_command(command)?;
+    }
+    Ok(calculator.digest)
+}
+
+/// Compute the rpHash of the response to a successful command.
+///
+/// `parameters` is the marshalled parameter area of the response. The rpHash is the digest of
+/// `responseCode || commandCode || parameters`, the response code being `TPM2_RC_SUCCESS`.
+///
+/// # Errors
+/// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper error
+/// is returned
+pub fn rp_hash(
+    hashing_algorithm: HashingAlgorithm,
+    command_code: CommandCode,
+    parameters: &[u8],
+) -> Result<Vec<u8>> {
+    hash::hash(
+        hashing_algorithm,
+        &[
+            &TPM2_RC_SUCCESS.to_be_bytes(),
+            &TPM2_CC::from(command_code).to_be_bytes(),
+            parameters,
+        ],
+    )
+}
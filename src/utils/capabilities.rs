@@ -0,0 +1,341 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Decoding of the capabilities reported by TPM2_GetCapability
+//!
+//! `CapabilityData` is the Rust native representation of `TPMS_CAPABILITY_DATA`, with each kind
+//! of capability decoded into the types of this crate. The methods of `Context` built on top of
+//! it (e.g. `Context::get_algorithms` or `Context::get_pcr_banks`) handle the paging of the
+//! results for the caller.
+//!
+//! The authorization policies of the hierarchies (`TPM2_CAP_AUTH_POLICIES`) are not supported:
+//! `TPMU_CAPABILITIES`, as defined by the TSS, has no member to hold them.
+use crate::constants::*;
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::{
+    TPM2_ALG_ID, TPM2_CAP, TPM2_CC, TPM2_HANDLE, TPM2_HT, TPM2_PT, TPMS_CAPABILITY_DATA,
+    TPMU_CAPABILITIES,
+};
+use crate::utils::algorithm_specifiers::{
+    AsymmetricAlgorithm, AsymmetricEncryptionScheme, EllipticCurve, EncryptionMode,
+    HashingAlgorithm, KeyDerivationFunction, KeyedHash, MaskGenerationFunction, ObjectType,
+    SignatureScheme, SymmetricAlgorithm,
+};
+use crate::utils::command_code::CommandCode;
+use crate::utils::PcrSelections;
+use log::error;
+use std::convert::TryFrom;
+
+/// Algorithm implemented by a TPM, classified with the enums of `algorithm_specifiers`.
+///
+/// Every algorithm identifier maps to a single variant, even when it appears in several of those
+/// enums; the variants are tried in the order in which they are declared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Hashing(HashingAlgorithm),
+    Asymmetric(AsymmetricAlgorithm),
+    Symmetric(SymmetricAlgorithm),
+    KeyedHash(KeyedHash),
+    Object(ObjectType),
+    Signature(SignatureScheme),
+    AsymmetricEncryption(AsymmetricEncryptionScheme),
+    EncryptionMode(EncryptionMode),
+    MaskGeneration(MaskGenerationFunction),
+    KeyDerivation(KeyDerivationFunction),
+    /// Algorithm not represented in this crate.
+    Other(TPM2_ALG_ID),
+}
+
+impl From<TPM2_ALG_ID> for Algorithm {
+    fn from(algorithm_id: TPM2_ALG_ID) -> Self {
+        if let Ok(algorithm) = HashingAlgorithm::try_from(algorithm_id) {
+            Algorithm::Hashing(algorithm)
+        } else if let Ok(algorithm) = AsymmetricAlgorithm::try_from(algorithm_id) {
+            Algorithm::Asymmetric(algorithm)
+        } else if let Ok(algorithm) = SymmetricAlgorithm::try_from(algorithm_id) {
+            Algorithm::Symmetric(algorithm)
+        } else if algorithm_id == TPM2_ALG_HMAC {
+            Algorithm::KeyedHash(KeyedHash::Hmac)
+        } else if let Ok(object_type) = ObjectType::try_from(algorithm_id) {
+            Algorithm::Object(object_type)
+        } else if let Ok(scheme) = SignatureScheme::try_from(algorithm_id) {
+            Algorithm::Signature(scheme)
+        } else if let Ok(scheme) = AsymmetricEncryptionScheme::try_from(algorithm_id) {
+            Algorithm::AsymmetricEncryption(scheme)
+        } else if let Ok(mode) = EncryptionMode::try_from(algorithm_id) {
+            Algorithm::EncryptionMode(mode)
+        } else if let Ok(function) = MaskGenerationFunction::try_from(algorithm_id) {
+            Algorithm::MaskGeneration(function)
+        } else if let Ok(function) = KeyDerivationFunction::try_from(algorithm_id) {
+            Algorithm::KeyDerivation(function)
+        } else {
+            Algorithm::Other(algorithm_id)
+        }
+    }
+}
+
+/// Types of handles which can be listed with `Context::get_handles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleType {
+    Pcr,
+    NvIndex,
+    LoadedSession,
+    SavedSession,
+    Permanent,
+    Transient,
+    Persistent,
+}
+
+impl HandleType {
+    /// Get the first handle of the range of handles of this type.
+    pub fn first_handle(self) -> TPM2_HANDLE {
+        TPM2_HANDLE::from(TPM2_HT::from(self)) << TPM2_HR_SHIFT
+    }
+
+    /// Get the last handle of the range of handles of this type.
+    pub fn last_handle(self) -> TPM2_HANDLE {
+        self.first_handle() | ((1 << TPM2_HR_SHIFT) - 1)
+    }
+}
+
+impl From<HandleType> for TPM2_HT {
+    fn from(handle_type: HandleType) -> Self {
+        match handle_type {
+            HandleType::Pcr => TPM2_HT_PCR,
+            HandleType::NvIndex => TPM2_HT_NV_INDEX,
+            HandleType::LoadedSession => TPM2_HT_LOADED_SESSION,
+            HandleType::SavedSession => TPM2_HT_SAVED_SESSION,
+            HandleType::Permanent => TPM2_HT_PERMANENT,
+            HandleType::Transient => TPM2_HT_TRANSIENT,
+            HandleType::Persistent => TPM2_HT_PERSISTENT,
+        }
+    }
+}
+
+/// Rust native representation of `TPMS_CAPABILITY_DATA`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapabilityData {
+    /// Implemented algorithms (`TPM2_CAP_ALGS`).
+    Algorithms(Vec<Algorithm>),
+    /// Handles of a given type (`TPM2_CAP_HANDLES`).
+    Handles(Vec<TPM2_HANDLE>),
+    /// Implemented commands (`TPM2_CAP_COMMANDS`); vendor-specific commands and commands unknown
+    /// to this crate are left out.
+    Commands(Vec<CommandCode>),
+    /// Commands requiring Physical Presence (`TPM2_CAP_PP_COMMANDS`).
+    PpCommands(Vec<CommandCode>),
+    /// Audited commands (`TPM2_CAP_AUDIT_COMMANDS`).
+    AuditCommands(Vec<CommandCode>),
+    /// Allocated PCR banks and the PCRs which are active in each (`TPM2_CAP_PCRS`).
+    AssignedPcrs(PcrSelections),
+    /// Properties of the TPM, as `(property, value)` pairs (`TPM2_CAP_TPM_PROPERTIES`).
+    TpmProperties(Vec<(TPM2_PT, u32)>),
+    /// Implemented elliptic curves (`TPM2_CAP_ECC_CURVES`); curves unknown to this crate are
+    /// left out.
+    EccCurves(Vec<EllipticCurve>),
+}
+
+impl CapabilityData {
+    /// Append the data of the next page of the same capability.
+    pub(crate) fn append(&mut self, other: CapabilityData) -> Result<()> {
+        match (self, other) {
+            (CapabilityData::Algorithms(data), CapabilityData::Algorithms(other)) => {
+                data.extend(other)
+            }
+            (CapabilityData::Handles(data), CapabilityData::Handles(other)) => data.extend(other),
+            (CapabilityData::Commands(data), CapabilityData::Commands(other))
+            | (CapabilityData::PpCommands(data), CapabilityData::PpCommands(other))
+            | (CapabilityData::AuditCommands(data), CapabilityData::AuditCommands(other)) => {
+                data.extend(other)
+            }
+            (CapabilityData::AssignedPcrs(data), CapabilityData::AssignedPcrs(other)) => {
+                data.union(&other)
+            }
+            (CapabilityData::TpmProperties(data), CapabilityData::TpmProperties(other)) => {
+                data.extend(other)
+            }
+            (CapabilityData::EccCurves(data), CapabilityData::EccCurves(other)) => {
+                data.extend(other)
+            }
+            _ => {
+                error!("Error: the TPM returned a different capability than requested.");
+                return Err(Error::local_error(WrapperErrorKind::InconsistentParams));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<TPMS_CAPABILITY_DATA> for CapabilityData {
+    type Error = Error;
+
+    fn try_from(capability_data: TPMS_CAPABILITY_DATA) -> Result<Self> {
+        // The union member read is selected by the capability, as in the TSS
+        let data = &capability_data.data;
+        match capability_data.capability {
+            TPM2_CAP_ALGS => {
+                let algorithms = unsafe { &data.algorithms };
+                Ok(CapabilityData::Algorithms(
+                    checked_items(&algorithms.algProperties, algorithms.count)?
+                        .iter()
+                        .map(|property| Algorithm::from(property.alg))
+                        .collect(),
+                ))
+            }
+            TPM2_CAP_HANDLES => {
+                let handles = unsafe { &data.handles };
+                Ok(CapabilityData::Handles(
+                    checked_items(&handles.handle, handles.count)?.to_vec(),
+                ))
+            }
+            TPM2_CAP_COMMANDS => {
+                let commands = unsafe { &data.command };
+                Ok(CapabilityData::Commands(
+                    checked_items(&commands.commandAttributes, commands.count)?
+                        .iter()
+                        .filter(|attributes| *attributes & TPMA_CC_V == 0)
+                        .filter_map(|attributes| {
+                            CommandCode::try_from(attributes & TPMA_CC_COMMANDINDEX_MASK).ok()
+                        })
+                        .collect(),
+                ))
+            }
+            TPM2_CAP_PP_COMMANDS => {
+                let commands = unsafe { &data.ppCommands };
+                Ok(CapabilityData::PpCommands(command_codes(checked_items(
+                    &commands.commandCodes,
+                    commands.count,
+                )?)))
+            }
+            TPM2_CAP_AUDIT_COMMANDS => {
+                let commands = unsafe { &data.auditCommands };
+                Ok(CapabilityData::AuditCommands(command_codes(checked_items(
+                    &commands.commandCodes,
+                    commands.count,
+                )?)))
+            }
+            TPM2_CAP_PCRS => Ok(CapabilityData::AssignedPcrs(PcrSelections::try_from(
+                unsafe { data.assignedPCR },
+            )?)),
+            TPM2_CAP_TPM_PROPERTIES => {
+                let properties = unsafe { &data.tpmProperties };
+                Ok(CapabilityData::TpmProperties(
+                    checked_items(&properties.tpmProperty, properties.count)?
+                        .iter()
+                        .map(|property| (property.property, property.value))
+                        .collect(),
+                ))
+            }
+            TPM2_CAP_ECC_CURVES => {
+                let curves = unsafe { &data.eccCurves };
+                Ok(CapabilityData::EccCurves(
+                    checked_items(&curves.eccCurves, curves.count)?
+                        .iter()
+                        .filter_map(|curve| EllipticCurve::try_from(*curve).ok())
+                        .collect(),
+                ))
+            }
+            capability => {
+                error!("Error: capability {} is not supported.", capability);
+                Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+            }
+        }
+    }
+}
+
+/// Get the property from which to start reading a capability, to get all of its data.
+pub(crate) fn first_property(capability: TPM2_CAP) -> u32 {
+    match capability {
+        TPM2_CAP_ALGS => TPM2_ALG_FIRST.into(),
+        TPM2_CAP_COMMANDS | TPM2_CAP_PP_COMMANDS | TPM2_CAP_AUDIT_COMMANDS => TPM2_CC_FIRST,
+        TPM2_CAP_ECC_CURVES => TPM2_ECC_NONE.into(),
+        _ => 0,
+    }
+}
+
+/// Get the largest number of items of a capability which the TSS can receive at once.
+pub(crate) fn max_property_count(capability: TPM2_CAP) -> u32 {
+    let data = TPMU_CAPABILITIES::default();
+    let count = unsafe {
+        match capability {
+            TPM2_CAP_ALGS => data.algorithms.algProperties.len(),
+            TPM2_CAP_HANDLES => data.handles.handle.len(),
+            TPM2_CAP_COMMANDS => data.command.commandAttributes.len(),
+            TPM2_CAP_PP_COMMANDS => data.ppCommands.commandCodes.len(),
+            TPM2_CAP_AUDIT_COMMANDS => data.auditCommands.commandCodes.len(),
+            TPM2_CAP_PCRS => data.assignedPCR.pcrSelections.len(),
+            TPM2_CAP_TPM_PROPERTIES => data.tpmProperties.tpmProperty.len(),
+            TPM2_CAP_ECC_CURVES => data.eccCurves.eccCurves.len(),
+            _ => 0,
+        }
+    };
+    count as u32
+}
+
+/// Get the property from which to continue reading a capability, after `capability_data` was
+/// returned with `moreData` set.
+pub(crate) fn next_property(capability_data: &TPMS_CAPABILITY_DATA) -> Result<Option<u32>> {
+    let data = &capability_data.data;
+    let last = match capability_data.capability {
+        TPM2_CAP_ALGS => {
+            let algorithms = unsafe { &data.algorithms };
+            checked_items(&algorithms.algProperties, algorithms.count)?
+                .last()
+                .map(|property| u32::from(property.alg))
+        }
+        TPM2_CAP_HANDLES => {
+            let handles = unsafe { &data.handles };
+            checked_items(&handles.handle, handles.count)?
+                .last()
+                .cloned()
+        }
+        TPM2_CAP_COMMANDS => {
+            let commands = unsafe { &data.command };
+            checked_items(&commands.commandAttributes, commands.count)?
+                .last()
+                .map(|attributes| attributes & TPMA_CC_COMMANDINDEX_MASK)
+        }
+        TPM2_CAP_PP_COMMANDS => {
+            let commands = unsafe { &data.ppCommands };
+            checked_items(&commands.commandCodes, commands.count)?
+                .last()
+                .cloned()
+        }
+        TPM2_CAP_AUDIT_COMMANDS => {
+            let commands = unsafe { &data.auditCommands };
+            checked_items(&commands.commandCodes, commands.count)?
+                .last()
+                .cloned()
+        }
+        TPM2_CAP_TPM_PROPERTIES => {
+            let properties = unsafe { &data.tpmProperties };
+            checked_items(&properties.tpmProperty, properties.count)?
+                .last()
+                .map(|property| property.property)
+        }
+        TPM2_CAP_ECC_CURVES => {
+            let curves = unsafe { &data.eccCurves };
+            checked_items(&curves.eccCurves, curves.count)?
+                .last()
+                .map(|curve| u32::from(*curve))
+        }
+        // All the PCR banks are returned at once
+        _ => None,
+    };
+    Ok(last.and_then(|last| last.checked_add(1)))
+}
+
+// Get the items of a TPML structure, checking its count.
+fn checked_items<T>(items: &[T], count: u32) -> Result<&[T]> {
+    if count as usize > items.len() {
+        error!("Error: invalid capability count {}.", count);
+        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+    }
+    Ok(&items[..count as usize])
+}
+
+fn command_codes(command_codes: &[TPM2_CC]) -> Vec<CommandCode> {
+    command_codes
+        .iter()
+        .filter_map(|command_code| CommandCode::try_from(*command_code).ok())
+        .collect()
+}
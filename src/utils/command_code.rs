@@ -0,0 +1,374 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use crate::constants::*;
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::TPM2_CC;
+use std::convert::TryFrom;
+
+/// Enum representing the TPM command codes (`TPM2_CC`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommandCode {
+    NvUndefineSpaceSpecial,
+    EvictControl,
+    HierarchyControl,
+    NvUndefineSpace,
+    ChangeEps,
+    ChangePps,
+    Clear,
+    ClearControl,
+    ClockSet,
+    HierarchyChangeAuth,
+    NvDefineSpace,
+    PcrAllocate,
+    PcrSetAuthPolicy,
+    PpCommands,
+    SetPrimaryPolicy,
+    FieldUpgradeStart,
+    ClockRateAdjust,
+    CreatePrimary,
+    NvGlobalWriteLock,
+    GetCommandAuditDigest,
+    NvIncrement,
+    NvSetBits,
+    NvExtend,
+    NvWrite,
+    NvWriteLock,
+    DictionaryAttackLockReset,
+    DictionaryAttackParameters,
+    NvChangeAuth,
+    PcrEvent,
+    PcrReset,
+    SequenceComplete,
+    SetAlgorithmSet,
+    SetCommandCodeAuditStatus,
+    FieldUpgradeData,
+    IncrementalSelfTest,
+    SelfTest,
+    Startup,
+    Shutdown,
+    StirRandom,
+    ActivateCredential,
+    Certify,
+    PolicyNv,
+    CertifyCreation,
+    Duplicate,
+    GetTime,
+    GetSessionAuditDigest,
+    NvRead,
+    NvReadLock,
+    ObjectChangeAuth,
+    PolicySecret,
+    Rewrap,
+    Create,
+    EcdhZGen,
+    Hmac,
+    Import,
+    Load,
+    Quote,
+    RsaDecrypt,
+    HmacStart,
+    SequenceUpdate,
+    Sign,
+    Unseal,
+    PolicySigned,
+    ContextLoad,
+    ContextSave,
+    EcdhKeyGen,
+    EncryptDecrypt,
+    FlushContext,
+    LoadExternal,
+    MakeCredential,
+    NvReadPublic,
+    PolicyAuthorize,
+    PolicyAuthValue,
+    PolicyCommandCode,
+    PolicyCounterTimer,
+    PolicyCpHash,
+    PolicyLocality,
+    PolicyNameHash,
+    PolicyOr,
+    PolicyTicket,
+    ReadPublic,
+    RsaEncrypt,
+    StartAuthSession,
+    VerifySignature,
+    EccParameters,
+    FirmwareRead,
+    GetCapability,
+    GetRandom,
+    GetTestResult,
+    Hash,
+    PcrRead,
+    PolicyPcr,
+    PolicyRestart,
+    ReadClock,
+    PcrExtend,
+    PcrSetAuthValue,
+    NvCertify,
+    EventSequenceComplete,
+    HashSequenceStart,
+    PolicyPhysicalPresence,
+    PolicyDuplicationSelect,
+    PolicyGetDigest,
+    TestParms,
+    Commit,
+    PolicyPassword,
+    ZGen2Phase,
+    EcEphemeral,
+    PolicyNvWritten,
+    PolicyTemplate,
+    CreateLoaded,
+    PolicyAuthorizeNv,
+    EncryptDecrypt2,
+    AcGetCapability,
+    AcSend,
+    PolicyAcSendSelect,
+}
+
+impl From<CommandCode> for TPM2_CC {
+    fn from(command_code: CommandCode) -> Self {
+        match command_code {
+            CommandCode::NvUndefineSpaceSpecial => TPM2_CC_NV_UndefineSpaceSpecial,
+            CommandCode::EvictControl => TPM2_CC_EvictControl,
+            CommandCode::HierarchyControl => TPM2_CC_HierarchyControl,
+            CommandCode::NvUndefineSpace => TPM2_CC_NV_UndefineSpace,
+            CommandCode::ChangeEps => TPM2_CC_ChangeEPS,
+            CommandCode::ChangePps => TPM2_CC_ChangePPS,
+            CommandCode::Clear => TPM2_CC_Clear,
+            CommandCode::ClearControl => TPM2_CC_ClearControl,
+            CommandCode::ClockSet => TPM2_CC_ClockSet,
+            CommandCode::HierarchyChangeAuth => TPM2_CC_HierarchyChangeAuth,
+            CommandCode::NvDefineSpace => TPM2_CC_NV_DefineSpace,
+            CommandCode::PcrAllocate => TPM2_CC_PCR_Allocate,
+            CommandCode::PcrSetAuthPolicy => TPM2_CC_PCR_SetAuthPolicy,
+            CommandCode::PpCommands => TPM2_CC_PP_Commands,
+            CommandCode::SetPrimaryPolicy => TPM2_CC_SetPrimaryPolicy,
+            CommandCode::FieldUpgradeStart => TPM2_CC_FieldUpgradeStart,
+            CommandCode::ClockRateAdjust => TPM2_CC_ClockRateAdjust,
+            CommandCode::CreatePrimary => TPM2_CC_CreatePrimary,
+            CommandCode::NvGlobalWriteLock => TPM2_CC_NV_GlobalWriteLock,
+            CommandCode::GetCommandAuditDigest => TPM2_CC_GetCommandAuditDigest,
+            CommandCode::NvIncrement => TPM2_CC_NV_Increment,
+            CommandCode::NvSetBits => TPM2_CC_NV_SetBits,
+            CommandCode::NvExtend => TPM2_CC_NV_Extend,
+            CommandCode::NvWrite => TPM2_CC_NV_Write,
+            CommandCode::NvWriteLock => TPM2_CC_NV_WriteLock,
+            CommandCode::DictionaryAttackLockReset => TPM2_CC_DictionaryAttackLockReset,
+            CommandCode::DictionaryAttackParameters => TPM2_CC_DictionaryAttackParameters,
+            CommandCode::NvChangeAuth => TPM2_CC_NV_ChangeAuth,
+            CommandCode::PcrEvent => TPM2_CC_PCR_Event,
+            CommandCode::PcrReset => TPM2_CC_PCR_Reset,
+            CommandCode::SequenceComplete => TPM2_CC_SequenceComplete,
+            CommandCode::SetAlgorithmSet => TPM2_CC_SetAlgorithmSet,
+            CommandCode::SetCommandCodeAuditStatus => TPM2_CC_SetCommandCodeAuditStatus,
+            CommandCode::FieldUpgradeData => TPM2_CC_FieldUpgradeData,
+            CommandCode::IncrementalSelfTest => TPM2_CC_IncrementalSelfTest,
+            CommandCode::SelfTest => TPM2_CC_SelfTest,
+            CommandCode::Startup => TPM2_CC_Startup,
+            CommandCode::Shutdown => TPM2_CC_Shutdown,
+            CommandCode::StirRandom => TPM2_CC_StirRandom,
+            CommandCode::ActivateCredential => TPM2_CC_ActivateCredential,
+            CommandCode::Certify => TPM2_CC_Certify,
+            CommandCode::PolicyNv => TPM2_CC_PolicyNV,
+            CommandCode::CertifyCreation => TPM2_CC_CertifyCreation,
+            CommandCode::Duplicate => TPM2_CC_Duplicate,
+            CommandCode::GetTime => TPM2_CC_GetTime,
+            CommandCode::GetSessionAuditDigest => TPM2_CC_GetSessionAuditDigest,
+            CommandCode::NvRead => TPM2_CC_NV_Read,
+            CommandCode::NvReadLock => TPM2_CC_NV_ReadLock,
+            CommandCode::ObjectChangeAuth => TPM2_CC_ObjectChangeAuth,
+            CommandCode::PolicySecret => TPM2_CC_PolicySecret,
+            CommandCode::Rewrap => TPM2_CC_Rewrap,
+            CommandCode::Create => TPM2_CC_Create,
+            CommandCode::EcdhZGen => TPM2_CC_ECDH_ZGen,
+            CommandCode::Hmac => TPM2_CC_HMAC,
+            CommandCode::Import => TPM2_CC_Import,
+            CommandCode::Load => TPM2_CC_Load,
+            CommandCode::Quote => TPM2_CC_Quote,
+            CommandCode::RsaDecrypt => TPM2_CC_RSA_Decrypt,
+            CommandCode::HmacStart => TPM2_CC_HMAC_Start,
+            CommandCode::SequenceUpdate => TPM2_CC_SequenceUpdate,
+            CommandCode::Sign => TPM2_CC_Sign,
+            CommandCode::Unseal => TPM2_CC_Unseal,
+            CommandCode::PolicySigned => TPM2_CC_PolicySigned,
+            CommandCode::ContextLoad => TPM2_CC_ContextLoad,
+            CommandCode::ContextSave => TPM2_CC_ContextSave,
+            CommandCode::EcdhKeyGen => TPM2_CC_ECDH_KeyGen,
+            CommandCode::EncryptDecrypt => TPM2_CC_EncryptDecrypt,
+            CommandCode::FlushContext => TPM2_CC_FlushContext,
+            CommandCode::LoadExternal => TPM2_CC_LoadExternal,
+            CommandCode::MakeCredential => TPM2_CC_MakeCredential,
+            CommandCode::NvReadPublic => TPM2_CC_NV_ReadPublic,
+            CommandCode::PolicyAuthorize => TPM2_CC_PolicyAuthorize,
+            CommandCode::PolicyAuthValue => TPM2_CC_PolicyAuthValue,
+            CommandCode::PolicyCommandCode => TPM2_CC_PolicyCommandCode,
+            CommandCode::PolicyCounterTimer => TPM2_CC_PolicyCounterTimer,
+            CommandCode::PolicyCpHash => TPM2_CC_PolicyCpHash,
+            CommandCode::PolicyLocality => TPM2_CC_PolicyLocality,
+            CommandCode::PolicyNameHash => TPM2_CC_PolicyNameHash,
+            CommandCode::PolicyOr => TPM2_CC_PolicyOR,
+            CommandCode::PolicyTicket => TPM2_CC_PolicyTicket,
+            CommandCode::ReadPublic => TPM2_CC_ReadPublic,
+            CommandCode::RsaEncrypt => TPM2_CC_RSA_Encrypt,
+            CommandCode::StartAuthSession => TPM2_CC_StartAuthSession,
+            CommandCode::VerifySignature => TPM2_CC_VerifySignature,
+            CommandCode::EccParameters => TPM2_CC_ECC_Parameters,
+            CommandCode::FirmwareRead => TPM2_CC_FirmwareRead,
+            CommandCode::GetCapability => TPM2_CC_GetCapability,
+            CommandCode::GetRandom => TPM2_CC_GetRandom,
+            CommandCode::GetTestResult => TPM2_CC_GetTestResult,
+            CommandCode::Hash => TPM2_CC_Hash,
+            CommandCode::PcrRead => TPM2_CC_PCR_Read,
+            CommandCode::PolicyPcr => TPM2_CC_PolicyPCR,
+            CommandCode::PolicyRestart => TPM2_CC_PolicyRestart,
+            CommandCode::ReadClock => TPM2_CC_ReadClock,
+            CommandCode::PcrExtend => TPM2_CC_PCR_Extend,
+            CommandCode::PcrSetAuthValue => TPM2_CC_PCR_SetAuthValue,
+            CommandCode::NvCertify => TPM2_CC_NV_Certify,
+            CommandCode::EventSequenceComplete => TPM2_CC_EventSequenceComplete,
+            CommandCode::HashSequenceStart => TPM2_CC_HashSequenceStart,
+            CommandCode::PolicyPhysicalPresence => TPM2_CC_PolicyPhysicalPresence,
+            CommandCode::PolicyDuplicationSelect => TPM2_CC_PolicyDuplicationSelect,
+            CommandCode::PolicyGetDigest => TPM2_CC_PolicyGetDigest,
+            CommandCode::TestParms => TPM2_CC_TestParms,
+            CommandCode::Commit => TPM2_CC_Commit,
+            CommandCode::PolicyPassword => TPM2_CC_PolicyPassword,
+            CommandCode::ZGen2Phase => TPM2_CC_ZGen_2Phase,
+            CommandCode::EcEphemeral => TPM2_CC_EC_Ephemeral,
+            CommandCode::PolicyNvWritten => TPM2_CC_PolicyNvWritten,
+            CommandCode::PolicyTemplate => TPM2_CC_PolicyTemplate,
+            CommandCode::CreateLoaded => TPM2_CC_CreateLoaded,
+            CommandCode::PolicyAuthorizeNv => TPM2_CC_PolicyAuthorizeNV,
+            CommandCode::EncryptDecrypt2 => TPM2_CC_EncryptDecrypt2,
+            CommandCode::AcGetCapability => TPM2_CC_AC_GetCapability,
+            CommandCode::AcSend => TPM2_CC_AC_Send,
+            CommandCode::PolicyAcSendSelect => TPM2_CC_Policy_AC_SendSelect,
+        }
+    }
+}
+
+impl TryFrom<TPM2_CC> for CommandCode {
+    type Error = Error;
+
+    #[allow(non_upper_case_globals)]
+    fn try_from(tpm2_cc: TPM2_CC) -> Result<Self> {
+        match tpm2_cc {
+            TPM2_CC_NV_UndefineSpaceSpecial => Ok(CommandCode::NvUndefineSpaceSpecial),
+            TPM2_CC_EvictControl => Ok(CommandCode::EvictControl),
+            TPM2_CC_HierarchyControl => Ok(CommandCode::HierarchyControl),
+            TPM2_CC_NV_UndefineSpace => Ok(CommandCode::NvUndefineSpace),
+            TPM2_CC_ChangeEPS => Ok(CommandCode::ChangeEps),
+            TPM2_CC_ChangePPS => Ok(CommandCode::ChangePps),
+            TPM2_CC_Clear => Ok(CommandCode::Clear),
+            TPM2_CC_ClearControl => Ok(CommandCode::ClearControl),
+            TPM2_CC_ClockSet => Ok(CommandCode::ClockSet),
+            TPM2_CC_HierarchyChangeAuth => Ok(CommandCode::HierarchyChangeAuth),
+            TPM2_CC_NV_DefineSpace => Ok(CommandCode::NvDefineSpace),
+            TPM2_CC_PCR_Allocate => Ok(CommandCode::PcrAllocate),
+            TPM2_CC_PCR_SetAuthPolicy => Ok(CommandCode::PcrSetAuthPolicy),
+            TPM2_CC_PP_Commands => Ok(CommandCode::PpCommands),
+            TPM2_CC_SetPrimaryPolicy => Ok(CommandCode::SetPrimaryPolicy),
+            TPM2_CC_FieldUpgradeStart => Ok(CommandCode::FieldUpgradeStart),
+            TPM2_CC_ClockRateAdjust => Ok(CommandCode::ClockRateAdjust),
+            TPM2_CC_CreatePrimary => Ok(CommandCode::CreatePrimary),
+            TPM2_CC_NV_GlobalWriteLock => Ok(CommandCode::NvGlobalWriteLock),
+            TPM2_CC_GetCommandAuditDigest => Ok(CommandCode::GetCommandAuditDigest),
+            TPM2_CC_NV_Increment => Ok(CommandCode::NvIncrement),
+            TPM2_CC_NV_SetBits => Ok(CommandCode::NvSetBits),
+            TPM2_CC_NV_Extend => Ok(CommandCode::NvExtend),
+            TPM2_CC_NV_Write => Ok(CommandCode::NvWrite),
+            TPM2_CC_NV_WriteLock => Ok(CommandCode::NvWriteLock),
+            TPM2_CC_DictionaryAttackLockReset => Ok(CommandCode::DictionaryAttackLockReset),
+            TPM2_CC_DictionaryAttackParameters => Ok(CommandCode::DictionaryAttackParameters),
+            TPM2_CC_NV_ChangeAuth => Ok(CommandCode::NvChangeAuth),
+            TPM2_CC_PCR_Event => Ok(CommandCode::PcrEvent),
+            TPM2_CC_PCR_Reset => Ok(CommandCode::PcrReset),
+            TPM2_CC_SequenceComplete => Ok(CommandCode::SequenceComplete),
+            TPM2_CC_SetAlgorithmSet => Ok(CommandCode::SetAlgorithmSet),
+            TPM2_CC_SetCommandCodeAuditStatus => Ok(CommandCode::SetCommandCodeAuditStatus),
+            TPM2_CC_FieldUpgradeData => Ok(CommandCode::FieldUpgradeData),
+            TPM2_CC_IncrementalSelfTest => Ok(CommandCode::IncrementalSelfTest),
+            TPM2_CC_SelfTest => Ok(CommandCode::SelfTest),
+            TPM2_CC_Startup => Ok(CommandCode::Startup),
+            TPM2_CC_Shutdown => Ok(CommandCode::Shutdown),
+            TPM2_CC_StirRandom => Ok(CommandCode::StirRandom),
+            TPM2_CC_ActivateCredential => Ok(CommandCode::ActivateCredential),
+            TPM2_CC_Certify => Ok(CommandCode::Certify),
+            TPM2_CC_PolicyNV => Ok(CommandCode::PolicyNv),
+            TPM2_CC_CertifyCreation => Ok(CommandCode::CertifyCreation),
+            TPM2_CC_Duplicate => Ok(CommandCode::Duplicate),
+            TPM2_CC_GetTime => Ok(CommandCode::GetTime),
+            TPM2_CC_GetSessionAuditDigest => Ok(CommandCode::GetSessionAuditDigest),
+            TPM2_CC_NV_Read => Ok(CommandCode::NvRead),
+            TPM2_CC_NV_ReadLock => Ok(CommandCode::NvReadLock),
+            TPM2_CC_ObjectChangeAuth => Ok(CommandCode::ObjectChangeAuth),
+            TPM2_CC_PolicySecret => Ok(CommandCode::PolicySecret),
+            TPM2_CC_Rewrap => Ok(CommandCode::Rewrap),
+            TPM2_CC_Create => Ok(CommandCode::Create),
+            TPM2_CC_ECDH_ZGen => Ok(CommandCode::EcdhZGen),
+            TPM2_CC_HMAC => Ok(CommandCode::Hmac),
+            TPM2_CC_Import => Ok(CommandCode::Import),
+            TPM2_CC_Load => Ok(CommandCode::Load),
+            TPM2_CC_Quote => Ok(CommandCode::Quote),
+            TPM2_CC_RSA_Decrypt => Ok(CommandCode::RsaDecrypt),
+            TPM2_CC_HMAC_Start => Ok(CommandCode::HmacStart),
+            TPM2_CC_SequenceUpdate => Ok(CommandCode::SequenceUpdate),
+            TPM2_CC_Sign => Ok(CommandCode::Sign),
+            TPM2_CC_Unseal => Ok(CommandCode::Unseal),
+            TPM2_CC_PolicySigned => Ok(CommandCode::PolicySigned),
+            TPM2_CC_ContextLoad => Ok(CommandCode::ContextLoad),
+            TPM2_CC_ContextSave => Ok(CommandCode::ContextSave),
+            TPM2_CC_ECDH_KeyGen => Ok(CommandCode::EcdhKeyGen),
+            TPM2_CC_EncryptDecrypt => Ok(CommandCode::EncryptDecrypt),
+            TPM2_CC_FlushContext => Ok(CommandCode::FlushContext),
+            TPM2_CC_LoadExternal => Ok(CommandCode::LoadExternal),
+            TPM2_CC_MakeCredential => Ok(CommandCode::MakeCredential),
+            TPM2_CC_NV_ReadPublic => Ok(CommandCode::NvReadPublic),
+            TPM2_CC_PolicyAuthorize => Ok(CommandCode::PolicyAuthorize),
+            TPM2_CC_PolicyAuthValue => Ok(CommandCode::PolicyAuthValue),
+            TPM2_CC_PolicyCommandCode => Ok(CommandCode::PolicyCommandCode),
+            TPM2_CC_PolicyCounterTimer => Ok(CommandCode::PolicyCounterTimer),
+            TPM2_CC_PolicyCpHash => Ok(CommandCode::PolicyCpHash),
+            TPM2_CC_PolicyLocality => Ok(CommandCode::PolicyLocality),
+            TPM2_CC_PolicyNameHash => Ok(CommandCode::PolicyNameHash),
+            TPM2_CC_PolicyOR => Ok(CommandCode::PolicyOr),
+            TPM2_CC_PolicyTicket => Ok(CommandCode::PolicyTicket),
+            TPM2_CC_ReadPublic => Ok(CommandCode::ReadPublic),
+            TPM2_CC_RSA_Encrypt => Ok(CommandCode::RsaEncrypt),
+            TPM2_CC_StartAuthSession => Ok(CommandCode::StartAuthSession),
+            TPM2_CC_VerifySignature => Ok(CommandCode::VerifySignature),
+            TPM2_CC_ECC_Parameters => Ok(CommandCode::EccParameters),
+            TPM2_CC_FirmwareRead => Ok(CommandCode::FirmwareRead),
+            TPM2_CC_GetCapability => Ok(CommandCode::GetCapability),
+            TPM2_CC_GetRandom => Ok(CommandCode::GetRandom),
+            TPM2_CC_GetTestResult => Ok(CommandCode::GetTestResult),
+            TPM2_CC_Hash => Ok(CommandCode::Hash),
+            TPM2_CC_PCR_Read => Ok(CommandCode::PcrRead),
+            TPM2_CC_PolicyPCR => Ok(CommandCode::PolicyPcr),
+            TPM2_CC_PolicyRestart => Ok(CommandCode::PolicyRestart),
+            TPM2_CC_ReadClock => Ok(CommandCode::ReadClock),
+            TPM2_CC_PCR_Extend => Ok(CommandCode::PcrExtend),
+            TPM2_CC_PCR_SetAuthValue => Ok(CommandCode::PcrSetAuthValue),
+            TPM2_CC_NV_Certify => Ok(CommandCode::NvCertify),
+            TPM2_CC_EventSequenceComplete => Ok(CommandCode::EventSequenceComplete),
+            TPM2_CC_HashSequenceStart => Ok(CommandCode::HashSequenceStart),
+            TPM2_CC_PolicyPhysicalPresence => Ok(CommandCode::PolicyPhysicalPresence),
+            TPM2_CC_PolicyDuplicationSelect => Ok(CommandCode::PolicyDuplicationSelect),
+            TPM2_CC_PolicyGetDigest => Ok(CommandCode::PolicyGetDigest),
+            TPM2_CC_TestParms => Ok(CommandCode::TestParms),
+            TPM2_CC_Commit => Ok(CommandCode::Commit),
+            TPM2_CC_PolicyPassword => Ok(CommandCode::PolicyPassword),
+            TPM2_CC_ZGen_2Phase => Ok(CommandCode::ZGen2Phase),
+            TPM2_CC_EC_Ephemeral => Ok(CommandCode::EcEphemeral),
+            TPM2_CC_PolicyNvWritten => Ok(CommandCode::PolicyNvWritten),
+            TPM2_CC_PolicyTemplate => Ok(CommandCode::PolicyTemplate),
+            TPM2_CC_CreateLoaded => Ok(CommandCode::CreateLoaded),
+            TPM2_CC_PolicyAuthorizeNV => Ok(CommandCode::PolicyAuthorizeNv),
+            TPM2_CC_EncryptDecrypt2 => Ok(CommandCode::EncryptDecrypt2),
+            TPM2_CC_AC_GetCapability => Ok(CommandCode::AcGetCapability),
+            TPM2_CC_AC_Send => Ok(CommandCode::AcSend),
+            TPM2_CC_Policy_AC_SendSelect => Ok(CommandCode::PolicyAcSendSelect),
+            _ => Err(Error::local_error(WrapperErrorKind::InvalidParam)),
+        }
+    }
+}
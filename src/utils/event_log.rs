@@ -0,0 +1,502 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! TCG PC Client firmware event log
+//!
+//! The firmware records each measurement it extends into the PCRs in an event log, which Linux
+//! exposes in `/sys/kernel/security/tpm0/binary_bios_measurements`. This module parses such a
+//! log, either in the SHA1 format or in the crypto-agile format, and replays it to compute the
+//! PCR values it implies. These can be compared with the values returned by `Context::pcr_read`
+//! to find out which PCRs, and possibly which events, do not match.
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use crate::utils::hash;
+use crate::utils::{DigestValues, PcrData, PcrSlot};
+use log::error;
+use std::convert::{TryFrom, TryInto};
+
+// Signature of the first event of a crypto-agile log.
+const SPEC_ID_EVENT_SIGNATURE: &[u8] = b"Spec ID Event03\0";
+// Signature of the event recording the locality from which the TPM was started.
+const STARTUP_LOCALITY_SIGNATURE: &[u8] = b"StartupLocality\0";
+
+/// Type of an event of the log, as defined by the TCG PC Client Platform Firmware Profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventType {
+    PrebootCert,
+    PostCode,
+    Unused,
+    NoAction,
+    Separator,
+    Action,
+    EventTag,
+    SCrtmContents,
+    SCrtmVersion,
+    CpuMicrocode,
+    PlatformConfigFlags,
+    TableOfDevices,
+    CompactHash,
+    Ipl,
+    IplPartitionData,
+    NonhostCode,
+    NonhostConfig,
+    NonhostInfo,
+    OmitBootDeviceEvents,
+    EfiEventBase,
+    EfiVariableDriverConfig,
+    EfiVariableBoot,
+    EfiBootServicesApplication,
+    EfiBootServicesDriver,
+    EfiRuntimeServicesDriver,
+    EfiGptEvent,
+    EfiAction,
+    EfiPlatformFirmwareBlob,
+    EfiHandoffTables,
+    EfiPlatformFirmwareBlob2,
+    EfiHandoffTables2,
+    EfiVariableBoot2,
+    EfiHcrtmEvent,
+    EfiVariableAuthority,
+    EfiSpdmFirmwareBlob,
+    EfiSpdmFirmwareConfig,
+    /// Event type not known by this crate.
+    Other(u32),
+}
+
+impl From<u32> for EventType {
+    fn from(event_type: u32) -> Self {
+        match event_type {
+            0x0000_0000 => EventType::PrebootCert,
+            0x0000_0001 => EventType::PostCode,
+            0x0000_0002 => EventType::Unused,
+            0x0000_0003 => EventType::NoAction,
+            0x0000_0004 => EventType::Separator,
+            0x0000_0005 => EventType::Action,
+            0x0000_0006 => EventType::EventTag,
+            0x0000_0007 => EventType::SCrtmContents,
+            0x0000_0008 => EventType::SCrtmVersion,
+            0x0000_0009 => EventType::CpuMicrocode,
+            0x0000_000a => EventType::PlatformConfigFlags,
+            0x0000_000b => EventType::TableOfDevices,
+            0x0000_000c => EventType::CompactHash,
+            0x0000_000d => EventType::Ipl,
+            0x0000_000e => EventType::IplPartitionData,
+            0x0000_000f => EventType::NonhostCode,
+            0x0000_0010 => EventType::NonhostConfig,
+            0x0000_0011 => EventType::NonhostInfo,
+            0x0000_0012 => EventType::OmitBootDeviceEvents,
+            0x8000_0000 => EventType::EfiEventBase,
+            0x8000_0001 => EventType::EfiVariableDriverConfig,
+            0x8000_0002 => EventType::EfiVariableBoot,
+            0x8000_0003 => EventType::EfiBootServicesApplication,
+            0x8000_0004 => EventType::EfiBootServicesDriver,
+            0x8000_0005 => EventType::EfiRuntimeServicesDriver,
+            0x8000_0006 => EventType::EfiGptEvent,
+            0x8000_0007 => EventType::EfiAction,
+            0x8000_0008 => EventType::EfiPlatformFirmwareBlob,
+            0x8000_0009 => EventType::EfiHandoffTables,
+            0x8000_000a => EventType::EfiPlatformFirmwareBlob2,
+            0x8000_000b => EventType::EfiHandoffTables2,
+            0x8000_000c => EventType::EfiVariableBoot2,
+            0x8000_0010 => EventType::EfiHcrtmEvent,
+            0x8000_00e0 => EventType::EfiVariableAuthority,
+            0x8000_00e1 => EventType::EfiSpdmFirmwareBlob,
+            0x8000_00e2 => EventType::EfiSpdmFirmwareConfig,
+            _ => EventType::Other(event_type),
+        }
+    }
+}
+
+impl From<EventType> for u32 {
+    fn from(event_type: EventType) -> Self {
+        match event_type {
+            EventType::PrebootCert => 0x0000_0000,
+            EventType::PostCode => 0x0000_0001,
+            EventType::Unused => 0x0000_0002,
+            EventType::NoAction => 0x0000_0003,
+            EventType::Separator => 0x0000_0004,
+            EventType::Action => 0x0000_0005,
+            EventType::EventTag => 0x0000_0006,
+            EventType::SCrtmContents => 0x0000_0007,
+            EventType::SCrtmVersion => 0x0000_0008,
+            EventType::CpuMicrocode => 0x0000_0009,
+            EventType::PlatformConfigFlags => 0x0000_000a,
+            EventType::TableOfDevices => 0x0000_000b,
+            EventType::CompactHash => 0x0000_000c,
+            EventType::Ipl => 0x0000_000d,
+            EventType::IplPartitionData => 0x0000_000e,
+            EventType::NonhostCode => 0x0000_000f,
+            EventType::NonhostConfig => 0x0000_0010,
+            EventType::NonhostInfo => 0x0000_0011,
+            EventType::OmitBootDeviceEvents => 0x0000_0012,
+            EventType::EfiEventBase => 0x8000_0000,
+            EventType::EfiVariableDriverConfig => 0x8000_0001,
+            EventType::EfiVariableBoot => 0x8000_0002,
+            EventType::EfiBootServicesApplication => 0x8000_0003,
+            EventType::EfiBootServicesDriver => 0x8000_0004,
+            EventType::EfiRuntimeServicesDriver => 0x8000_0005,
+            EventType::EfiGptEvent => 0x8000_0006,
+            EventType::EfiAction => 0x8000_0007,
+            EventType::EfiPlatformFirmwareBlob => 0x8000_0008,
+            EventType::EfiHandoffTables => 0x8000_0009,
+            EventType::EfiPlatformFirmwareBlob2 => 0x8000_000a,
+            EventType::EfiHandoffTables2 => 0x8000_000b,
+            EventType::EfiVariableBoot2 => 0x8000_000c,
+            EventType::EfiHcrtmEvent => 0x8000_0010,
+            EventType::EfiVariableAuthority => 0x8000_00e0,
+            EventType::EfiSpdmFirmwareBlob => 0x8000_00e1,
+            EventType::EfiSpdmFirmwareConfig => 0x8000_00e2,
+            EventType::Other(event_type) => event_type,
+        }
+    }
+}
+
+/// An event of the log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pcr_index: u32,
+    event_type: EventType,
+    digests: DigestValues,
+    data: Vec<u8>,
+}
+
+impl Event {
+    /// Get the index of the PCR extended by the event.
+    pub fn pcr_index(&self) -> u32 {
+        self.pcr_index
+    }
+
+    /// Get the type of the event.
+    pub fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    /// Get the digests extended into the PCR, one per bank.
+    ///
+    /// Digests computed with algorithms unknown to this crate are not included.
+    pub fn digests(&self) -> &DigestValues {
+        &self.digests
+    }
+
+    /// Get the event data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A PCR whose value differs from the one obtained by replaying the event log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcrMismatch {
+    /// Bank of the PCR.
+    pub hashing_algorithm: HashingAlgorithm,
+    /// The PCR.
+    pub pcr_slot: PcrSlot,
+    /// Value obtained by replaying the event log.
+    pub expected: Vec<u8>,
+    /// Value read from the TPM.
+    pub actual: Vec<u8>,
+    /// Indices in the log of the events extended into the PCR.
+    pub events: Vec<usize>,
+    /// If the value read from the TPM is obtained by replaying only the first events of `events`,
+    /// their number. The events which follow are then missing from the PCR.
+    pub matching_events: Option<usize>,
+}
+
+/// A parsed event log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLog {
+    crypto_agile: bool,
+    algorithms: Vec<HashingAlgorithm>,
+    events: Vec<Event>,
+}
+
+impl EventLog {
+    /// Parse a binary event log.
+    ///
+    /// The format is detected from the first event: crypto-agile logs start with a
+    /// `Spec ID Event03` event, other logs are parsed as SHA1 logs.
+    ///
+    /// # Errors
+    /// * if the log is empty or truncated, or if an event contains a digest computed with an
+    /// algorithm not listed in the header of a crypto-agile log, an `InvalidParam` wrapper error
+    /// is returned
+    pub fn parse(log: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(log);
+        if reader.is_empty() {
+            error!("Error: the event log is empty.");
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let first_event = reader.read_sha1_event()?;
+        let crypto_agile = first_event.event_type == EventType::NoAction
+            && first_event.data.starts_with(SPEC_ID_EVENT_SIGNATURE);
+        let mut events = Vec::new();
+        let algorithms = if crypto_agile {
+            let digest_sizes = parse_spec_id_event(&first_event.data)?;
+            events.push(first_event);
+            while !reader.is_empty() {
+                events.push(reader.read_crypto_agile_event(&digest_sizes)?);
+            }
+            digest_sizes
+                .iter()
+                .filter_map(|(algorithm_id, _)| HashingAlgorithm::try_from(*algorithm_id).ok())
+                .collect()
+        } else {
+            events.push(first_event);
+            while !reader.is_empty() {
+                events.push(reader.read_sha1_event()?);
+            }
+            vec![HashingAlgorithm::Sha1]
+        };
+
+        Ok(EventLog {
+            crypto_agile,
+            algorithms,
+            events,
+        })
+    }
+
+    /// Check if the log is in the crypto-agile format.
+    pub fn is_crypto_agile(&self) -> bool {
+        self.crypto_agile
+    }
+
+    /// Get the hashing algorithms of the banks measured in the log.
+    pub fn algorithms(&self) -> &[HashingAlgorithm] {
+        &self.algorithms
+    }
+
+    /// Get the events of the log, in order.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Compute the PCR values obtained by extending all the events of the log.
+    ///
+    /// Values are computed for every bank measured in the log which has a software
+    /// implementation (all but SM3), and for every PCR extended by at least one event.
+    ///
+    /// # Errors
+    /// * if an event extends a PCR with an index larger than 31, or does not contain a digest for
+    /// one of the banks, an `InvalidParam` wrapper error is returned
+    pub fn replay(&self) -> Result<PcrData> {
+        let mut pcr_data = PcrData::new();
+        for hashing_algorithm in self.replayable_algorithms() {
+            for pcr_index in self.extended_pcrs() {
+                let value = self
+                    .replay_pcr(hashing_algorithm, pcr_index)?
+                    .pop()
+                    .unwrap(); // the initial value is always present
+                pcr_data.insert(hashing_algorithm, PcrSlot::from_index(pcr_index)?, value);
+            }
+        }
+        Ok(pcr_data)
+    }
+
+    /// Compare the PCR values read from the TPM with the values obtained by replaying the log.
+    ///
+    /// Only the PCRs present in both are compared.
+    ///
+    /// # Errors
+    /// * errors encountered while replaying the log are returned, see `replay`
+    pub fn mismatches(&self, pcr_data: &PcrData) -> Result<Vec<PcrMismatch>> {
+        let mut mismatches = Vec::new();
+        for hashing_algorithm in self.replayable_algorithms() {
+            for pcr_index in self.extended_pcrs() {
+                let pcr_slot = PcrSlot::from_index(pcr_index)?;
+                let actual = match pcr_data.get(hashing_algorithm, pcr_slot) {
+                    Some(actual) => actual,
+                    None => continue,
+                };
+                let values = self.replay_pcr(hashing_algorithm, pcr_index)?;
+                let expected = &values[values.len() - 1];
+                if expected[..] == actual[..] {
+                    continue;
+                }
+                mismatches.push(PcrMismatch {
+                    hashing_algorithm,
+                    pcr_slot,
+                    expected: expected.clone(),
+                    actual: actual.to_vec(),
+                    events: self.pcr_events(pcr_index).map(|(index, _)| index).collect(),
+                    matching_events: values.iter().position(|value| value[..] == actual[..]),
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+
+    fn replayable_algorithms(&self) -> Vec<HashingAlgorithm> {
+        self.algorithms
+            .iter()
+            .cloned()
+            .filter(|algorithm| *algorithm != HashingAlgorithm::Sm3_256)
+            .collect()
+    }
+
+    // Indices of the PCRs extended by the log, sorted.
+    fn extended_pcrs(&self) -> Vec<u32> {
+        let mut pcrs: Vec<u32> = self
+            .events
+            .iter()
+            .filter(|event| event.event_type != EventType::NoAction || is_startup_locality(event))
+            .map(|event| event.pcr_index)
+            .collect();
+        pcrs.sort();
+        pcrs.dedup();
+        pcrs
+    }
+
+    // Events extended into a PCR, along with their index in the log.
+    fn pcr_events(&self, pcr_index: u32) -> impl Iterator<Item = (usize, &Event)> {
+        self.events.iter().enumerate().filter(move |(_, event)| {
+            event.pcr_index == pcr_index && event.event_type != EventType::NoAction
+        })
+    }
+
+    // Successive values of a PCR: its initial value, then its value after each of its events.
+    fn replay_pcr(
+        &self,
+        hashing_algorithm: HashingAlgorithm,
+        pcr_index: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        let mut value = vec![0; hashing_algorithm.digest_size()];
+        // PCR 0 starts with the locality in its last byte if it was not 0
+        if let Some(event) = self
+            .events
+            .iter()
+            .find(|event| event.pcr_index == pcr_index && is_startup_locality(event))
+        {
+            let last = value.len() - 1;
+            value[last] = event.data[STARTUP_LOCALITY_SIGNATURE.len()];
+        }
+        let mut values = vec![value];
+        for (index, event) in self.pcr_events(pcr_index) {
+            let digest = event.digests.get(hashing_algorithm).ok_or_else(|| {
+                error!(
+                    "Error: event {} has no {:?} digest.",
+                    index, hashing_algorithm
+                );
+                Error::local_error(WrapperErrorKind::InvalidParam)
+            })?;
+            let previous = &values[values.len() - 1];
+            let value = hash::hash(hashing_algorithm, &[previous, digest])?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+}
+
+fn is_startup_locality(event: &Event) -> bool {
+    event.event_type == EventType::NoAction
+        && event.pcr_index == 0
+        && event.data.len() > STARTUP_LOCALITY_SIGNATURE.len()
+        && event.data.starts_with(STARTUP_LOCALITY_SIGNATURE)
+}
+
+// Parse the algorithms and digest sizes listed in a `TCG_EfiSpecIdEvent`.
+fn parse_spec_id_event(data: &[u8]) -> Result<Vec<(u16, u16)>> {
+    let mut reader = Reader::new(data);
+    // signature, platformClass, specVersionMinor, specVersionMajor, specErrata, uintnSize
+    let _ = reader.read_bytes(SPEC_ID_EVENT_SIGNATURE.len() + 8)?;
+    let number_of_algorithms = reader.read_u32()?;
+    let mut digest_sizes = Vec::new();
+    for _ in 0..number_of_algorithms {
+        let algorithm_id = reader.read_u16()?;
+        let digest_size = reader.read_u16()?;
+        digest_sizes.push((algorithm_id, digest_size));
+    }
+    let vendor_info_size = reader.read_u8()?;
+    let _ = reader.read_bytes(vendor_info_size.into())?;
+    Ok(digest_sizes)
+}
+
+// Reader for the little-endian values of the event log.
+#[derive(Debug)]
+struct Reader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Reader { buffer }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.buffer.len() {
+            error!("Error: the event log is truncated.");
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let (bytes, rest) = self.buffer.split_at(len);
+        self.buffer = rest;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    // Read a `TCG_PCR_EVENT`.
+    fn read_sha1_event(&mut self) -> Result<Event> {
+        let pcr_index = self.read_u32()?;
+        let event_type = EventType::from(self.read_u32()?);
+        let mut digests = DigestValues::new();
+        digests.set(
+            HashingAlgorithm::Sha1,
+            self.read_bytes(HashingAlgorithm::Sha1.digest_size())?,
+        )?;
+        let data = self.read_event_data()?;
+        Ok(Event {
+            pcr_index,
+            event_type,
+            digests,
+            data,
+        })
+    }
+
+    // Read a `TCG_PCR_EVENT2`.
+    fn read_crypto_agile_event(&mut self, digest_sizes: &[(u16, u16)]) -> Result<Event> {
+        let pcr_index = self.read_u32()?;
+        let event_type = EventType::from(self.read_u32()?);
+        let mut digests = DigestValues::new();
+        for _ in 0..self.read_u32()? {
+            let algorithm_id = self.read_u16()?;
+            let digest_size = match digest_sizes.iter().find(|(id, _)| *id == algorithm_id) {
+                Some((_, digest_size)) => *digest_size,
+                None => {
+                    error!(
+                        "Error: digest with algorithm 0x{:04x} not listed in the log header.",
+                        algorithm_id
+                    );
+                    return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+                }
+            };
+            let digest = self.read_bytes(digest_size.into())?;
+            if let Ok(hashing_algorithm) = HashingAlgorithm::try_from(algorithm_id) {
+                digests.set(hashing_algorithm, digest)?;
+            }
+        }
+        let data = self.read_event_data()?;
+        Ok(Event {
+            pcr_index,
+            event_type,
+            digests,
+            data,
+        })
+    }
+
+    fn read_event_data(&mut self) -> Result<Vec<u8>> {
+        let size = self.read_u32()?;
+        Ok(self.read_bytes(size as usize)?.to_vec())
+    }
+}
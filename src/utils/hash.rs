@@ -0,0 +1,87 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Software implementations of the TPM hashing algorithms
+//!
+//! Used to replicate in software computations otherwise performed by the TPM, so that their
+//! results can be predicted or verified without one.
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use log::error;
+use sha2::Digest;
+
+/// Incremental hash computation with one of the algorithms supported by the TPM.
+#[derive(Debug, Clone)]
+pub enum Hasher {
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Sha384(sha2::Sha384),
+    Sha512(sha2::Sha512),
+    Sha3_256(sha3::Sha3_256),
+    Sha3_384(sha3::Sha3_384),
+    Sha3_512(sha3::Sha3_512),
+}
+
+impl Hasher {
+    /// Start a new hash computation.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn new(hashing_algorithm: HashingAlgorithm) -> Result<Self> {
+        match hashing_algorithm {
+            HashingAlgorithm::Sha1 => Ok(Hasher::Sha1(sha1::Sha1::new())),
+            HashingAlgorithm::Sha256 => Ok(Hasher::Sha256(sha2::Sha256::new())),
+            HashingAlgorithm::Sha384 => Ok(Hasher::Sha384(sha2::Sha384::new())),
+            HashingAlgorithm::Sha512 => Ok(Hasher::Sha512(sha2::Sha512::new())),
+            HashingAlgorithm::Sha3_256 => Ok(Hasher::Sha3_256(sha3::Sha3_256::new())),
+            HashingAlgorithm::Sha3_384 => Ok(Hasher::Sha3_384(sha3::Sha3_384::new())),
+            HashingAlgorithm::Sha3_512 => Ok(Hasher::Sha3_512(sha3::Sha3_512::new())),
+            HashingAlgorithm::Sm3_256 => {
+                error!(
+                    "Error: no software implementation of {:?}.",
+                    hashing_algorithm
+                );
+                Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+            }
+        }
+    }
+
+    /// Add data to the hash computation.
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha384(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Sha3_256(hasher) => hasher.update(data),
+            Hasher::Sha3_384(hasher) => hasher.update(data),
+            Hasher::Sha3_512(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Finish the computation and return the digest.
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha384(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha512(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha3_256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha3_384(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha3_512(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// Compute the digest of the concatenation of `data`.
+///
+/// # Errors
+/// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper error
+/// is returned
+pub fn hash(hashing_algorithm: HashingAlgorithm, data: &[&[u8]]) -> Result<Vec<u8>> {
+    let mut hasher = Hasher::new(hashing_algorithm)?;
+    for part in data {
+        hasher.update(part);
+    }
+    Ok(hasher.finalize())
+}
@@ -0,0 +1,536 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Linux IMA measurement list
+//!
+//! The Linux Integrity Measurement Architecture measures the files accessed at runtime into PCR
+//! 10 and records the measurements in a list, exposed in ASCII form in
+//! `/sys/kernel/security/ima/ascii_runtime_measurements` and in binary form in
+//! `/sys/kernel/security/ima/binary_runtime_measurements`. This module parses both forms for the
+//! `ima`, `ima-ng` and `ima-sig` templates, recomputes the template hashes, replays the list to
+//! compute the expected PCR values, and checks the measured files against an allowlist.
+//!
+//! The binary form is parsed as written by a little-endian kernel.
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use crate::utils::hash;
+use crate::utils::{PcrData, PcrSlot};
+use log::error;
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+// Size of the buffer in which the file name is hashed with the `ima` template.
+const IMA_NAME_SIZE: usize = 256;
+
+/// Template of an entry of the measurement list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImaTemplate {
+    /// `ima`: SHA1 digest of the file and its name, truncated to 255 bytes.
+    Ima,
+    /// `ima-ng`: digest of the file with its algorithm, and its name.
+    ImaNg,
+    /// `ima-sig`: same as `ima-ng`, along with the signature of the file if any.
+    ImaSig,
+}
+
+impl ImaTemplate {
+    fn from_name(name: &[u8]) -> Result<Self> {
+        match name {
+            b"ima" => Ok(ImaTemplate::Ima),
+            b"ima-ng" => Ok(ImaTemplate::ImaNg),
+            b"ima-sig" => Ok(ImaTemplate::ImaSig),
+            _ => {
+                error!(
+                    "Error: unsupported IMA template {}.",
+                    String::from_utf8_lossy(name)
+                );
+                Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+            }
+        }
+    }
+}
+
+/// An entry of the measurement list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImaEntry {
+    pcr_index: u32,
+    template_hash: Vec<u8>,
+    template: ImaTemplate,
+    file_digest_algorithm: HashingAlgorithm,
+    file_digest: Vec<u8>,
+    file_name: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl ImaEntry {
+    /// Get the index of the PCR extended with the entry.
+    pub fn pcr_index(&self) -> u32 {
+        self.pcr_index
+    }
+
+    /// Get the template hash recorded in the list.
+    pub fn template_hash(&self) -> &[u8] {
+        &self.template_hash
+    }
+
+    /// Get the template of the entry.
+    pub fn template(&self) -> ImaTemplate {
+        self.template
+    }
+
+    /// Get the algorithm with which the file digest was computed.
+    pub fn file_digest_algorithm(&self) -> HashingAlgorithm {
+        self.file_digest_algorithm
+    }
+
+    /// Get the digest of the file.
+    pub fn file_digest(&self) -> &[u8] {
+        &self.file_digest
+    }
+
+    /// Get the name of the file, as recorded by the kernel.
+    pub fn file_name(&self) -> &[u8] {
+        &self.file_name
+    }
+
+    /// Get the signature of the file; empty if there is none or if the template does not record
+    /// it.
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Check if the entry records a measurement violation, which the kernel marks with a
+    /// template hash of zeros.
+    pub fn is_violation(&self) -> bool {
+        self.template_hash.iter().all(|byte| *byte == 0)
+    }
+
+    /// Get the template data over which the template hash is computed.
+    pub fn template_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self.template {
+            ImaTemplate::Ima => {
+                data.extend_from_slice(&self.file_digest);
+                let mut name = [0_u8; IMA_NAME_SIZE];
+                let len = self.file_name.len().min(IMA_NAME_SIZE - 1);
+                name[..len].copy_from_slice(&self.file_name[..len]);
+                data.extend_from_slice(&name);
+            }
+            ImaTemplate::ImaNg | ImaTemplate::ImaSig => {
+                let mut digest_field = algorithm_name(self.file_digest_algorithm)
+                    .as_bytes()
+                    .to_vec();
+                digest_field.extend_from_slice(b":\0");
+                digest_field.extend_from_slice(&self.file_digest);
+                let mut name_field = self.file_name.clone();
+                name_field.push(0);
+                let mut fields = vec![digest_field, name_field];
+                if self.template == ImaTemplate::ImaSig {
+                    fields.push(self.signature.clone());
+                }
+                for field in fields {
+                    data.extend_from_slice(&(field.len() as u32).to_le_bytes());
+                    data.extend_from_slice(&field);
+                }
+            }
+        }
+        data
+    }
+
+    /// Compute the template hash of the entry with the given algorithm.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn compute_template_hash(&self, hashing_algorithm: HashingAlgorithm) -> Result<Vec<u8>> {
+        hash::hash(hashing_algorithm, &[&self.template_data()])
+    }
+}
+
+/// Digests allowed for each file name.
+#[derive(Debug, Default, Clone)]
+pub struct ImaAllowlist {
+    digests: HashMap<Vec<u8>, Vec<(HashingAlgorithm, Vec<u8>)>>,
+}
+
+impl ImaAllowlist {
+    /// Create an empty allowlist.
+    pub fn new() -> Self {
+        ImaAllowlist {
+            digests: HashMap::new(),
+        }
+    }
+
+    /// Allow a digest for a file name; several digests can be allowed for the same name.
+    pub fn allow(&mut self, file_name: &str, hashing_algorithm: HashingAlgorithm, digest: &[u8]) {
+        self.digests
+            .entry(file_name.as_bytes().to_vec())
+            .or_default()
+            .push((hashing_algorithm, digest.to_vec()));
+    }
+
+    /// Check if the file name and digest of an entry are allowed.
+    pub fn is_allowed(&self, entry: &ImaEntry) -> bool {
+        match self.digests.get(&entry.file_name) {
+            Some(digests) => digests.iter().any(|(algorithm, digest)| {
+                *algorithm == entry.file_digest_algorithm && *digest == entry.file_digest
+            }),
+            None => false,
+        }
+    }
+}
+
+/// A parsed measurement list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImaLog {
+    template_hash_algorithm: HashingAlgorithm,
+    entries: Vec<ImaEntry>,
+}
+
+impl ImaLog {
+    /// Parse the ASCII form of a measurement list.
+    ///
+    /// `template_hash_algorithm` is the algorithm of the template hashes of the list: SHA1 for
+    /// the historical lists, or the algorithm of the bank for the per-bank lists.
+    ///
+    /// Each line is made of the PCR index, the template hash, the template name, the file digest
+    /// and the file name; `ima-sig` lines end with the signature, if any. As the file name may
+    /// contain spaces, the last field of an `ima-sig` line is taken as the signature only if it
+    /// is empty or made of hexadecimal digits.
+    ///
+    /// # Errors
+    /// * if a line is malformed, an `InvalidParam` wrapper error is returned
+    /// * if a line uses another template, or a file digest algorithm unknown to this crate, an
+    /// `UnsupportedParam` wrapper error is returned
+    pub fn parse_ascii(log: &str, template_hash_algorithm: HashingAlgorithm) -> Result<Self> {
+        let mut entries = Vec::new();
+        for line in log.lines().filter(|line| !line.is_empty()) {
+            let mut fields = line.splitn(5, ' ');
+            let mut next_field = || {
+                fields.next().ok_or_else(|| {
+                    error!("Error: malformed IMA measurement: {}.", line);
+                    Error::local_error(WrapperErrorKind::InvalidParam)
+                })
+            };
+            let pcr_index = next_field()?.parse::<u32>().map_err(|e| {
+                error!("Error: invalid IMA PCR index: {}.", e);
+                Error::local_error(WrapperErrorKind::InvalidParam)
+            })?;
+            let template_hash = from_hex(next_field()?)?;
+            let template = ImaTemplate::from_name(next_field()?.as_bytes())?;
+            let digest = next_field()?;
+            let name = next_field()?;
+            let (file_digest_algorithm, file_digest) = match template {
+                ImaTemplate::Ima => (HashingAlgorithm::Sha1, from_hex(digest)?),
+                ImaTemplate::ImaNg | ImaTemplate::ImaSig => {
+                    let mut parts = digest.splitn(2, ':');
+                    let algorithm = parse_algorithm_name(parts.next().unwrap().as_bytes())?; // splitn always returns at least one element
+                    let file_digest = from_hex(parts.next().ok_or_else(|| {
+                        error!("Error: malformed IMA file digest: {}.", digest);
+                        Error::local_error(WrapperErrorKind::InvalidParam)
+                    })?)?;
+                    (algorithm, file_digest)
+                }
+            };
+            // The signature field is separated by a space even when empty
+            let (file_name, signature) = match (template, name.rfind(' ')) {
+                (ImaTemplate::ImaSig, Some(index)) if index + 1 == name.len() => {
+                    (&name[..index], Vec::new())
+                }
+                (ImaTemplate::ImaSig, Some(index)) if is_hex(&name[index + 1..]) => {
+                    (&name[..index], from_hex(&name[index + 1..])?)
+                }
+                _ => (name, Vec::new()),
+            };
+            entries.push(ImaEntry {
+                pcr_index,
+                template_hash,
+                template,
+                file_digest_algorithm,
+                file_digest,
+                file_name: file_name.as_bytes().to_vec(),
+                signature,
+            });
+        }
+        check_template_hashes(&entries, template_hash_algorithm)?;
+
+        Ok(ImaLog {
+            template_hash_algorithm,
+            entries,
+        })
+    }
+
+    /// Parse the binary form of a measurement list.
+    ///
+    /// `template_hash_algorithm` is the algorithm of the template hashes of the list, see
+    /// `parse_ascii`.
+    ///
+    /// # Errors
+    /// * if the list is truncated or malformed, an `InvalidParam` wrapper error is returned
+    /// * if an entry uses another template, or a file digest algorithm unknown to this crate, an
+    /// `UnsupportedParam` wrapper error is returned
+    pub fn parse_binary(log: &[u8], template_hash_algorithm: HashingAlgorithm) -> Result<Self> {
+        let mut reader = Reader::new(log);
+        let mut entries = Vec::new();
+        while !reader.is_empty() {
+            let pcr_index = reader.read_u32()?;
+            let template_hash = reader
+                .read_bytes(template_hash_algorithm.digest_size())?
+                .to_vec();
+            let template = ImaTemplate::from_name(reader.read_sized()?)?;
+            let entry = match template {
+                ImaTemplate::Ima => ImaEntry {
+                    pcr_index,
+                    template_hash,
+                    template,
+                    file_digest_algorithm: HashingAlgorithm::Sha1,
+                    file_digest: reader
+                        .read_bytes(HashingAlgorithm::Sha1.digest_size())?
+                        .to_vec(),
+                    file_name: reader.read_sized()?.to_vec(),
+                    signature: Vec::new(),
+                },
+                ImaTemplate::ImaNg | ImaTemplate::ImaSig => {
+                    let mut fields = Reader::new(reader.read_sized()?);
+                    let digest_field = fields.read_sized()?;
+                    let name_field = fields.read_sized()?;
+                    let signature = if template == ImaTemplate::ImaSig {
+                        fields.read_sized()?.to_vec()
+                    } else {
+                        Vec::new()
+                    };
+                    if !fields.is_empty() {
+                        error!("Error: trailing bytes in IMA template data.");
+                        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+                    }
+                    let (file_digest_algorithm, file_digest) = parse_digest_field(digest_field)?;
+                    ImaEntry {
+                        pcr_index,
+                        template_hash,
+                        template,
+                        file_digest_algorithm,
+                        file_digest,
+                        file_name: name_field
+                            .split(|byte| *byte == 0)
+                            .next()
+                            .unwrap() // split always returns at least one element
+                            .to_vec(),
+                        signature,
+                    }
+                }
+            };
+            entries.push(entry);
+        }
+        check_template_hashes(&entries, template_hash_algorithm)?;
+
+        Ok(ImaLog {
+            template_hash_algorithm,
+            entries,
+        })
+    }
+
+    /// Get the algorithm of the template hashes of the list.
+    pub fn template_hash_algorithm(&self) -> HashingAlgorithm {
+        self.template_hash_algorithm
+    }
+
+    /// Get the entries of the list, in order.
+    pub fn entries(&self) -> &[ImaEntry] {
+        &self.entries
+    }
+
+    /// Get the indices of the entries whose recorded template hash does not match their data.
+    ///
+    /// Violations are not included.
+    ///
+    /// # Errors
+    /// * if the template hash algorithm has no software implementation (SM3), an
+    /// `UnsupportedParam` wrapper error is returned
+    pub fn invalid_template_hashes(&self) -> Result<Vec<usize>> {
+        let mut invalid = Vec::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if !entry.is_violation()
+                && entry.compute_template_hash(self.template_hash_algorithm)? != entry.template_hash
+            {
+                invalid.push(index);
+            }
+        }
+        Ok(invalid)
+    }
+
+    /// Compute the values of the PCRs of a bank obtained by extending all the entries of the
+    /// list.
+    ///
+    /// The template hashes are recomputed with the algorithm of the bank; violations are
+    /// extended as a digest of 0xFF bytes, as done by the kernel.
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    /// * if an entry extends a PCR with an index larger than 31, an `InvalidParam` wrapper error
+    /// is returned
+    pub fn replay(&self, hashing_algorithm: HashingAlgorithm) -> Result<PcrData> {
+        let mut values: Vec<(u32, Vec<u8>)> = Vec::new();
+        for entry in self.entries.iter() {
+            let template_hash = if entry.is_violation() {
+                vec![0xff; hashing_algorithm.digest_size()]
+            } else {
+                entry.compute_template_hash(hashing_algorithm)?
+            };
+            let index = match values
+                .iter()
+                .position(|(pcr_index, _)| *pcr_index == entry.pcr_index)
+            {
+                Some(index) => index,
+                None => {
+                    values.push((entry.pcr_index, vec![0; hashing_algorithm.digest_size()]));
+                    values.len() - 1
+                }
+            };
+            let value = hash::hash(hashing_algorithm, &[&values[index].1, &template_hash])?;
+            values[index].1 = value;
+        }
+        let mut pcr_data = PcrData::new();
+        for (pcr_index, value) in values {
+            pcr_data.insert(hashing_algorithm, PcrSlot::from_index(pcr_index)?, value);
+        }
+        Ok(pcr_data)
+    }
+
+    /// Get the indices of the entries whose file name and digest are not in the allowlist.
+    ///
+    /// Violations are included, unless their (zero) digest is allowed.
+    pub fn disallowed_entries(&self, allowlist: &ImaAllowlist) -> Vec<usize> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !allowlist.is_allowed(entry))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+fn check_template_hashes(
+    entries: &[ImaEntry],
+    template_hash_algorithm: HashingAlgorithm,
+) -> Result<()> {
+    if entries
+        .iter()
+        .any(|entry| entry.template_hash.len() != template_hash_algorithm.digest_size())
+    {
+        error!(
+            "Error: IMA template hash is not a {:?} digest.",
+            template_hash_algorithm
+        );
+        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+    }
+    Ok(())
+}
+
+// Name of the algorithm in the `d-ng` field.
+fn algorithm_name(hashing_algorithm: HashingAlgorithm) -> &'static str {
+    match hashing_algorithm {
+        HashingAlgorithm::Sha1 => "sha1",
+        HashingAlgorithm::Sha256 => "sha256",
+        HashingAlgorithm::Sha384 => "sha384",
+        HashingAlgorithm::Sha512 => "sha512",
+        HashingAlgorithm::Sm3_256 => "sm3",
+        HashingAlgorithm::Sha3_256 => "sha3-256",
+        HashingAlgorithm::Sha3_384 => "sha3-384",
+        HashingAlgorithm::Sha3_512 => "sha3-512",
+    }
+}
+
+fn parse_algorithm_name(name: &[u8]) -> Result<HashingAlgorithm> {
+    match name {
+        b"sha1" => Ok(HashingAlgorithm::Sha1),
+        b"sha256" => Ok(HashingAlgorithm::Sha256),
+        b"sha384" => Ok(HashingAlgorithm::Sha384),
+        b"sha512" => Ok(HashingAlgorithm::Sha512),
+        b"sm3" => Ok(HashingAlgorithm::Sm3_256),
+        b"sha3-256" => Ok(HashingAlgorithm::Sha3_256),
+        b"sha3-384" => Ok(HashingAlgorithm::Sha3_384),
+        b"sha3-512" => Ok(HashingAlgorithm::Sha3_512),
+        _ => {
+            error!(
+                "Error: unsupported IMA digest algorithm {}.",
+                String::from_utf8_lossy(name)
+            );
+            Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+        }
+    }
+}
+
+// Parse a `d-ng` field: the algorithm name, followed by ":\0" and the digest.
+fn parse_digest_field(field: &[u8]) -> Result<(HashingAlgorithm, Vec<u8>)> {
+    let separator = field
+        .windows(2)
+        .position(|bytes| bytes == b":\0")
+        .ok_or_else(|| {
+            error!("Error: malformed IMA file digest.");
+            Error::local_error(WrapperErrorKind::InvalidParam)
+        })?;
+    let hashing_algorithm = parse_algorithm_name(&field[..separator])?;
+    let digest = field[separator + 2..].to_vec();
+    if digest.len() != hashing_algorithm.digest_size() {
+        error!(
+            "Error: IMA file digest is not a {:?} digest.",
+            hashing_algorithm
+        );
+        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+    }
+    Ok((hashing_algorithm, digest))
+}
+
+fn is_hex(hex: &str) -> bool {
+    !hex.is_empty() && hex.len() & 1 == 0 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if !is_hex(hex) {
+        error!(
+            "Error: invalid hexadecimal value in IMA measurement: {}.",
+            hex
+        );
+        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+    }
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()) // checked above
+        .collect())
+}
+
+// Reader for the little-endian values of the binary list.
+#[derive(Debug)]
+struct Reader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buffer: &'a [u8]) -> Self {
+        Reader { buffer }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.buffer.len() {
+            error!("Error: the IMA measurement list is truncated.");
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let (bytes, rest) = self.buffer.split_at(len);
+        self.buffer = rest;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap())) // should not fail given the length read
+    }
+
+    // Read a value preceded by its 32-bit length.
+    fn read_sized(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_u32()?;
+        self.read_bytes(len as usize)
+    }
+}
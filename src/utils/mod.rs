@@ -9,8 +9,18 @@
 //! type name. Unions are converted to Rust `enum`s by dropping the `TPMU` qualifier and appending
 //! `Union`.
 pub mod algorithm_specifiers;
+pub mod attest;
+pub mod audit;
+pub mod capabilities;
+pub mod command_code;
+pub mod event_log;
+pub mod hash;
+pub mod ima;
+pub mod nv;
+pub mod policy;
 pub mod tags;
 pub mod tickets;
+pub mod verify;
 
 use crate::constants::*;
 use crate::response_code::{Error, Result, WrapperErrorKind};
@@ -22,8 +32,8 @@ use log::error;
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
+use tickets::CreationTicket;
 
 /// Helper for building `TPM2B_PUBLIC` values out of its subcomponents.
 ///
@@ -601,6 +611,50 @@ impl ObjectAttributes {
     }
 }
 
+bitfield! {
+    /// Rust native wrapper for `TPMA_LOCALITY` values.
+    ///
+    /// The value either selects any combination of the localities 0 to 4, or holds a single
+    /// extended locality (32 to 255).
+    #[derive(Copy, Clone, PartialEq)]
+    pub struct LocalityAttributes(TPMA_LOCALITY);
+    impl Debug;
+    // Locality flags
+    pub locality_zero, set_locality_zero: 0;
+    pub locality_one, set_locality_one: 1;
+    pub locality_two, set_locality_two: 2;
+    pub locality_three, set_locality_three: 3;
+    pub locality_four, set_locality_four: 4;
+    u8, extended, _: 7, 5;
+}
+
+impl LocalityAttributes {
+    /// Create attributes selecting a single locality.
+    ///
+    /// # Errors
+    /// * if `locality` is between 5 and 31, which are not valid localities, an `InvalidParam`
+    /// wrapper error is returned
+    pub fn from_locality(locality: u8) -> Result<Self> {
+        match locality {
+            0..=4 => Ok(LocalityAttributes(1 << locality)),
+            5..=31 => {
+                error!("Error: {} is not a valid locality.", locality);
+                Err(Error::local_error(WrapperErrorKind::InvalidParam))
+            }
+            _ => Ok(LocalityAttributes(locality)),
+        }
+    }
+
+    /// Get the extended locality held by the attributes, if any.
+    pub fn extended_locality(&self) -> Option<u8> {
+        if self.extended() != 0 {
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+}
+
 /// Rust enum representation of `TPMU_PUBLIC_ID`.
 // Most of the field types are from bindgen which does not implement Debug on them.
 #[allow(missing_debug_implementations)]
@@ -854,6 +908,30 @@ impl AsymSchemeUnion {
     }
 }
 
+/// Description of the creation of an object, returned alongside it by the TPM.
+///
+/// The hash and ticket can be passed to `Context::certify_creation` to prove that the object was
+/// created by the TPM, with the given creation data.
+#[derive(Clone)]
+pub struct KeyCreationData {
+    /// The `TPMS_CREATION_DATA` describing the environment in which the object was created.
+    pub creation_data: TPM2B_CREATION_DATA,
+    /// Digest of the creation data, using the name algorithm of the object.
+    pub creation_hash: Vec<u8>,
+    /// Ticket binding the creation data to the object.
+    pub creation_ticket: CreationTicket,
+}
+
+// `TPM2B_CREATION_DATA` is from bindgen which does not implement Debug on it.
+impl std::fmt::Debug for KeyCreationData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyCreationData")
+            .field("creation_hash", &self.creation_hash)
+            .field("creation_ticket", &self.creation_ticket)
+            .finish()
+    }
+}
+
 /// Rust native representation of an asymmetric signature.
 ///
 /// The structure contains the signature as a byte vector and the scheme with which the signature
@@ -1108,6 +1186,22 @@ impl TpmaSessionBuilder {
         self
     }
 
+    /// Make the session an audit session.
+    pub fn with_audit(self) -> Self {
+        self.with_flag(TPMA_SESSION_AUDIT)
+    }
+
+    /// Make the session an audit session, only allowing the command if the session is
+    /// exclusive, i.e. if no other command was executed since the last one it audited.
+    pub fn with_audit_exclusive(self) -> Self {
+        self.with_flag(TPMA_SESSION_AUDIT | TPMA_SESSION_AUDITEXCLUSIVE)
+    }
+
+    /// Make the session an audit session, initializing its audit digest with the command.
+    pub fn with_audit_reset(self) -> Self {
+        self.with_flag(TPMA_SESSION_AUDIT | TPMA_SESSION_AUDITRESET)
+    }
+
     /// Function used to add masks.
     pub fn with_mask(mut self, mask: TPMA_SESSION) -> Self {
         self.mask = Some(self.mask.unwrap_or(0) | mask);
@@ -1265,6 +1359,32 @@ pub fn create_restricted_decryption_rsa_public(
         .build()
 }
 
+/// Create the TPM2B_PUBLIC structure for an ECC restricted decryption key.
+///
+/// * `symmetric` - Cipher to be used for decrypting children of the key
+/// * `curve` - identifier of the precise curve to be used with the key
+pub fn create_restricted_decryption_ecc_public(
+    symmetric: Cipher,
+    curve: EllipticCurve,
+) -> Result<TPM2B_PUBLIC> {
+    let ecc_parms = TpmsEccParmsBuilder::new_restricted_decryption_key(symmetric, curve).build()?;
+    let mut object_attributes = ObjectAttributes(0);
+    object_attributes.set_fixed_tpm(true);
+    object_attributes.set_fixed_parent(true);
+    object_attributes.set_sensitive_data_origin(true);
+    object_attributes.set_user_with_auth(true);
+    object_attributes.set_decrypt(true);
+    object_attributes.set_sign_encrypt(false);
+    object_attributes.set_restricted(true);
+
+    Tpm2BPublicBuilder::new()
+        .with_type(TPM2_ALG_ECC)
+        .with_name_alg(TPM2_ALG_SHA256)
+        .with_object_attributes(object_attributes)
+        .with_parms(PublicParmsUnion::EccDetail(ecc_parms))
+        .build()
+}
+
 /// Create the TPM2B_PUBLIC structure for an RSA unrestricted signing key.
 ///
 /// * `scheme` - Asymmetric scheme to be used for signing
@@ -1322,7 +1442,7 @@ pub fn create_unrestricted_signing_ecc_public(
 }
 
 // Enum with the bit flag for each PCR slot.
-#[derive(BitFlags, Hash, Debug, PartialEq, Clone, Copy)]
+#[derive(BitFlags, Hash, Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(u32)]
 pub enum PcrSlot {
     Slot0 = 0x0000_0001,
@@ -1349,10 +1469,42 @@ pub enum PcrSlot {
     Slot21 = 0x0020_0000,
     Slot22 = 0x0040_0000,
     Slot23 = 0x0080_0000,
+    Slot24 = 0x0100_0000,
+    Slot25 = 0x0200_0000,
+    Slot26 = 0x0400_0000,
+    Slot27 = 0x0800_0000,
+    Slot28 = 0x1000_0000,
+    Slot29 = 0x2000_0000,
+    Slot30 = 0x4000_0000,
+    Slot31 = 0x8000_0000,
+}
+
+impl PcrSlot {
+    /// Get the slot of the PCR with the given index.
+    ///
+    /// # Errors
+    /// * if the index is larger than 31, an `InvalidParam` wrapper error is returned
+    pub fn from_index(index: u32) -> Result<PcrSlot> {
+        if index > 31 {
+            error!("Error: invalid PCR index {}.", index);
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        // There is exactly one slot for each bit
+        Ok(BitFlags::<PcrSlot>::try_from(1_u32 << index)
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap())
+    }
+
+    /// Get the index of the PCR.
+    pub fn index(self) -> u32 {
+        (self as u32).trailing_zeros()
+    }
 }
 
 // Enum with the possible values for sizeofSelect.
-#[derive(FromPrimitive, ToPrimitive, Debug, Copy, Clone, PartialEq)]
+#[derive(FromPrimitive, ToPrimitive, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum PcrSelectSize {
     OneByte = 1,
@@ -1370,6 +1522,18 @@ impl Default for PcrSelectSize {
     }
 }
 
+impl PcrSelectSize {
+    // The smallest size able to hold the given PCR slots.
+    fn minimum_for(pcr_slots: BitFlags<PcrSlot>) -> PcrSelectSize {
+        match pcr_slots.bits() {
+            0..=0x0000_00ff => PcrSelectSize::OneByte,
+            0x0000_0100..=0x0000_ffff => PcrSelectSize::TwoBytes,
+            0x0001_0000..=0x00ff_ffff => PcrSelectSize::ThreeBytes,
+            _ => PcrSelectSize::FourBytes,
+        }
+    }
+}
+
 /// A struct representing pcr selections
 ///
 /// The minimum number of octets allowed in a TPMS_PCR_SELECT.sizeOfSelect
@@ -1377,19 +1541,106 @@ impl Default for PcrSelectSize {
 /// number of PCR required by the platform-specific
 /// specification with which the TPM is compliant or by the implementer if
 /// not adhering to a platform-specific specification.
-#[derive(Debug, Default, Clone)]
+///
+/// Each bank has its own sizeofSelect. The banks are kept in the order in
+/// which they were first selected, which is also the order used when
+/// converting to a TPML_PCR_SELECTION.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct PcrSelections {
-    size_of_select: PcrSelectSize,
-    items: HashMap<HashingAlgorithm, BitFlags<PcrSlot>>,
+    items: Vec<(HashingAlgorithm, PcrSelectSize, BitFlags<PcrSlot>)>,
+}
+
+impl PcrSelections {
+    /// Check if no PCR slot is selected in any bank.
+    pub fn is_empty(&self) -> bool {
+        self.items
+            .iter()
+            .all(|(_, _, pcr_slots)| pcr_slots.is_empty())
+    }
+
+    /// Iterate over the banks, in order, with their size of select and selected PCR slots.
+    pub fn iter(
+        &self,
+    ) -> impl Iterator<Item = (HashingAlgorithm, PcrSelectSize, Vec<PcrSlot>)> + '_ {
+        self.items
+            .iter()
+            .map(|(hash_algorithm, size_of_select, pcr_slots)| {
+                (*hash_algorithm, *size_of_select, pcr_slots.iter().collect())
+            })
+    }
+
+    /// Remove from the selection the PCR slots selected in `other`.
+    ///
+    /// Banks left without any selected slot are removed.
+    pub fn subtract(&mut self, other: &PcrSelections) {
+        for (hash_algorithm, _, pcr_slots) in other.items.iter() {
+            if let Some((_, _, selected_pcr_slots)) = self
+                .items
+                .iter_mut()
+                .find(|(algorithm, _, _)| algorithm == hash_algorithm)
+            {
+                selected_pcr_slots.remove(*pcr_slots);
+            }
+        }
+        self.items.retain(|(_, _, pcr_slots)| !pcr_slots.is_empty());
+    }
+
+    /// Add to the selection the PCR slots selected in `other`.
+    ///
+    /// Banks only present in `other` are appended, and the largest size of select is kept for
+    /// banks present in both.
+    pub fn union(&mut self, other: &PcrSelections) {
+        for (hash_algorithm, size_of_select, pcr_slots) in other.items.iter() {
+            self.add(*hash_algorithm, *size_of_select, *pcr_slots);
+        }
+    }
+
+    /// Keep in the selection only the PCR slots which are also selected in `other`.
+    ///
+    /// Banks left without any selected slot are removed.
+    pub fn intersect(&mut self, other: &PcrSelections) {
+        for (hash_algorithm, _, selected_pcr_slots) in self.items.iter_mut() {
+            *selected_pcr_slots = match other
+                .items
+                .iter()
+                .find(|(algorithm, _, _)| algorithm == hash_algorithm)
+            {
+                Some((_, _, pcr_slots)) => *selected_pcr_slots & *pcr_slots,
+                None => BitFlags::empty(),
+            };
+        }
+        self.items.retain(|(_, _, pcr_slots)| !pcr_slots.is_empty());
+    }
+
+    // Insert a selection into the storage, or update it if the bank
+    // already exists. The largest sizeofSelect is kept.
+    fn add(
+        &mut self,
+        hash_algorithm: HashingAlgorithm,
+        size_of_select: PcrSelectSize,
+        pcr_slots: BitFlags<PcrSlot>,
+    ) {
+        match self
+            .items
+            .iter_mut()
+            .find(|(algorithm, _, _)| *algorithm == hash_algorithm)
+        {
+            Some((_, previous_size_of_select, previous_pcr_slots)) => {
+                *previous_size_of_select = (*previous_size_of_select).max(size_of_select);
+                *previous_pcr_slots |= pcr_slots;
+            }
+            None => self.items.push((hash_algorithm, size_of_select, pcr_slots)),
+        }
+    }
 }
 
 impl From<PcrSelections> for TPML_PCR_SELECTION {
     fn from(pcr_selections: PcrSelections) -> TPML_PCR_SELECTION {
         let mut ret: TPML_PCR_SELECTION = Default::default();
-        for (hash_algorithm, pcr_slots) in pcr_selections.items {
+        for (hash_algorithm, size_of_select, pcr_slots) in pcr_selections.items {
             let tpms_pcr_selection = &mut ret.pcrSelections[ret.count as usize];
-            tpms_pcr_selection.hash = hash_algorithm.clone().into();
-            tpms_pcr_selection.sizeofSelect = pcr_selections.size_of_select.to_u8().unwrap();
+            tpms_pcr_selection.hash = hash_algorithm.into();
+            tpms_pcr_selection.sizeofSelect = size_of_select.to_u8().unwrap();
             tpms_pcr_selection.pcrSelect = pcr_slots.bits().to_le_bytes();
             ret.count += 1;
         }
@@ -1401,18 +1652,17 @@ impl TryFrom<TPML_PCR_SELECTION> for PcrSelections {
     type Error = Error;
     fn try_from(tpml_pcr_selection: TPML_PCR_SELECTION) -> Result<PcrSelections> {
         let mut ret: PcrSelections = Default::default();
-        let mut size_of_select: Option<PcrSelectSize> = None;
+        if tpml_pcr_selection.count as usize > tpml_pcr_selection.pcrSelections.len() {
+            error!(
+                "Error: invalid TPML_PCR_SELECTION count {}.",
+                tpml_pcr_selection.count
+            );
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
         // Loop over available selections
-        for selection_index in 0..(tpml_pcr_selection.count as usize) {
-            let selection = &tpml_pcr_selection.pcrSelections[selection_index];
-            // Parse the pcr slots from the pcrSelect bit mask.
-            let parsed_pcr_slots: BitFlags<PcrSlot> = BitFlags::<PcrSlot>::try_from(
-                u32::from_le_bytes(selection.pcrSelect),
-            )
-            .or_else(|e| {
-                error!("Error parsing pcrSelect to a BitFlags<PcrSlot>: {}.", e);
-                Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
-            })?;
+        for selection in
+            tpml_pcr_selection.pcrSelections[..tpml_pcr_selection.count as usize].iter()
+        {
             // Parse the sizeofSelect into a SelectSize.
             let parsed_size_of_select = match PcrSelectSize::from_u8(selection.sizeofSelect) {
                 Some(val) => val,
@@ -1424,32 +1674,34 @@ impl TryFrom<TPML_PCR_SELECTION> for PcrSelections {
                     return Err(Error::local_error(WrapperErrorKind::InvalidParam));
                 }
             };
-            // Check for variations in sizeofSelect.
-            // Something that currently is not supported.
-            if parsed_size_of_select != size_of_select.unwrap_or(parsed_size_of_select) {
-                return Err(Error::local_error(WrapperErrorKind::UnsupportedParam));
+            // Parse the pcr slots from the pcrSelect bit mask, which
+            // must not select anything outside of the sizeofSelect octets.
+            if selection.pcrSelect[usize::from(selection.sizeofSelect)..]
+                .iter()
+                .any(|octet| *octet != 0)
+            {
+                error!("Error: pcrSelect has bits set beyond sizeofSelect.");
+                return Err(Error::local_error(WrapperErrorKind::InvalidParam));
             }
-            size_of_select = Some(parsed_size_of_select);
+            let parsed_pcr_slots: BitFlags<PcrSlot> = BitFlags::<PcrSlot>::try_from(
+                u32::from_le_bytes(selection.pcrSelect),
+            )
+            .map_err(|e| {
+                error!("Error parsing pcrSelect to a BitFlags<PcrSlot>: {}.", e);
+                Error::local_error(WrapperErrorKind::UnsupportedParam)
+            })?;
             // Parse the hash
             let parsed_hash_algorithm =
-                HashingAlgorithm::try_from(selection.hash).or_else(|e| {
+                HashingAlgorithm::try_from(selection.hash).map_err(|e| {
                     error!("Error converting hash to a HashingAlgorithm: {}.", e);
-                    Err(Error::local_error(WrapperErrorKind::InvalidParam))
+                    Error::local_error(WrapperErrorKind::InvalidParam)
                 })?;
-            // Insert the select into the storage. Or update
-            // if the item already exists
-            match ret.items.get_mut(&parsed_hash_algorithm) {
-                Some(previously_parsed_pcr_slots) => {
-                    *previously_parsed_pcr_slots |= parsed_pcr_slots;
-                }
-                None => {
-                    let _ = ret.items.insert(parsed_hash_algorithm, parsed_pcr_slots);
-                }
-            }
+            ret.add(
+                parsed_hash_algorithm,
+                parsed_size_of_select,
+                parsed_pcr_slots,
+            );
         }
-        // Default case will only happen with a completely empty
-        // selection.
-        ret.size_of_select = size_of_select.unwrap_or_default();
         Ok(ret)
     }
 }
@@ -1458,7 +1710,7 @@ impl TryFrom<TPML_PCR_SELECTION> for PcrSelections {
 #[derive(Debug, Default)]
 pub struct PcrSelectionsBuilder {
     size_of_select: Option<PcrSelectSize>,
-    items: HashMap<HashingAlgorithm, BitFlags<PcrSlot>>,
+    items: Vec<(HashingAlgorithm, Option<PcrSelectSize>, BitFlags<PcrSlot>)>,
 }
 
 impl PcrSelectionsBuilder {
@@ -1472,12 +1724,27 @@ impl PcrSelectionsBuilder {
     /// Set the size of the pcr selection(sizeofSelect)
     ///
     /// # Arguments
-    /// size_of_select -- The size that will be used for all selections(sizeofSelect).
+    /// size_of_select -- The size that will be used for all selections(sizeofSelect)
+    /// for which no specific size has been set.
     pub fn with_size_of_select(mut self, size_of_select: PcrSelectSize) -> Self {
         self.size_of_select = Some(size_of_select);
         self
     }
 
+    /// Set the size of the pcr selection(sizeofSelect) of a specific bank.
+    ///
+    /// # Arguments
+    /// hash_algorithm -- The HashingAlgorithm of the bank
+    /// size_of_select -- The size that will be used for the selection of this bank.
+    pub fn with_bank_size_of_select(
+        mut self,
+        hash_algorithm: HashingAlgorithm,
+        size_of_select: PcrSelectSize,
+    ) -> Self {
+        self.bank(hash_algorithm).1 = Some(size_of_select);
+        self
+    }
+
     /// Adds a selection associated with a specific HashingAlgorithm.
     ///
     /// This function will not overwrite the values already associated
@@ -1492,14 +1759,7 @@ impl PcrSelectionsBuilder {
         pcr_slots: &[PcrSlot],
     ) -> Self {
         let selected_pcr_slots: BitFlags<PcrSlot> = pcr_slots.iter().cloned().collect();
-        match self.items.get_mut(&hash_algorithm) {
-            Some(previously_selected_pcr_slots) => {
-                *previously_selected_pcr_slots |= selected_pcr_slots;
-            }
-            None => {
-                let _ = self.items.insert(hash_algorithm, selected_pcr_slots);
-            }
-        }
+        self.bank(hash_algorithm).2 |= selected_pcr_slots;
         self
     }
 
@@ -1510,15 +1770,289 @@ impl PcrSelectionsBuilder {
     /// be defaulted to 3. This may not be the correct size for
     /// the current platform. The correct values can be obtained
     /// by quering the tpm for its capabilities.
+    ///
+    /// The size of select of a bank is increased if it is too
+    /// small to hold the selected PCR slots.
     pub fn build(self) -> PcrSelections {
-        let select_size = self.size_of_select.unwrap_or_default();
-        PcrSelections {
-            size_of_select: select_size,
-            items: self.items,
+        let default_size_of_select = self.size_of_select.unwrap_or_default();
+        let mut pcr_selections = PcrSelections::default();
+        for (hash_algorithm, size_of_select, pcr_slots) in self.items {
+            let size_of_select = size_of_select
+                .unwrap_or(default_size_of_select)
+                .max(PcrSelectSize::minimum_for(pcr_slots));
+            pcr_selections.add(hash_algorithm, size_of_select, pcr_slots);
+        }
+        pcr_selections
+    }
+
+    // Get the entry of a bank, creating it if needed.
+    fn bank(
+        &mut self,
+        hash_algorithm: HashingAlgorithm,
+    ) -> &mut (HashingAlgorithm, Option<PcrSelectSize>, BitFlags<PcrSlot>) {
+        let index = match self
+            .items
+            .iter()
+            .position(|(algorithm, _, _)| *algorithm == hash_algorithm)
+        {
+            Some(index) => index,
+            None => {
+                self.items
+                    .push((hash_algorithm, None, BitFlags::<PcrSlot>::empty()));
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[index]
+    }
+}
+
+/// Values of a set of PCRs, as read from the TPM.
+///
+/// The digests are grouped by bank, in the order in which the TPM returned the banks, and sorted
+/// by PCR slot within a bank.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PcrData {
+    banks: Vec<(HashingAlgorithm, PcrBank)>,
+}
+
+// Values of the PCRs read in a bank, sorted by PCR slot.
+type PcrBank = Vec<(PcrSlot, Vec<u8>)>;
+
+impl PcrData {
+    /// Create an empty set of PCR values.
+    pub fn new() -> Self {
+        PcrData { banks: Vec::new() }
+    }
+
+    /// Get the value of a PCR in the given bank, if it was read.
+    pub fn get(&self, hashing_algorithm: HashingAlgorithm, pcr_slot: PcrSlot) -> Option<&[u8]> {
+        self.banks
+            .iter()
+            .find(|(algorithm, _)| *algorithm == hashing_algorithm)
+            .and_then(|(_, values)| values.iter().find(|(slot, _)| *slot == pcr_slot))
+            .map(|(_, digest)| &digest[..])
+    }
+
+    /// Get the values read in a bank, sorted by PCR slot.
+    pub fn bank(&self, hashing_algorithm: HashingAlgorithm) -> Option<&[(PcrSlot, Vec<u8>)]> {
+        self.banks
+            .iter()
+            .find(|(algorithm, _)| *algorithm == hashing_algorithm)
+            .map(|(_, values)| &values[..])
+    }
+
+    /// Iterate over the values, bank by bank.
+    pub fn iter(&self) -> impl Iterator<Item = (HashingAlgorithm, PcrSlot, &[u8])> {
+        self.banks.iter().flat_map(|(algorithm, values)| {
+            values
+                .iter()
+                .map(move |(slot, digest)| (*algorithm, *slot, &digest[..]))
+        })
+    }
+
+    /// Number of PCR values held.
+    pub fn len(&self) -> usize {
+        self.banks.iter().map(|(_, values)| values.len()).sum()
+    }
+
+    /// Check if no PCR value is held.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add the values returned by a TPM2_PCR_Read call.
+    ///
+    /// The digests in `tpml_digest` are matched, in order, with the PCR slots selected in
+    /// `tpml_pcr_selection`, bank by bank and in increasing slot order.
+    pub(crate) fn add(
+        &mut self,
+        tpml_pcr_selection: &TPML_PCR_SELECTION,
+        tpml_digest: &TPML_DIGEST,
+    ) -> Result<()> {
+        let mut digests = tpml_digest.digests.iter().take(tpml_digest.count as usize);
+        for selection in tpml_pcr_selection
+            .pcrSelections
+            .iter()
+            .take(tpml_pcr_selection.count as usize)
+        {
+            let hashing_algorithm = HashingAlgorithm::try_from(selection.hash).map_err(|e| {
+                error!("Error converting hash to a HashingAlgorithm: {}.", e);
+                Error::local_error(WrapperErrorKind::InvalidParam)
+            })?;
+            let pcr_slots = BitFlags::<PcrSlot>::try_from(u32::from_le_bytes(selection.pcrSelect))
+                .map_err(|e| {
+                    error!("Error parsing pcrSelect to a BitFlags<PcrSlot>: {}.", e);
+                    Error::local_error(WrapperErrorKind::UnsupportedParam)
+                })?;
+            for pcr_slot in pcr_slots.iter() {
+                let digest = digests.next().ok_or_else(|| {
+                    error!("Error: fewer digests than selected PCRs returned by the TPM.");
+                    Error::local_error(WrapperErrorKind::InvalidParam)
+                })?;
+                self.insert(
+                    hashing_algorithm,
+                    pcr_slot,
+                    digest.buffer[..digest.size as usize].to_vec(),
+                );
+            }
+        }
+        if digests.next().is_some() {
+            error!("Error: more digests than selected PCRs returned by the TPM.");
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        Ok(())
+    }
+
+    /// Set the value of a PCR, replacing any previous value.
+    ///
+    /// Allows building the expected PCR values a quote is checked against.
+    pub fn insert(
+        &mut self,
+        hashing_algorithm: HashingAlgorithm,
+        pcr_slot: PcrSlot,
+        digest: Vec<u8>,
+    ) {
+        let index = match self
+            .banks
+            .iter()
+            .position(|(algorithm, _)| *algorithm == hashing_algorithm)
+        {
+            Some(index) => index,
+            None => {
+                self.banks.push((hashing_algorithm, Vec::new()));
+                self.banks.len() - 1
+            }
+        };
+        let values = &mut self.banks[index].1;
+        match values.iter_mut().find(|(slot, _)| *slot == pcr_slot) {
+            Some((_, previous_digest)) => *previous_digest = digest,
+            None => {
+                values.push((pcr_slot, digest));
+                values.sort_by_key(|(slot, _)| slot.index());
+            }
         }
     }
 }
 
+/// Rust native representation of `TPML_DIGEST_VALUES`.
+///
+/// Holds digests of the same data computed with different hashing algorithms, at most one per
+/// algorithm, e.g. to extend all the active banks of a PCR at once. The digests are kept in the
+/// order in which they were added.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DigestValues {
+    digests: Vec<(HashingAlgorithm, Vec<u8>)>,
+}
+
+impl DigestValues {
+    /// Create an empty list of digests.
+    pub fn new() -> Self {
+        DigestValues {
+            digests: Vec::new(),
+        }
+    }
+
+    /// Set the digest for a hashing algorithm, replacing any previous value.
+    ///
+    /// # Errors
+    /// * if the size of `digest` does not match the hashing algorithm, a `WrongParamSize` wrapper
+    /// error is returned
+    pub fn set(&mut self, hashing_algorithm: HashingAlgorithm, digest: &[u8]) -> Result<()> {
+        if digest.len() != hashing_algorithm.digest_size() {
+            error!(
+                "Error: digest of size {} provided, {:?} digests are {} bytes long.",
+                digest.len(),
+                hashing_algorithm,
+                hashing_algorithm.digest_size()
+            );
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        match self
+            .digests
+            .iter_mut()
+            .find(|(algorithm, _)| *algorithm == hashing_algorithm)
+        {
+            Some((_, previous_digest)) => *previous_digest = digest.to_vec(),
+            None => self.digests.push((hashing_algorithm, digest.to_vec())),
+        }
+        Ok(())
+    }
+
+    /// Get the digest for a hashing algorithm.
+    pub fn get(&self, hashing_algorithm: HashingAlgorithm) -> Option<&[u8]> {
+        self.digests
+            .iter()
+            .find(|(algorithm, _)| *algorithm == hashing_algorithm)
+            .map(|(_, digest)| &digest[..])
+    }
+
+    /// Get the number of digests in the list.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    /// Check whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Iterate over the hashing algorithms and digests of the list.
+    pub fn iter(&self) -> impl Iterator<Item = (HashingAlgorithm, &[u8])> {
+        self.digests
+            .iter()
+            .map(|(algorithm, digest)| (*algorithm, &digest[..]))
+    }
+}
+
+impl TryFrom<DigestValues> for TPML_DIGEST_VALUES {
+    type Error = Error;
+
+    fn try_from(digest_values: DigestValues) -> Result<Self> {
+        let mut ret: TPML_DIGEST_VALUES = Default::default();
+        if digest_values.digests.len() > ret.digests.len() {
+            error!(
+                "Error: at most {} digests can be provided.",
+                ret.digests.len()
+            );
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        for (hashing_algorithm, digest) in digest_values.digests {
+            let mut buffer = [0_u8; 64];
+            buffer[..digest.len()].clone_from_slice(&digest);
+            let tpmt_ha = &mut ret.digests[ret.count as usize];
+            tpmt_ha.hashAlg = hashing_algorithm.into();
+            // All members of TPMU_HA start at the same address, the largest one is used as buffer
+            tpmt_ha.digest.sha512 = buffer;
+            ret.count += 1;
+        }
+        Ok(ret)
+    }
+}
+
+impl TryFrom<TPML_DIGEST_VALUES> for DigestValues {
+    type Error = Error;
+
+    fn try_from(tpml_digest_values: TPML_DIGEST_VALUES) -> Result<Self> {
+        if tpml_digest_values.count as usize > tpml_digest_values.digests.len() {
+            error!(
+                "Error: Invalid TPML_DIGEST_VALUES count({}).",
+                tpml_digest_values.count
+            );
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let mut ret = DigestValues::new();
+        for tpmt_ha in tpml_digest_values.digests[..tpml_digest_values.count as usize].iter() {
+            let hashing_algorithm = HashingAlgorithm::try_from(tpmt_ha.hashAlg).map_err(|e| {
+                error!("Error converting hashAlg to a HashingAlgorithm: {}.", e);
+                Error::local_error(WrapperErrorKind::InvalidParam)
+            })?;
+            let digest = unsafe { &tpmt_ha.digest.sha512[..hashing_algorithm.digest_size()] };
+            ret.set(hashing_algorithm, digest)?;
+        }
+        Ok(ret)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PublicKey {
     Rsa(Vec<u8>),
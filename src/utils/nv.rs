@@ -0,0 +1,315 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use crate::constants::{
+    TPM2_HR_RANGE_MASK, TPM2_HR_SHIFT, TPM2_HT_NV_INDEX, TPM2_NT_BITS, TPM2_NT_COUNTER,
+    TPM2_NT_EXTEND, TPM2_NT_ORDINARY, TPM2_NT_PIN_FAIL, TPM2_NT_PIN_PASS,
+};
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::{
+    TPM2B_DIGEST, TPM2B_NV_PUBLIC, TPM2_NT, TPMA_NV, TPMI_RH_NV_INDEX, TPMS_NV_PUBLIC,
+};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use bitfield::bitfield;
+use log::error;
+use std::convert::{TryFrom, TryInto};
+
+/// Enum representing the TPM_NT (NV index types).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NvIndexType {
+    Ordinary,
+    Counter,
+    Bits,
+    Extend,
+    PinFail,
+    PinPass,
+}
+
+impl From<NvIndexType> for TPM2_NT {
+    fn from(index_type: NvIndexType) -> Self {
+        match index_type {
+            NvIndexType::Ordinary => TPM2_NT_ORDINARY,
+            NvIndexType::Counter => TPM2_NT_COUNTER,
+            NvIndexType::Bits => TPM2_NT_BITS,
+            NvIndexType::Extend => TPM2_NT_EXTEND,
+            NvIndexType::PinFail => TPM2_NT_PIN_FAIL,
+            NvIndexType::PinPass => TPM2_NT_PIN_PASS,
+        }
+    }
+}
+
+impl TryFrom<TPM2_NT> for NvIndexType {
+    type Error = Error;
+
+    fn try_from(tpm2_nt: TPM2_NT) -> Result<Self> {
+        match tpm2_nt {
+            TPM2_NT_ORDINARY => Ok(NvIndexType::Ordinary),
+            TPM2_NT_COUNTER => Ok(NvIndexType::Counter),
+            TPM2_NT_BITS => Ok(NvIndexType::Bits),
+            TPM2_NT_EXTEND => Ok(NvIndexType::Extend),
+            TPM2_NT_PIN_FAIL => Ok(NvIndexType::PinFail),
+            TPM2_NT_PIN_PASS => Ok(NvIndexType::PinPass),
+            _ => Err(Error::local_error(WrapperErrorKind::InvalidParam)),
+        }
+    }
+}
+
+bitfield! {
+    /// Rust native wrapper for `TPMA_NV` values.
+    #[derive(Copy, Clone, PartialEq)]
+    pub struct NvIndexAttributes(TPMA_NV);
+    impl Debug;
+    // NV index attribute flags
+    pub pp_write, set_pp_write: 0;
+    pub owner_write, set_owner_write: 1;
+    pub auth_write, set_auth_write: 2;
+    pub policy_write, set_policy_write: 3;
+    u8, tpm_nt, set_tpm_nt: 7, 4;
+    pub policy_delete, set_policy_delete: 10;
+    pub write_locked, set_write_locked: 11;
+    pub write_all, set_write_all: 12;
+    pub write_define, set_write_define: 13;
+    pub write_stclear, set_write_stclear: 14;
+    pub global_lock, set_global_lock: 15;
+    pub pp_read, set_pp_read: 16;
+    pub owner_read, set_owner_read: 17;
+    pub auth_read, set_auth_read: 18;
+    pub policy_read, set_policy_read: 19;
+    pub no_da, set_no_da: 25;
+    pub orderly, set_orderly: 26;
+    pub clear_stclear, set_clear_stclear: 27;
+    pub read_locked, set_read_locked: 28;
+    pub written, set_written: 29;
+    pub platform_create, set_platform_create: 30;
+    pub read_stclear, set_read_stclear: 31;
+}
+
+impl NvIndexAttributes {
+    /// Get the type of the NV index.
+    ///
+    /// # Errors
+    /// * if the type field holds a value not defined by the specification, an `InvalidParam`
+    /// wrapper error is returned
+    pub fn index_type(&self) -> Result<NvIndexType> {
+        NvIndexType::try_from(self.tpm_nt())
+    }
+
+    /// Set the type of the NV index.
+    pub fn set_index_type(&mut self, index_type: NvIndexType) {
+        self.set_tpm_nt(index_type.into());
+    }
+
+    /// Create attributes for an ordinary index that can be read and written with its
+    /// authentication value.
+    pub fn new_auth_read_write() -> Self {
+        let mut attrs = NvIndexAttributes(0);
+        attrs.set_index_type(NvIndexType::Ordinary);
+        attrs.set_auth_read(true);
+        attrs.set_auth_write(true);
+        attrs
+    }
+
+    /// Create attributes for an ordinary index that can be read and written with owner
+    /// authorization.
+    pub fn new_owner_read_write() -> Self {
+        let mut attrs = NvIndexAttributes(0);
+        attrs.set_index_type(NvIndexType::Ordinary);
+        attrs.set_owner_read(true);
+        attrs.set_owner_write(true);
+        attrs
+    }
+}
+
+/// Rust native representation of `TPMS_NV_PUBLIC`, the public area of an NV index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NvPublic {
+    nv_index: TPMI_RH_NV_INDEX,
+    name_alg: HashingAlgorithm,
+    attributes: NvIndexAttributes,
+    auth_policy: Vec<u8>,
+    data_size: u16,
+}
+
+impl NvPublic {
+    /// Get the TPM handle of the NV index.
+    pub fn nv_index(&self) -> TPMI_RH_NV_INDEX {
+        self.nv_index
+    }
+
+    /// Get the hash algorithm used to compute the name of the index.
+    pub fn name_alg(&self) -> HashingAlgorithm {
+        self.name_alg
+    }
+
+    /// Get the attributes of the index.
+    pub fn attributes(&self) -> NvIndexAttributes {
+        self.attributes
+    }
+
+    /// Get the authorization policy digest of the index.
+    pub fn auth_policy(&self) -> &[u8] {
+        &self.auth_policy
+    }
+
+    /// Get the size in bytes of the data area of the index.
+    pub fn data_size(&self) -> u16 {
+        self.data_size
+    }
+}
+
+impl TryFrom<TPM2B_NV_PUBLIC> for NvPublic {
+    type Error = Error;
+
+    fn try_from(tss_nv_public: TPM2B_NV_PUBLIC) -> Result<Self> {
+        let nv_public = tss_nv_public.nvPublic;
+        let name_alg = HashingAlgorithm::try_from(nv_public.nameAlg).map_err(|e| {
+            error!("Error converting nameAlg to a HashingAlgorithm: {}.", e);
+            Error::local_error(WrapperErrorKind::InvalidParam)
+        })?;
+
+        let len = nv_public.authPolicy.size.into();
+        if len > nv_public.authPolicy.buffer.len() {
+            error!(
+                "Error: Invalid auth policy size.(Policy size: {0} > Policy buffer size: {1})",
+                len,
+                nv_public.authPolicy.buffer.len(),
+            );
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let mut auth_policy = nv_public.authPolicy.buffer.to_vec();
+        auth_policy.truncate(len);
+
+        Ok(NvPublic {
+            nv_index: nv_public.nvIndex,
+            name_alg,
+            attributes: NvIndexAttributes(nv_public.attributes),
+            auth_policy,
+            data_size: nv_public.dataSize,
+        })
+    }
+}
+
+impl TryFrom<NvPublic> for TPM2B_NV_PUBLIC {
+    type Error = Error;
+
+    fn try_from(nv_public: NvPublic) -> Result<Self> {
+        let mut auth_policy: TPM2B_DIGEST = Default::default();
+        if nv_public.auth_policy.len() > auth_policy.buffer.len() {
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        auth_policy.buffer[..nv_public.auth_policy.len()].clone_from_slice(&nv_public.auth_policy);
+        auth_policy.size = nv_public.auth_policy.len().try_into().unwrap(); // should not fail given the check above
+
+        Ok(TPM2B_NV_PUBLIC {
+            size: std::mem::size_of::<TPMS_NV_PUBLIC>()
+                .try_into()
+                .expect("Failed to convert usize to u16"), // should not fail on valid targets
+            nvPublic: TPMS_NV_PUBLIC {
+                nvIndex: nv_public.nv_index,
+                nameAlg: nv_public.name_alg.into(),
+                attributes: nv_public.attributes.0,
+                authPolicy: auth_policy,
+                dataSize: nv_public.data_size,
+            },
+        })
+    }
+}
+
+/// A builder for `NvPublic` values.
+///
+/// # Default values
+/// * Name algorithm: SHA256
+/// * Attributes: ordinary index that can be read and written with its authentication value
+/// * Authorization policy: empty
+#[derive(Debug, Clone)]
+pub struct NvPublicBuilder {
+    nv_index: Option<TPMI_RH_NV_INDEX>,
+    name_alg: HashingAlgorithm,
+    attributes: NvIndexAttributes,
+    auth_policy: Vec<u8>,
+    data_size: Option<u16>,
+}
+
+impl NvPublicBuilder {
+    /// Create a new builder with the default values.
+    pub fn new() -> Self {
+        NvPublicBuilder {
+            nv_index: None,
+            name_alg: HashingAlgorithm::Sha256,
+            attributes: NvIndexAttributes::new_auth_read_write(),
+            auth_policy: Vec::new(),
+            data_size: None,
+        }
+    }
+
+    /// Set the TPM handle of the NV index (e.g. `0x0150_0000`).
+    pub fn with_nv_index(mut self, nv_index: TPMI_RH_NV_INDEX) -> Self {
+        self.nv_index = Some(nv_index);
+        self
+    }
+
+    /// Set the algorithm used to compute the name of the index.
+    pub fn with_name_alg(mut self, name_alg: HashingAlgorithm) -> Self {
+        self.name_alg = name_alg;
+        self
+    }
+
+    /// Set the attributes of the index.
+    pub fn with_index_attributes(mut self, attributes: NvIndexAttributes) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Set the authorization policy digest of the index.
+    pub fn with_auth_policy(mut self, auth_policy: &[u8]) -> Self {
+        self.auth_policy = auth_policy.to_vec();
+        self
+    }
+
+    /// Set the size in bytes of the data area of the index.
+    pub fn with_data_size(mut self, data_size: u16) -> Self {
+        self.data_size = Some(data_size);
+        self
+    }
+
+    /// Build an `NvPublic` with the previously provided parameters.
+    ///
+    /// # Errors
+    /// * if the NV index or the data size is not provided, a `ParamsMissing` wrapper error is
+    /// returned
+    /// * if the NV index is not in the NV index handle range, an `InvalidParam` wrapper error is
+    /// returned
+    /// * if the authorization policy is longer than 64 bytes, a `WrongParamSize` wrapper error is
+    /// returned
+    /// * if the type stored in the attributes is not a valid NV index type, an `InvalidParam`
+    /// wrapper error is returned
+    pub fn build(self) -> Result<NvPublic> {
+        let nv_index = self
+            .nv_index
+            .ok_or_else(|| Error::local_error(WrapperErrorKind::ParamsMissing))?;
+        if (nv_index & TPM2_HR_RANGE_MASK) >> TPM2_HR_SHIFT != u32::from(TPM2_HT_NV_INDEX) {
+            error!("Error: 0x{:08x} is not an NV index handle.", nv_index);
+            return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+        }
+        let data_size = self
+            .data_size
+            .ok_or_else(|| Error::local_error(WrapperErrorKind::ParamsMissing))?;
+        if self.auth_policy.len() > 64 {
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        let _ = self.attributes.index_type()?;
+
+        Ok(NvPublic {
+            nv_index,
+            name_alg: self.name_alg,
+            attributes: self.attributes,
+            auth_policy: self.auth_policy,
+            data_size,
+        })
+    }
+}
+
+impl Default for NvPublicBuilder {
+    fn default() -> Self {
+        NvPublicBuilder::new()
+    }
+}
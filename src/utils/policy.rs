@@ -0,0 +1,419 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Software computation of policy digests
+//!
+//! A `PolicyCalculator` replays policy commands the way a TPM does in a trial session, so that
+//! the `policyDigest` of a policy (e.g. the authorization policy of an object or NV index) can be
+//! computed without access to a TPM. The digests produced are identical to those returned by
+//! `Context::policy_get_digest` for a trial session started with the same hashing algorithm and
+//! on which the same commands were executed.
+use crate::constants::*;
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::{TPM2_CC, TPM2_EO, TPML_PCR_SELECTION};
+use crate::utils::algorithm_specifiers::HashingAlgorithm;
+use crate::utils::command_code::CommandCode;
+use crate::utils::hash::{self, Hasher};
+use crate::utils::{LocalityAttributes, PcrSelections};
+use log::error;
+use std::convert::TryFrom;
+
+/// Enum representing the comparison operations (`TPM2_EO`) used by TPM2_PolicyNV and
+/// TPM2_PolicyCounterTimer.
+///
+/// The operation compares operand A, read from the TPM, with operand B, provided by the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperation {
+    /// A = B
+    Equal,
+    /// A != B
+    NotEqual,
+    /// A > B, signed
+    SignedGreaterThan,
+    /// A > B, unsigned
+    UnsignedGreaterThan,
+    /// A < B, signed
+    SignedLessThan,
+    /// A < B, unsigned
+    UnsignedLessThan,
+    /// A >= B, signed
+    SignedGreaterOrEqual,
+    /// A >= B, unsigned
+    UnsignedGreaterOrEqual,
+    /// A <= B, signed
+    SignedLessOrEqual,
+    /// A <= B, unsigned
+    UnsignedLessOrEqual,
+    /// All bits set in B are set in A
+    BitSet,
+    /// All bits set in B are clear in A
+    BitClear,
+}
+
+impl From<ComparisonOperation> for TPM2_EO {
+    fn from(operation: ComparisonOperation) -> Self {
+        match operation {
+            ComparisonOperation::Equal => TPM2_EO_EQ,
+            ComparisonOperation::NotEqual => TPM2_EO_NEQ,
+            ComparisonOperation::SignedGreaterThan => TPM2_EO_SIGNED_GT,
+            ComparisonOperation::UnsignedGreaterThan => TPM2_EO_UNSIGNED_GT,
+            ComparisonOperation::SignedLessThan => TPM2_EO_SIGNED_LT,
+            ComparisonOperation::UnsignedLessThan => TPM2_EO_UNSIGNED_LT,
+            ComparisonOperation::SignedGreaterOrEqual => TPM2_EO_SIGNED_GE,
+            ComparisonOperation::UnsignedGreaterOrEqual => TPM2_EO_UNSIGNED_GE,
+            ComparisonOperation::SignedLessOrEqual => TPM2_EO_SIGNED_LE,
+            ComparisonOperation::UnsignedLessOrEqual => TPM2_EO_UNSIGNED_LE,
+            ComparisonOperation::BitSet => TPM2_EO_BITSET,
+            ComparisonOperation::BitClear => TPM2_EO_BITCLEAR,
+        }
+    }
+}
+
+impl TryFrom<TPM2_EO> for ComparisonOperation {
+    type Error = Error;
+
+    fn try_from(tpm2_eo: TPM2_EO) -> Result<Self> {
+        match tpm2_eo {
+            TPM2_EO_EQ => Ok(ComparisonOperation::Equal),
+            TPM2_EO_NEQ => Ok(ComparisonOperation::NotEqual),
+            TPM2_EO_SIGNED_GT => Ok(ComparisonOperation::SignedGreaterThan),
+            TPM2_EO_UNSIGNED_GT => Ok(ComparisonOperation::UnsignedGreaterThan),
+            TPM2_EO_SIGNED_LT => Ok(ComparisonOperation::SignedLessThan),
+            TPM2_EO_UNSIGNED_LT => Ok(ComparisonOperation::UnsignedLessThan),
+            TPM2_EO_SIGNED_GE => Ok(ComparisonOperation::SignedGreaterOrEqual),
+            TPM2_EO_UNSIGNED_GE => Ok(ComparisonOperation::UnsignedGreaterOrEqual),
+            TPM2_EO_SIGNED_LE => Ok(ComparisonOperation::SignedLessOrEqual),
+            TPM2_EO_UNSIGNED_LE => Ok(ComparisonOperation::UnsignedLessOrEqual),
+            TPM2_EO_BITSET => Ok(ComparisonOperation::BitSet),
+            TPM2_EO_BITCLEAR => Ok(ComparisonOperation::BitClear),
+            _ => Err(Error::local_error(WrapperErrorKind::InvalidParam)),
+        }
+    }
+}
+
+/// Calculator of policy digests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyCalculator {
+    hashing_algorithm: HashingAlgorithm,
+    digest: Vec<u8>,
+}
+
+impl PolicyCalculator {
+    /// Create a calculator with an empty policy (all-zero digest).
+    ///
+    /// # Errors
+    /// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper
+    /// error is returned
+    pub fn new(hashing_algorithm: HashingAlgorithm) -> Result<Self> {
+        let _ = Hasher::new(hashing_algorithm)?;
+        Ok(PolicyCalculator {
+            hashing_algorithm,
+            digest: vec![0; hashing_algorithm.digest_size()],
+        })
+    }
+
+    /// Get the hashing algorithm of the policy.
+    pub fn hashing_algorithm(&self) -> HashingAlgorithm {
+        self.hashing_algorithm
+    }
+
+    /// Get the current policy digest.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Reset the policy digest to its initial value, as done by TPM2_PolicyRestart.
+    pub fn restart(&mut self) {
+        self.digest = vec![0; self.hashing_algorithm.digest_size()];
+    }
+
+    /// Replay TPM2_PolicyPCR.
+    ///
+    /// `pcr_digest` is the digest, with the algorithm of the policy, of the concatenation of the
+    /// expected values of the selected PCRs.
+    ///
+    /// # Errors
+    /// * if the size of `pcr_digest` does not match the algorithm of the policy, a
+    /// `WrongParamSize` wrapper error is returned
+    pub fn policy_pcr(&mut self, pcr_selections: PcrSelections, pcr_digest: &[u8]) -> Result<()> {
+        self.check_digest_size(pcr_digest)?;
+        let pcrs = marshal_pcr_selection(&pcr_selections.into());
+        self.update(TPM2_CC_PolicyPCR, &[&pcrs, pcr_digest])
+    }
+
+    /// Replay TPM2_PolicyPCR from the expected values of the selected PCRs.
+    ///
+    /// The values must be given in the order in which the TPM reads them: by bank, in the order
+    /// of the selection, then by increasing PCR index.
+    pub fn policy_pcr_values(
+        &mut self,
+        pcr_selections: PcrSelections,
+        pcr_values: &[&[u8]],
+    ) -> Result<()> {
+        let pcr_digest = hash::hash(self.hashing_algorithm, pcr_values)?;
+        self.policy_pcr(pcr_selections, &pcr_digest)
+    }
+
+    /// Replay TPM2_PolicyAuthValue.
+    pub fn policy_auth_value(&mut self) -> Result<()> {
+        self.update(TPM2_CC_PolicyAuthValue, &[])
+    }
+
+    /// Replay TPM2_PolicyPassword.
+    ///
+    /// The resulting digest is the same as the one of TPM2_PolicyAuthValue.
+    pub fn policy_password(&mut self) -> Result<()> {
+        self.update(TPM2_CC_PolicyAuthValue, &[])
+    }
+
+    /// Replay TPM2_PolicyCommandCode.
+    pub fn policy_command_code(&mut self, code: CommandCode) -> Result<()> {
+        self.update(
+            TPM2_CC_PolicyCommandCode,
+            &[&TPM2_CC::from(code).to_be_bytes()],
+        )
+    }
+
+    /// Replay TPM2_PolicyPhysicalPresence.
+    pub fn policy_physical_presence(&mut self) -> Result<()> {
+        self.update(TPM2_CC_PolicyPhysicalPresence, &[])
+    }
+
+    /// Replay TPM2_PolicyLocality.
+    pub fn policy_locality(&mut self, locality: LocalityAttributes) -> Result<()> {
+        self.update(TPM2_CC_PolicyLocality, &[&[locality.0]])
+    }
+
+    /// Replay TPM2_PolicyCpHash.
+    ///
+    /// # Errors
+    /// * if the size of `cp_hash` does not match the algorithm of the policy, a `WrongParamSize`
+    /// wrapper error is returned
+    pub fn policy_cp_hash(&mut self, cp_hash: &[u8]) -> Result<()> {
+        self.check_digest_size(cp_hash)?;
+        self.update(TPM2_CC_PolicyCpHash, &[cp_hash])
+    }
+
+    /// Replay TPM2_PolicyNameHash.
+    ///
+    /// # Errors
+    /// * if the size of `name_hash` does not match the algorithm of the policy, a
+    /// `WrongParamSize` wrapper error is returned
+    pub fn policy_name_hash(&mut self, name_hash: &[u8]) -> Result<()> {
+        self.check_digest_size(name_hash)?;
+        self.update(TPM2_CC_PolicyNameHash, &[name_hash])
+    }
+
+    /// Replay TPM2_PolicyTemplate.
+    ///
+    /// # Errors
+    /// * if the size of `template_hash` does not match the algorithm of the policy, a
+    /// `WrongParamSize` wrapper error is returned
+    pub fn policy_template(&mut self, template_hash: &[u8]) -> Result<()> {
+        self.check_digest_size(template_hash)?;
+        self.update(TPM2_CC_PolicyTemplate, &[template_hash])
+    }
+
+    /// Replay TPM2_PolicyNvWritten.
+    pub fn policy_nv_written(&mut self, written_set: bool) -> Result<()> {
+        self.update(TPM2_CC_PolicyNvWritten, &[&[u8::from(written_set)]])
+    }
+
+    /// Replay TPM2_PolicyNV.
+    ///
+    /// `nv_index_name` is the name of the NV index whose content is compared with `operand_b`.
+    pub fn policy_nv(
+        &mut self,
+        nv_index_name: &[u8],
+        operand_b: &[u8],
+        offset: u16,
+        operation: ComparisonOperation,
+    ) -> Result<()> {
+        let args = self.operation_args(operand_b, offset, operation)?;
+        self.update(TPM2_CC_PolicyNV, &[&args, nv_index_name])
+    }
+
+    /// Replay TPM2_PolicyCounterTimer.
+    pub fn policy_counter_timer(
+        &mut self,
+        operand_b: &[u8],
+        offset: u16,
+        operation: ComparisonOperation,
+    ) -> Result<()> {
+        let args = self.operation_args(operand_b, offset, operation)?;
+        self.update(TPM2_CC_PolicyCounterTimer, &[&args])
+    }
+
+    /// Replay TPM2_PolicyDuplicationSelect.
+    ///
+    /// `object_name` is only included in the digest if `include_object` is set.
+    pub fn policy_duplication_select(
+        &mut self,
+        object_name: &[u8],
+        new_parent_name: &[u8],
+        include_object: bool,
+    ) -> Result<()> {
+        let include = [u8::from(include_object)];
+        if include_object {
+            self.update(
+                TPM2_CC_PolicyDuplicationSelect,
+                &[object_name, new_parent_name, &include],
+            )
+        } else {
+            self.update(
+                TPM2_CC_PolicyDuplicationSelect,
+                &[new_parent_name, &include],
+            )
+        }
+    }
+
+    /// Replay TPM2_PolicySigned.
+    ///
+    /// `auth_object_name` is the name of the key which signs the authorization.
+    pub fn policy_signed(&mut self, auth_object_name: &[u8], policy_ref: &[u8]) -> Result<()> {
+        self.update(TPM2_CC_PolicySigned, &[auth_object_name])?;
+        self.update_ref(policy_ref)
+    }
+
+    /// Replay TPM2_PolicySecret.
+    ///
+    /// `auth_object_name` is the name of the entity whose authorization is required; for a
+    /// permanent handle (e.g. a hierarchy) it is the handle in big-endian form.
+    pub fn policy_secret(&mut self, auth_object_name: &[u8], policy_ref: &[u8]) -> Result<()> {
+        self.update(TPM2_CC_PolicySecret, &[auth_object_name])?;
+        self.update_ref(policy_ref)
+    }
+
+    /// Replay TPM2_PolicyOR.
+    ///
+    /// # Errors
+    /// * if fewer than 2 or more than 8 digests are provided, a `WrongParamSize` wrapper error is
+    /// returned
+    /// * if the size of one of the digests does not match the algorithm of the policy, a
+    /// `WrongParamSize` wrapper error is returned
+    pub fn policy_or(&mut self, digests: &[&[u8]]) -> Result<()> {
+        if digests.len() < 2 || digests.len() > 8 {
+            error!(
+                "Error: PolicyOR requires between 2 and 8 digests, {} provided.",
+                digests.len()
+            );
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        for digest in digests {
+            self.check_digest_size(digest)?;
+        }
+        self.restart();
+        self.update(TPM2_CC_PolicyOR, digests)
+    }
+
+    /// Replay TPM2_PolicyAuthorize.
+    ///
+    /// `key_sign_name` is the name of the key which signs the approved policies.
+    pub fn policy_authorize(&mut self, key_sign_name: &[u8], policy_ref: &[u8]) -> Result<()> {
+        self.restart();
+        self.update(TPM2_CC_PolicyAuthorize, &[key_sign_name])?;
+        self.update_ref(policy_ref)
+    }
+
+    /// Replay TPM2_PolicyAuthorizeNV.
+    ///
+    /// `nv_index_name` is the name of the NV index holding the approved policy.
+    pub fn policy_authorize_nv(&mut self, nv_index_name: &[u8]) -> Result<()> {
+        self.restart();
+        self.update(TPM2_CC_PolicyAuthorizeNV, &[nv_index_name])
+    }
+
+    // args := H(operandB || offset || operation)
+    fn operation_args(
+        &self,
+        operand_b: &[u8],
+        offset: u16,
+        operation: ComparisonOperation,
+    ) -> Result<Vec<u8>> {
+        hash::hash(
+            self.hashing_algorithm,
+            &[
+                operand_b,
+                &offset.to_be_bytes(),
+                &TPM2_EO::from(operation).to_be_bytes(),
+            ],
+        )
+    }
+
+    // policyDigest_new := H(policyDigest_old || commandCode || data)
+    fn update(&mut self, command_code: TPM2_CC, data: &[&[u8]]) -> Result<()> {
+        let mut hasher = Hasher::new(self.hashing_algorithm)?;
+        hasher.update(&self.digest);
+        hasher.update(&command_code.to_be_bytes());
+        for part in data {
+            hasher.update(part);
+        }
+        self.digest = hasher.finalize();
+        Ok(())
+    }
+
+    // policyDigest_new := H(policyDigest_old || policyRef)
+    fn update_ref(&mut self, policy_ref: &[u8]) -> Result<()> {
+        self.digest = hash::hash(self.hashing_algorithm, &[&self.digest, policy_ref])?;
+        Ok(())
+    }
+
+    fn check_digest_size(&self, digest: &[u8]) -> Result<()> {
+        if digest.len() != self.hashing_algorithm.digest_size() {
+            error!(
+                "Error: digest of size {} provided, {:?} digests are {} bytes long.",
+                digest.len(),
+                self.hashing_algorithm,
+                self.hashing_algorithm.digest_size()
+            );
+            return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+        }
+        Ok(())
+    }
+}
+
+/// Compute the cpHash of a command, as used by TPM2_PolicyCpHash and TPM2_PolicySigned.
+///
+/// `handle_names` are the names of the entities referenced by the handles of the command, in the
+/// order in which the handles appear, and `parameters` is the marshalled parameter area of the
+/// command. The cpHash is the digest of `commandCode || names || parameters`.
+///
+/// # Errors
+/// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper error
+/// is returned
+pub fn cp_hash(
+    hashing_algorithm: HashingAlgorithm,
+    command_code: CommandCode,
+    handle_names: &[&[u8]],
+    parameters: &[u8],
+) -> Result<Vec<u8>> {
+    let mut hasher = Hasher::new(hashing_algorithm)?;
+    hasher.update(&TPM2_CC::from(command_code).to_be_bytes());
+    for name in handle_names {
+        hasher.update(name);
+    }
+    hasher.update(parameters);
+    Ok(hasher.finalize())
+}
+
+/// Compute the nameHash of a command, as used by TPM2_PolicyNameHash.
+///
+/// `handle_names` are the names of the entities referenced by the handles of the command, in the
+/// order in which the handles appear. The nameHash is the digest of their concatenation.
+///
+/// # Errors
+/// * if the algorithm has no software implementation (SM3), an `UnsupportedParam` wrapper error
+/// is returned
+pub fn name_hash(hashing_algorithm: HashingAlgorithm, handle_names: &[&[u8]]) -> Result<Vec<u8>> {
+    hash::hash(hashing_algorithm, handle_names)
+}
+
+// Marshal a TPML_PCR_SELECTION to its TPM wire format.
+fn marshal_pcr_selection(pcr_selection: &TPML_PCR_SELECTION) -> Vec<u8> {
+    let mut buffer = pcr_selection.count.to_be_bytes().to_vec();
+    for selection in pcr_selection.pcrSelections[..pcr_selection.count as usize].iter() {
+        buffer.extend_from_slice(&selection.hash.to_be_bytes());
+        buffer.push(selection.sizeofSelect);
+        buffer.extend_from_slice(&selection.pcrSelect[..usize::from(selection.sizeofSelect)]);
+    }
+    buffer
+}
@@ -1,5 +1,7 @@
 use crate::response_code::{Error, Result, WrapperErrorKind};
-use crate::tss2_esys::{TPM2B_DIGEST, TPMT_TK_HASHCHECK, TPMT_TK_VERIFIED};
+use crate::tss2_esys::{
+    TPM2B_DIGEST, TPM2_ST, TPMT_TK_AUTH, TPMT_TK_CREATION, TPMT_TK_HASHCHECK, TPMT_TK_VERIFIED,
+};
 use crate::utils::tags::StructureTag;
 use crate::utils::Hierarchy;
 use log::error;
@@ -14,19 +16,10 @@ macro_rules! impl_ticket_try_froms {
         impl TryFrom<$ticket_type> for $tss_ticket_type {
             type Error = Error;
             fn try_from(ticket: $ticket_type) -> Result<Self> {
-                let digest = ticket.digest;
-                if digest.len() > TPM2B_DIGEST_BUFFER_SIZE {
-                    return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
-                }
-                let mut buffer = [0; TPM2B_DIGEST_BUFFER_SIZE];
-                buffer[..digest.len()].clone_from_slice(&digest[..digest.len()]);
                 Ok($tss_ticket_type {
                     tag: <$ticket_type>::TAG.into(),
                     hierarchy: ticket.hierarchy.rh(),
-                    digest: TPM2B_DIGEST {
-                        size: digest.len().try_into().unwrap(), // should not fail based on the checks done above
-                        buffer,
-                    },
+                    digest: digest_to_tss(&ticket.digest)?,
                 })
             }
         }
@@ -35,35 +28,82 @@ macro_rules! impl_ticket_try_froms {
             type Error = Error;
 
             fn try_from(tss_ticket: $tss_ticket_type) -> Result<Self> {
-                match StructureTag::try_from(tss_ticket.tag) {
-                    Ok(val) => {
-                        if val != <$ticket_type>::TAG {
-                            return Err(Error::local_error(WrapperErrorKind::InconsistentParams));
-                        }
-                    }
-                    Err(why) => {
-                        error!("Failed to parsed tag: {}", why);
-                        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
-                    }
-                };
-
-                let len = tss_ticket.digest.size.into();
-                if len > TPM2B_DIGEST_BUFFER_SIZE {
-                    error!(
-                        "Error: Invalid digest size.(Digest size: {0} > Digest buffer size: {1})",
-                        len, TPM2B_DIGEST_BUFFER_SIZE,
-                    );
-                    return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+                if parse_tag(tss_ticket.tag)? != <$ticket_type>::TAG {
+                    return Err(Error::local_error(WrapperErrorKind::InconsistentParams));
                 }
-                let mut digest = tss_ticket.digest.buffer.to_vec();
-                digest.truncate(len);
-
+                let digest = digest_from_tss(&tss_ticket.digest)?;
                 let hierarchy = tss_ticket.hierarchy.try_into()?;
 
                 Ok($ticket_type { hierarchy, digest })
             }
         }
     };
+    // Ticket types which can carry one of several tags store it in a `tag` field.
+    ($ticket_type:ident, $tss_ticket_type:ident, $($tag:ident)|+) => {
+        impl TryFrom<$ticket_type> for $tss_ticket_type {
+            type Error = Error;
+            fn try_from(ticket: $ticket_type) -> Result<Self> {
+                Ok($tss_ticket_type {
+                    tag: ticket.tag.into(),
+                    hierarchy: ticket.hierarchy.rh(),
+                    digest: digest_to_tss(&ticket.digest)?,
+                })
+            }
+        }
+
+        impl TryFrom<$tss_ticket_type> for $ticket_type {
+            type Error = Error;
+
+            fn try_from(tss_ticket: $tss_ticket_type) -> Result<Self> {
+                let tag = parse_tag(tss_ticket.tag)?;
+                match tag {
+                    $(StructureTag::$tag)|+ => (),
+                    _ => return Err(Error::local_error(WrapperErrorKind::InconsistentParams)),
+                }
+                let digest = digest_from_tss(&tss_ticket.digest)?;
+                let hierarchy = tss_ticket.hierarchy.try_into()?;
+
+                Ok($ticket_type {
+                    tag,
+                    hierarchy,
+                    digest,
+                })
+            }
+        }
+    };
+}
+
+fn parse_tag(tag: TPM2_ST) -> Result<StructureTag> {
+    StructureTag::try_from(tag).map_err(|why| {
+        error!("Failed to parsed tag: {}", why);
+        Error::local_error(WrapperErrorKind::InvalidParam)
+    })
+}
+
+fn digest_to_tss(digest: &[u8]) -> Result<TPM2B_DIGEST> {
+    if digest.len() > TPM2B_DIGEST_BUFFER_SIZE {
+        return Err(Error::local_error(WrapperErrorKind::WrongParamSize));
+    }
+    let mut buffer = [0; TPM2B_DIGEST_BUFFER_SIZE];
+    buffer[..digest.len()].clone_from_slice(&digest[..digest.len()]);
+    Ok(TPM2B_DIGEST {
+        size: digest.len().try_into().unwrap(), // should not fail based on the checks done above
+        buffer,
+    })
+}
+
+fn digest_from_tss(tss_digest: &TPM2B_DIGEST) -> Result<Vec<u8>> {
+    let len = tss_digest.size.into();
+    if len > TPM2B_DIGEST_BUFFER_SIZE {
+        error!(
+            "Error: Invalid digest size.(Digest size: {0} > Digest buffer size: {1})",
+            len, TPM2B_DIGEST_BUFFER_SIZE,
+        );
+        return Err(Error::local_error(WrapperErrorKind::InvalidParam));
+    }
+    let mut digest = tss_digest.buffer.to_vec();
+    digest.truncate(len);
+    Ok(digest)
 }
 
 pub trait Ticket {
@@ -95,7 +135,7 @@ impl Ticket for HashcheckTicket {
 impl_ticket_try_froms!(HashcheckTicket, TPMT_TK_HASHCHECK);
 
 /// Rust native wrapper for `TPMT_TK_VERIFIED` objects.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VerifiedTicket {
     hierarchy: Hierarchy,
     digest: Vec<u8>,
@@ -116,3 +156,58 @@ impl Ticket for VerifiedTicket {
 }
 
 impl_ticket_try_froms!(VerifiedTicket, TPMT_TK_VERIFIED);
+
+/// Rust native wrapper for `TPMT_TK_CREATION` objects.
+///
+/// The ticket is produced when an object is created and allows TPM2_CertifyCreation to check
+/// that the object was created by the TPM.
+#[derive(Debug, Clone)]
+pub struct CreationTicket {
+    hierarchy: Hierarchy,
+    digest: Vec<u8>,
+}
+
+impl Ticket for CreationTicket {
+    /// The tag of the creation ticket.
+    const TAG: StructureTag = StructureTag::Creation;
+    /// Get the hierarchy associated with the creation ticket.
+    fn hierarchy(&self) -> Hierarchy {
+        self.hierarchy
+    }
+    /// Get the digest associated with the creation ticket.
+    fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+}
+
+impl_ticket_try_froms!(CreationTicket, TPMT_TK_CREATION);
+
+/// Rust native wrapper for `TPMT_TK_AUTH` objects.
+///
+/// The ticket is produced by either TPM2_PolicySigned or TPM2_PolicySecret, which is reflected
+/// in its tag (`StructureTag::AuthSigned` or `StructureTag::AuthSecret`).
+#[derive(Debug, Clone)]
+pub struct AuthTicket {
+    tag: StructureTag,
+    hierarchy: Hierarchy,
+    digest: Vec<u8>,
+}
+
+impl AuthTicket {
+    /// Get the tag of the ticket.
+    pub fn tag(&self) -> StructureTag {
+        self.tag
+    }
+
+    /// Get the hierarchy associated with the authorization ticket.
+    pub fn hierarchy(&self) -> Hierarchy {
+        self.hierarchy
+    }
+
+    /// Get the digest associated with the authorization ticket.
+    pub fn digest(&self) -> &[u8] {
+        &self.digest
+    }
+}
+
+impl_ticket_try_froms!(AuthTicket, TPMT_TK_AUTH, AuthSigned | AuthSecret);
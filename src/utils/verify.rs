@@ -0,0 +1,508 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+//! Software verification of signatures and quotes
+//!
+//! Allows a verifier to check the signatures produced by a TPM, and the quotes it signs, without
+//! having access to a TPM itself. Keys are given either as the `TPM2B_PUBLIC` structure returned
+//! by `Context::read_public`, or as the `PublicKey` returned by
+//! `TransientKeyContext::read_public_key`. RSASSA-PKCS1-v1_5 and RSASSA-PSS signatures are verified with the
+//! public exponent of the key, using the `rsa` crate. ECDSA signatures are verified over the NIST
+//! curve of the key, using the `p192`, `p224`, `p256`, `p384` and `p521` crates.
+use crate::constants::{TPM2_ALG_ECC, TPM2_ALG_RSA};
+use crate::response_code::{Error, Result, WrapperErrorKind};
+use crate::tss2_esys::TPM2B_PUBLIC;
+use crate::utils::algorithm_specifiers::{EllipticCurve, HashingAlgorithm};
+use crate::utils::attest::{Attest, AttestInfo};
+use crate::utils::hash;
+use crate::utils::tags::StructureTag;
+use crate::utils::{AsymSchemeUnion, PcrData, PcrSlot, PublicKey, Signature, SignatureData};
+use log::error;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Sign, Pss, RsaPublicKey};
+use std::convert::TryFrom;
+
+// Public exponent of the RSA keys whose exponent is set to 0 in their parameters.
+const RSA_DEFAULT_EXPONENT: u32 = 65537;
+
+// Public key to verify signatures with.
+enum VerifyingKey<'a> {
+    Rsa(RsaPublicKey),
+    Ecc {
+        curve: EllipticCurve,
+        x: &'a [u8],
+        y: &'a [u8],
+    },
+}
+
+/// Reason for which a quote is not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteVerificationFailure {
+    /// The signature does not match the attestation data and the public key.
+    InvalidSignature,
+    /// The attestation data is not a quote.
+    NotAQuote(StructureTag),
+    /// The qualifying data of the quote is not the expected nonce.
+    NonceMismatch {
+        /// Nonce provided by the verifier.
+        expected: Vec<u8>,
+        /// Qualifying data included in the quote.
+        quoted: Vec<u8>,
+    },
+    /// A PCR included in the quote is not present in the expected PCR values.
+    MissingPcrValue {
+        /// Bank of the PCR.
+        hashing_algorithm: HashingAlgorithm,
+        /// The PCR.
+        pcr_slot: PcrSlot,
+    },
+    /// The digest of the quoted PCRs does not match the expected PCR values.
+    PcrDigestMismatch {
+        /// Digest of the expected PCR values.
+        expected: Vec<u8>,
+        /// Digest included in the quote.
+        quoted: Vec<u8>,
+    },
+}
+
+/// Result of the verification of a quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteVerification {
+    attest: Attest,
+    failures: Vec<QuoteVerificationFailure>,
+}
+
+impl QuoteVerification {
+    /// Check if the quote passed all the checks.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Get the reasons for which the quote is not valid; empty if it is.
+    pub fn failures(&self) -> &[QuoteVerificationFailure] {
+        &self.failures
+    }
+
+    /// Get the parsed attestation data of the quote.
+    ///
+    /// Its content can only be trusted if the quote is valid.
+    pub fn attest(&self) -> &Attest {
+        &self.attest
+    }
+}
+
+/// Verify a quote produced by `Context::quote`.
+///
+/// The following checks are performed, and all those which fail are reported:
+/// * the signature is verified over `attest`, the marshalled `TPMS_ATTEST` structure
+/// * `attest` must be a quote, and its qualifying data must be `nonce`
+/// * the digest of the values in `pcr_data` of the quoted PCRs, computed with the hashing
+/// algorithm of the signature scheme, must match the digest included in the quote
+///
+/// # Errors
+/// * if `attest` cannot be parsed, an `InvalidParam` wrapper error is returned
+/// * errors returned while verifying the signature are forwarded, see `verify_signature`
+pub fn verify_quote(
+    public: &TPM2B_PUBLIC,
+    attest: &[u8],
+    signature: &Signature,
+    nonce: &[u8],
+    pcr_data: &PcrData,
+) -> Result<QuoteVerification> {
+    let signature_valid = verify_signature(public, attest, signature)?;
+    check_quote(signature_valid, attest, signature, nonce, pcr_data)
+}
+
+/// Verify a quote produced by `Context::quote` with a `PublicKey`.
+///
+/// The same checks as for `verify_quote` are performed.
+///
+/// # Errors
+/// * if `attest` cannot be parsed, an `InvalidParam` wrapper error is returned
+/// * errors returned while verifying the signature are forwarded, see
+/// `verify_signature_with_public_key`
+pub fn verify_quote_with_public_key(
+    public_key: &PublicKey,
+    attest: &[u8],
+    signature: &Signature,
+    nonce: &[u8],
+    pcr_data: &PcrData,
+) -> Result<QuoteVerification> {
+    let signature_valid = verify_signature_with_public_key(public_key, attest, signature)?;
+    check_quote(signature_valid, attest, signature, nonce, pcr_data)
+}
+
+// Check the content of a quote whose signature has already been verified.
+fn check_quote(
+    signature_valid: bool,
+    attest: &[u8],
+    signature: &Signature,
+    nonce: &[u8],
+    pcr_data: &PcrData,
+) -> Result<QuoteVerification> {
+    let mut failures = Vec::new();
+    if !signature_valid {
+        failures.push(QuoteVerificationFailure::InvalidSignature);
+    }
+    let attest = Attest::unmarshal(attest)?;
+    if attest.extra_data() != nonce {
+        failures.push(QuoteVerificationFailure::NonceMismatch {
+            expected: nonce.to_vec(),
+            quoted: attest.extra_data().to_vec(),
+        });
+    }
+    match attest.attested() {
+        AttestInfo::Quote(info) => {
+            let mut values = Vec::new();
+            let mut missing_values = false;
+            for (hashing_algorithm, _, pcr_slots) in info.pcr_selections.iter() {
+                for pcr_slot in pcr_slots {
+                    match pcr_data.get(hashing_algorithm, pcr_slot) {
+                        Some(value) => values.push(value),
+                        None => {
+                            missing_values = true;
+                            failures.push(QuoteVerificationFailure::MissingPcrValue {
+                                hashing_algorithm,
+                                pcr_slot,
+                            });
+                        }
+                    }
+                }
+            }
+            if !missing_values {
+                let expected = hash::hash(signature_hashing_algorithm(signature)?, &values)?;
+                if expected != info.pcr_digest {
+                    failures.push(QuoteVerificationFailure::PcrDigestMismatch {
+                        expected,
+                        quoted: info.pcr_digest.clone(),
+                    });
+                }
+            }
+        }
+        _ => failures.push(QuoteVerificationFailure::NotAQuote(
+            attest.attestation_type(),
+        )),
+    }
+
+    Ok(QuoteVerification { attest, failures })
+}
+
+/// Verify a signature over a message.
+///
+/// Returns `false` if the signature does not match the message and the public key.
+///
+/// # Errors
+/// * if the signature scheme is not RSASSA, RSAPSS or ECDSA, if its hashing algorithm has no
+/// software implementation (SM3), or if the curve of the key is not a NIST curve, an
+/// `UnsupportedParam` wrapper error is returned
+/// * if the signature scheme does not match the type of the public key, an `InconsistentParams`
+/// wrapper error is returned
+/// * if the public key is invalid, e.g. if its point is not on its curve, an `InvalidParam`
+/// wrapper error is returned
+pub fn verify_signature(
+    public: &TPM2B_PUBLIC,
+    message: &[u8],
+    signature: &Signature,
+) -> Result<bool> {
+    let public_area = &public.publicArea;
+    let key = match public_area.type_ {
+        TPM2_ALG_RSA => {
+            // The type of the key was checked to be RSA
+            let (modulus, parameters) =
+                unsafe { (&public_area.unique.rsa, &public_area.parameters.rsaDetail) };
+            let exponent = if parameters.exponent == 0 {
+                RSA_DEFAULT_EXPONENT
+            } else {
+                parameters.exponent
+            };
+            VerifyingKey::Rsa(rsa_public_key(
+                public_key_buffer(&modulus.buffer, modulus.size)?,
+                exponent,
+            )?)
+        }
+        TPM2_ALG_ECC => {
+            // The type of the key was checked to be ECC
+            let (point, parameters) =
+                unsafe { (&public_area.unique.ecc, &public_area.parameters.eccDetail) };
+            let curve = match EllipticCurve::try_from(parameters.curveID) {
+                Ok(curve) => curve,
+                Err(e) => {
+                    error!("Error: unknown curve {:#x}.", parameters.curveID);
+                    return Err(e);
+                }
+            };
+            VerifyingKey::Ecc {
+                curve,
+                x: public_key_buffer(&point.x.buffer, point.x.size)?,
+                y: public_key_buffer(&point.y.buffer, point.y.size)?,
+            }
+        }
+        _ => {
+            error!(
+                "Error: signature scheme {:?} does not match the public key.",
+                signature.scheme
+            );
+            return Err(Error::local_error(WrapperErrorKind::InconsistentParams));
+        }
+    };
+    verify_signature_with_key(&key, message, signature)
+}
+
+/// Verify a signature over a message with a `PublicKey`.
+///
+/// A `PublicKey` only holds the modulus of RSA keys and the public point of ECC keys, so:
+/// * RSA keys are taken to have the default public exponent, 65537, as the keys created by
+/// `TransientKeyContext` with an exponent of 0
+/// * the curve of ECC keys is deduced from the size of the coordinates of their point, which must
+/// be that of the field elements of a NIST curve, as returned by the TPM
+///
+/// Keys with other exponents or curves can be used with `verify_signature`.
+///
+/// Returns `false` if the signature does not match the message and the public key.
+///
+/// # Errors
+/// * if the coordinates of an ECC key do not have the size of the field elements of a NIST curve,
+/// an `UnsupportedParam` wrapper error is returned
+/// * the other errors are the same as for `verify_signature`
+pub fn verify_signature_with_public_key(
+    public_key: &PublicKey,
+    message: &[u8],
+    signature: &Signature,
+) -> Result<bool> {
+    let key = match public_key {
+        PublicKey::Rsa(modulus) => {
+            VerifyingKey::Rsa(rsa_public_key(modulus, RSA_DEFAULT_EXPONENT)?)
+        }
+        PublicKey::Ecc { x, y } => {
+            let curve = match x.len().max(y.len()) {
+                24 => EllipticCurve::NistP192,
+                28 => EllipticCurve::NistP224,
+                32 => EllipticCurve::NistP256,
+                48 => EllipticCurve::NistP384,
+                65 | 66 => EllipticCurve::NistP521,
+                size => {
+                    error!("Error: no NIST curve has coordinates of {} bytes.", size);
+                    return Err(Error::local_error(WrapperErrorKind::UnsupportedParam));
+                }
+            };
+            VerifyingKey::Ecc { curve, x, y }
+        }
+    };
+    verify_signature_with_key(&key, message, signature)
+}
+
+// Verify a signature with a key whose parameters have been read.
+fn verify_signature_with_key(
+    key: &VerifyingKey,
+    message: &[u8],
+    signature: &Signature,
+) -> Result<bool> {
+    let hashing_algorithm = signature_hashing_algorithm(signature)?;
+    let digest = hash::hash(hashing_algorithm, &[message])?;
+    match (key, &signature.scheme, &signature.signature) {
+        (VerifyingKey::Rsa(key), AsymSchemeUnion::RSASSA(_), SignatureData::RsaSignature(sig)) => {
+            Ok(verify_rsassa(key, hashing_algorithm, &digest, sig))
+        }
+        (VerifyingKey::Rsa(key), AsymSchemeUnion::RSAPSS(_), SignatureData::RsaSignature(sig)) => {
+            verify_rsapss(key, hashing_algorithm, &digest, sig)
+        }
+        (
+            VerifyingKey::Ecc { curve, x, y },
+            AsymSchemeUnion::ECDSA(_),
+            SignatureData::EcdsaSignature { r, s },
+        ) => match curve {
+            EllipticCurve::NistP192 => verify_ecdsa_p192(x, y, &digest, r, s),
+            EllipticCurve::NistP224 => verify_ecdsa_p224(x, y, &digest, r, s),
+            EllipticCurve::NistP256 => verify_ecdsa_p256(x, y, &digest, r, s),
+            EllipticCurve::NistP384 => verify_ecdsa_p384(x, y, &digest, r, s),
+            EllipticCurve::NistP521 => verify_ecdsa_p521(x, y, &digest, r, s),
+            _ => {
+                error!(
+                    "Error: verification of signatures over {:?} is not supported.",
+                    curve
+                );
+                Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+            }
+        },
+        _ => {
+            error!(
+                "Error: signature scheme {:?} does not match the public key.",
+                signature.scheme
+            );
+            Err(Error::local_error(WrapperErrorKind::InconsistentParams))
+        }
+    }
+}
+
+// Build an RSA key from its modulus and public exponent.
+fn rsa_public_key(modulus: &[u8], exponent: u32) -> Result<RsaPublicKey> {
+    RsaPublicKey::new(BigUint::from_bytes_be(modulus), BigUint::from(exponent)).map_err(|e| {
+        error!("Error: invalid RSA public key ({}).", e);
+        Error::local_error(WrapperErrorKind::InvalidParam)
+    })
+}
+
+fn public_key_buffer(buffer: &[u8], size: u16) -> Result<&[u8]> {
+    buffer.get(..size.into()).ok_or_else(|| {
+        error!("Error: invalid public key size ({}).", size);
+        Error::local_error(WrapperErrorKind::InvalidParam)
+    })
+}
+
+fn signature_hashing_algorithm(signature: &Signature) -> Result<HashingAlgorithm> {
+    match signature.scheme {
+        AsymSchemeUnion::RSASSA(hashing_algorithm)
+        | AsymSchemeUnion::RSAPSS(hashing_algorithm)
+        | AsymSchemeUnion::ECDSA(hashing_algorithm) => Ok(hashing_algorithm),
+        _ => {
+            error!(
+                "Error: verification of {:?} signatures is not supported.",
+                signature.scheme
+            );
+            Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+        }
+    }
+}
+
+// DER encoding of the `DigestInfo` structure preceding the digest, as defined in RFC 8017.
+fn digest_info_prefix(hashing_algorithm: HashingAlgorithm) -> Option<&'static [u8]> {
+    match hashing_algorithm {
+        HashingAlgorithm::Sha1 => Some(&[
+            0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+            0x14,
+        ]),
+        HashingAlgorithm::Sha256 => Some(&[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x01, 0x05, 0x00, 0x04, 0x20,
+        ]),
+        HashingAlgorithm::Sha384 => Some(&[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x02, 0x05, 0x00, 0x04, 0x30,
+        ]),
+        HashingAlgorithm::Sha512 => Some(&[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x03, 0x05, 0x00, 0x04, 0x40,
+        ]),
+        HashingAlgorithm::Sha3_256 => Some(&[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x08, 0x05, 0x00, 0x04, 0x20,
+        ]),
+        HashingAlgorithm::Sha3_384 => Some(&[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x09, 0x05, 0x00, 0x04, 0x30,
+        ]),
+        HashingAlgorithm::Sha3_512 => Some(&[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x0a, 0x05, 0x00, 0x04, 0x40,
+        ]),
+        HashingAlgorithm::Sm3_256 => None,
+    }
+}
+
+// RSASSA-PKCS1-v1_5 verification (RFC 8017, section 8.2.2).
+fn verify_rsassa(
+    key: &RsaPublicKey,
+    hashing_algorithm: HashingAlgorithm,
+    digest: &[u8],
+    signature: &[u8],
+) -> bool {
+    let prefix = match digest_info_prefix(hashing_algorithm) {
+        Some(prefix) => prefix,
+        None => return false,
+    };
+    let scheme = Pkcs1v15Sign {
+        hash_len: Some(digest.len()),
+        prefix: prefix.into(),
+    };
+    key.verify(scheme, digest, signature).is_ok()
+}
+
+// RSASSA-PSS scheme with MGF1, using the same hashing algorithm as the signature.
+fn pss_scheme(hashing_algorithm: HashingAlgorithm, salt_len: usize) -> Result<Pss> {
+    match hashing_algorithm {
+        HashingAlgorithm::Sha1 => Ok(Pss::new_with_salt::<sha1::Sha1>(salt_len)),
+        HashingAlgorithm::Sha256 => Ok(Pss::new_with_salt::<sha2::Sha256>(salt_len)),
+        HashingAlgorithm::Sha384 => Ok(Pss::new_with_salt::<sha2::Sha384>(salt_len)),
+        HashingAlgorithm::Sha512 => Ok(Pss::new_with_salt::<sha2::Sha512>(salt_len)),
+        HashingAlgorithm::Sha3_256 => Ok(Pss::new_with_salt::<sha3::Sha3_256>(salt_len)),
+        HashingAlgorithm::Sha3_384 => Ok(Pss::new_with_salt::<sha3::Sha3_384>(salt_len)),
+        HashingAlgorithm::Sha3_512 => Ok(Pss::new_with_salt::<sha3::Sha3_512>(salt_len)),
+        HashingAlgorithm::Sm3_256 => {
+            error!(
+                "Error: no software implementation of {:?}.",
+                hashing_algorithm
+            );
+            Err(Error::local_error(WrapperErrorKind::UnsupportedParam))
+        }
+    }
+}
+
+// RSASSA-PSS verification (RFC 8017, section 8.1.2). TPMs use a salt as long as the digest, or,
+// for older ones, the longest salt allowed by the size of the key; both are accepted.
+fn verify_rsapss(
+    key: &RsaPublicKey,
+    hashing_algorithm: HashingAlgorithm,
+    digest: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let encoded_len = (key.n().bits() + 6) / 8;
+    let mut salt_lens = vec![digest.len()];
+    if let Some(max_salt_len) = encoded_len.checked_sub(digest.len() + 2) {
+        if max_salt_len != digest.len() {
+            salt_lens.push(max_salt_len);
+        }
+    }
+    for salt_len in salt_lens {
+        let scheme = pss_scheme(hashing_algorithm, salt_len)?;
+        if key.verify(scheme, digest, signature).is_ok() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Left-pad a big-endian integer with zeros to `size` bytes, or return `None` if it is longer.
+fn field_bytes(value: &[u8], size: usize) -> Option<Vec<u8>> {
+    let padding = size.checked_sub(value.len())?;
+    let mut bytes = vec![0; padding];
+    bytes.extend_from_slice(value);
+    Some(bytes)
+}
+
+// Define the ECDSA verification (SEC 1, section 4.1.4) over a curve, given the crate which
+// implements it and the size of its field elements.
+macro_rules! verify_ecdsa {
+    ($name:ident, $curve:ident, $size:expr) => {
+        fn $name(x: &[u8], y: &[u8], digest: &[u8], r: &[u8], s: &[u8]) -> Result<bool> {
+            use $curve::ecdsa::signature::hazmat::PrehashVerifier;
+            use $curve::ecdsa::{Signature, VerifyingKey};
+
+            // Uncompressed SEC 1 encoding of the public point
+            let key = match (field_bytes(x, $size), field_bytes(y, $size)) {
+                (Some(x), Some(y)) => {
+                    VerifyingKey::from_sec1_bytes(&[&[0x04], &x[..], &y[..]].concat()).ok()
+                }
+                _ => None,
+            }
+            .ok_or_else(|| {
+                error!("Error: the public key is not a point of its curve.");
+                Error::local_error(WrapperErrorKind::InvalidParam)
+            })?;
+            let signature = match (field_bytes(r, $size), field_bytes(s, $size)) {
+                (Some(r), Some(s)) => match Signature::from_slice(&[r, s].concat()) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                },
+                _ => return Ok(false),
+            };
+            // Digests shorter than the field elements are used as integers of the same value
+            let digest = field_bytes(digest, $size).unwrap_or_else(|| digest.to_vec());
+            Ok(key.verify_prehash(&digest, &signature).is_ok())
+        }
+    };
+}
+
+verify_ecdsa!(verify_ecdsa_p192, p192, 24);
+verify_ecdsa!(verify_ecdsa_p224, p224, 28);
+verify_ecdsa!(verify_ecdsa_p256, p256, 32);
+verify_ecdsa!(verify_ecdsa_p384, p384, 48);
+verify_ecdsa!(verify_ecdsa_p521, p521, 66);
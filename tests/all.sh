@@ -45,7 +45,7 @@ rustup toolchain install nightly
 ############################
 # Install legacy toolchain #
 ############################
-rustup toolchain install 1.38.0
+rustup toolchain install 1.65.0
 
 ####################
 # Verify doc build #
@@ -60,4 +60,4 @@ cargo +nightly build
 #####################################
 # Verify build with legacy compiler #
 #####################################
-RUST_BACKTRACE=1 cargo +1.38.0 build
+RUST_BACKTRACE=1 cargo +1.65.0 build
@@ -0,0 +1,210 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use std::convert::TryFrom;
+use tss_esapi::constants::TPM2_GENERATED_VALUE;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::{TPM2B_ATTEST, TPM2_ST};
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::attest::{
+    Attest, AttestInfo, ClockInfo, CommandAuditInfo, CreationInfo, NvDigestCertifyInfo,
+    SessionAuditInfo, TimeAttestInfo, TimeInfo,
+};
+use tss_esapi::utils::tags::StructureTag;
+use tss_esapi::utils::{PcrSelectionsBuilder, PcrSlot};
+
+const CLOCK_INFO: ClockInfo = ClockInfo {
+    clock: 0x0102_0304_0506_0708,
+    reset_count: 3,
+    restart_count: 2,
+    safe: true,
+};
+
+fn tpm2b(data: &[u8]) -> Vec<u8> {
+    let mut buffer = (data.len() as u16).to_be_bytes().to_vec();
+    buffer.extend_from_slice(data);
+    buffer
+}
+
+fn clock_info() -> Vec<u8> {
+    let mut buffer = CLOCK_INFO.clock.to_be_bytes().to_vec();
+    buffer.extend_from_slice(&CLOCK_INFO.reset_count.to_be_bytes());
+    buffer.extend_from_slice(&CLOCK_INFO.restart_count.to_be_bytes());
+    buffer.push(1);
+    buffer
+}
+
+// Marshal a TPMS_ATTEST with the given type and body
+fn attest(attestation_type: StructureTag, body: &[u8]) -> Vec<u8> {
+    let mut buffer = TPM2_GENERATED_VALUE.to_be_bytes().to_vec();
+    buffer.extend_from_slice(&TPM2_ST::from(attestation_type).to_be_bytes());
+    buffer.extend_from_slice(&tpm2b(&[0x00, 0x0b, 0xaa, 0xbb]));
+    buffer.extend_from_slice(&tpm2b(&[0xff; 16]));
+    buffer.extend_from_slice(&clock_info());
+    buffer.extend_from_slice(&0x2020_u64.to_be_bytes());
+    buffer.extend_from_slice(body);
+    buffer
+}
+
+#[test]
+fn test_quote() {
+    let mut body = 1_u32.to_be_bytes().to_vec();
+    // SHA256 bank, PCRs 0, 1 and 16
+    body.extend_from_slice(&[0x00, 0x0b, 0x03, 0x03, 0x00, 0x01]);
+    body.extend_from_slice(&tpm2b(&[0x55; 32]));
+    let buffer = attest(StructureTag::AttestQuote, &body);
+
+    let attest = Attest::unmarshal(&buffer).unwrap();
+    assert_eq!(attest.magic(), TPM2_GENERATED_VALUE);
+    assert_eq!(attest.attestation_type(), StructureTag::AttestQuote);
+    assert_eq!(attest.qualified_signer(), &[0x00, 0x0b, 0xaa, 0xbb]);
+    assert_eq!(attest.extra_data(), &[0xff; 16]);
+    assert_eq!(attest.clock_info(), CLOCK_INFO);
+    assert_eq!(attest.firmware_version(), 0x2020);
+    match attest.attested() {
+        AttestInfo::Quote(info) => {
+            assert_eq!(
+                info.pcr_selections,
+                PcrSelectionsBuilder::new()
+                    .with_selection(
+                        HashingAlgorithm::Sha256,
+                        &[PcrSlot::Slot0, PcrSlot::Slot1, PcrSlot::Slot16]
+                    )
+                    .build()
+            );
+            assert_eq!(info.pcr_digest, vec![0x55; 32]);
+        }
+        _ => panic!("Wrong attestation type parsed."),
+    }
+
+    // The same structure wrapped in a TPM2B_ATTEST
+    let mut tss_attest = TPM2B_ATTEST {
+        size: buffer.len() as u16,
+        ..Default::default()
+    };
+    tss_attest.attestationData[..buffer.len()].copy_from_slice(&buffer);
+    assert_eq!(Attest::try_from(tss_attest).unwrap(), attest);
+}
+
+#[test]
+fn test_certify() {
+    let mut body = tpm2b(&[0x01; 34]);
+    body.extend_from_slice(&tpm2b(&[0x02; 34]));
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestCertify, &body)).unwrap();
+    match attest.attested() {
+        AttestInfo::Certify(info) => {
+            assert_eq!(info.name, vec![0x01; 34]);
+            assert_eq!(info.qualified_name, vec![0x02; 34]);
+        }
+        _ => panic!("Wrong attestation type parsed."),
+    }
+}
+
+#[test]
+fn test_creation() {
+    let mut body = tpm2b(&[0x01; 34]);
+    body.extend_from_slice(&tpm2b(&[0x02; 32]));
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestCreation, &body)).unwrap();
+    assert_eq!(
+        attest.attested(),
+        &AttestInfo::Creation(CreationInfo {
+            object_name: vec![0x01; 34],
+            creation_hash: vec![0x02; 32],
+        })
+    );
+}
+
+#[test]
+fn test_time() {
+    let mut body = 1000_u64.to_be_bytes().to_vec();
+    body.extend_from_slice(&clock_info());
+    body.extend_from_slice(&0x2020_u64.to_be_bytes());
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestTime, &body)).unwrap();
+    assert_eq!(
+        attest.attested(),
+        &AttestInfo::Time(TimeAttestInfo {
+            time: TimeInfo {
+                time: 1000,
+                clock_info: CLOCK_INFO,
+            },
+            firmware_version: 0x2020,
+        })
+    );
+}
+
+#[test]
+fn test_command_audit() {
+    let mut body = 7_u64.to_be_bytes().to_vec();
+    body.extend_from_slice(&[0x00, 0x0b]);
+    body.extend_from_slice(&tpm2b(&[0x01; 32]));
+    body.extend_from_slice(&tpm2b(&[0x02; 32]));
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestCommandAudit, &body)).unwrap();
+    assert_eq!(
+        attest.attested(),
+        &AttestInfo::CommandAudit(CommandAuditInfo {
+            audit_counter: 7,
+            digest_algorithm: HashingAlgorithm::Sha256,
+            audit_digest: vec![0x01; 32],
+            command_digest: vec![0x02; 32],
+        })
+    );
+}
+
+#[test]
+fn test_session_audit() {
+    let mut body = vec![0x01];
+    body.extend_from_slice(&tpm2b(&[0x01; 32]));
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestSessionAudit, &body)).unwrap();
+    assert_eq!(
+        attest.attested(),
+        &AttestInfo::SessionAudit(SessionAuditInfo {
+            exclusive_session: true,
+            session_digest: vec![0x01; 32],
+        })
+    );
+}
+
+#[test]
+fn test_nv_digest() {
+    let mut body = tpm2b(&[0x01; 34]);
+    body.extend_from_slice(&tpm2b(&[0x02; 32]));
+    let attest = Attest::unmarshal(&attest(StructureTag::AttestNvDigest, &body)).unwrap();
+    assert_eq!(
+        attest.attested(),
+        &AttestInfo::NvDigest(NvDigestCertifyInfo {
+            index_name: vec![0x01; 34],
+            nv_digest: vec![0x02; 32],
+        })
+    );
+}
+
+#[test]
+fn test_invalid_attest() {
+    let mut body = tpm2b(&[0x01; 34]);
+    body.extend_from_slice(&tpm2b(&[0x02; 32]));
+    let buffer = attest(StructureTag::AttestCreation, &body);
+
+    // Truncated
+    assert_eq!(
+        Attest::unmarshal(&buffer[..buffer.len() - 1]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    // Trailing bytes
+    let mut trailing = buffer.clone();
+    trailing.push(0);
+    assert_eq!(
+        Attest::unmarshal(&trailing).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    // Not an attestation tag
+    assert_eq!(
+        Attest::unmarshal(&attest(StructureTag::Hashcheck, &body)).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    // Wrong magic
+    let mut magic = buffer;
+    magic[0] = 0;
+    assert_eq!(
+        Attest::unmarshal(&magic).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
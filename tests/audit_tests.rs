@@ -0,0 +1,117 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::audit::{self, AuditDigestCalculator, AuditedCommand};
+use tss_esapi::utils::command_code::CommandCode;
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// TPM2_GetRandom of 16 bytes, returning 0x00..0x0f
+fn get_random() -> AuditedCommand {
+    let mut response_parameters = vec![0x00, 0x10];
+    response_parameters.extend(0..16);
+    AuditedCommand {
+        command_code: CommandCode::GetRandom,
+        handle_names: vec![],
+        command_parameters: vec![0x00, 0x10],
+        response_parameters,
+    }
+}
+
+// TPM2_ReadPublic of an object, with a truncated response
+fn read_public() -> AuditedCommand {
+    let mut name = vec![0x00, 0x0b];
+    name.extend(0..32);
+    AuditedCommand {
+        command_code: CommandCode::ReadPublic,
+        handle_names: vec![name],
+        command_parameters: vec![],
+        response_parameters: vec![0xaa; 10],
+    }
+}
+
+const GET_RANDOM_CP_HASH: &str = "3a936d6ea415e99156592175f59f848645d0b0c31d4878750e234d23dd5723fb";
+const GET_RANDOM_RP_HASH: &str = "eb4f5e3abdf445a0fd0ea12407f7372a3975e6241162608cc456339466e87ad0";
+// Audit digests after each of the commands
+const FIRST_AUDIT_DIGEST: &str = "b2fe243e13b3d34e1b4199f53abc090e39b54260c5c16dda7dd5432cfee77ead";
+const SECOND_AUDIT_DIGEST: &str =
+    "2f3f9f587a4ebd33f993e7f9a6b45efed729730d2de4763cd702c4ad5db63740";
+
+#[test]
+fn test_initial_digest() {
+    let calculator = AuditDigestCalculator::new(HashingAlgorithm::Sha384).unwrap();
+    assert_eq!(calculator.hashing_algorithm(), HashingAlgorithm::Sha384);
+    assert_eq!(calculator.digest(), &[0; 48][..]);
+    assert_eq!(
+        audit::audit_digest(HashingAlgorithm::Sha384, &[]).unwrap(),
+        vec![0; 48]
+    );
+}
+
+#[test]
+fn test_command_hashes() {
+    let command = get_random();
+    assert_eq!(
+        command.cp_hash(HashingAlgorithm::Sha256).unwrap(),
+        from_hex(GET_RANDOM_CP_HASH)
+    );
+    assert_eq!(
+        command.rp_hash(HashingAlgorithm::Sha256).unwrap(),
+        from_hex(GET_RANDOM_RP_HASH)
+    );
+    assert_eq!(
+        audit::rp_hash(
+            HashingAlgorithm::Sha256,
+            CommandCode::GetRandom,
+            &command.response_parameters
+        )
+        .unwrap(),
+        from_hex(GET_RANDOM_RP_HASH)
+    );
+}
+
+#[test]
+fn test_audit_digest() {
+    let mut calculator = AuditDigestCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .extend(&from_hex(GET_RANDOM_CP_HASH), &from_hex(GET_RANDOM_RP_HASH))
+        .unwrap();
+    assert_eq!(calculator.digest(), &from_hex(FIRST_AUDIT_DIGEST)[..]);
+    calculator.audit_command(&read_public()).unwrap();
+    assert_eq!(calculator.digest(), &from_hex(SECOND_AUDIT_DIGEST)[..]);
+
+    assert_eq!(
+        audit::audit_digest(HashingAlgorithm::Sha256, &[get_random(), read_public()]).unwrap(),
+        from_hex(SECOND_AUDIT_DIGEST)
+    );
+}
+
+#[test]
+fn test_continue_from_digest() {
+    let mut calculator =
+        AuditDigestCalculator::with_digest(HashingAlgorithm::Sha256, &from_hex(FIRST_AUDIT_DIGEST))
+            .unwrap();
+    calculator.audit_command(&read_public()).unwrap();
+    assert_eq!(calculator.digest(), &from_hex(SECOND_AUDIT_DIGEST)[..]);
+}
+
+#[test]
+fn test_wrong_digest_size() {
+    assert_eq!(
+        AuditDigestCalculator::with_digest(HashingAlgorithm::Sha256, &[0; 20]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+    let mut calculator = AuditDigestCalculator::new(HashingAlgorithm::Sha1).unwrap();
+    assert_eq!(
+        calculator
+            .extend(&from_hex(GET_RANDOM_CP_HASH), &from_hex(GET_RANDOM_RP_HASH))
+            .unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+}
@@ -0,0 +1,153 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use std::convert::TryFrom;
+use tss_esapi::constants::*;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::{
+    TPML_ALG_PROPERTY, TPML_CCA, TPML_ECC_CURVE, TPML_HANDLE, TPML_TAGGED_TPM_PROPERTY,
+    TPMS_CAPABILITY_DATA, TPMU_CAPABILITIES,
+};
+use tss_esapi::utils::algorithm_specifiers::{
+    AsymmetricAlgorithm, EllipticCurve, HashingAlgorithm, KeyedHash,
+};
+use tss_esapi::utils::capabilities::{Algorithm, CapabilityData, HandleType};
+use tss_esapi::utils::command_code::CommandCode;
+
+#[test]
+fn algorithm_from_id() {
+    assert_eq!(
+        Algorithm::from(TPM2_ALG_SHA256),
+        Algorithm::Hashing(HashingAlgorithm::Sha256)
+    );
+    assert_eq!(
+        Algorithm::from(TPM2_ALG_ECC),
+        Algorithm::Asymmetric(AsymmetricAlgorithm::Ecc)
+    );
+    assert_eq!(
+        Algorithm::from(TPM2_ALG_HMAC),
+        Algorithm::KeyedHash(KeyedHash::Hmac)
+    );
+    assert_eq!(Algorithm::from(0x7fff), Algorithm::Other(0x7fff));
+}
+
+#[test]
+fn handle_type_range() {
+    assert_eq!(HandleType::Persistent.first_handle(), 0x8100_0000);
+    assert_eq!(HandleType::Persistent.last_handle(), 0x81ff_ffff);
+    assert_eq!(HandleType::NvIndex.first_handle(), 0x0100_0000);
+    assert_eq!(HandleType::Pcr.first_handle(), 0);
+}
+
+#[test]
+fn decode_algorithms() {
+    let mut algorithms = TPML_ALG_PROPERTY {
+        count: 2,
+        ..Default::default()
+    };
+    algorithms.algProperties[0].alg = TPM2_ALG_RSA;
+    algorithms.algProperties[1].alg = TPM2_ALG_SHA1;
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_ALGS,
+        data: TPMU_CAPABILITIES { algorithms },
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap(),
+        CapabilityData::Algorithms(vec![
+            Algorithm::Asymmetric(AsymmetricAlgorithm::Rsa),
+            Algorithm::Hashing(HashingAlgorithm::Sha1),
+        ])
+    );
+}
+
+#[test]
+fn decode_commands() {
+    let mut command = TPML_CCA {
+        count: 3,
+        ..Default::default()
+    };
+    command.commandAttributes[0] = TPM2_CC_GetRandom | 0x0040_0000;
+    command.commandAttributes[1] = TPMA_CC_V | 0x0001;
+    command.commandAttributes[2] = TPM2_CC_GetCapability;
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_COMMANDS,
+        data: TPMU_CAPABILITIES { command },
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap(),
+        CapabilityData::Commands(vec![CommandCode::GetRandom, CommandCode::GetCapability])
+    );
+}
+
+#[test]
+fn decode_properties() {
+    let mut tpm_properties = TPML_TAGGED_TPM_PROPERTY {
+        count: 2,
+        ..Default::default()
+    };
+    tpm_properties.tpmProperty[0].property = TPM2_PT_MANUFACTURER;
+    tpm_properties.tpmProperty[0].value = 0x4942_4d00;
+    tpm_properties.tpmProperty[1].property = TPM2_PT_PCR_COUNT;
+    tpm_properties.tpmProperty[1].value = 24;
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_TPM_PROPERTIES,
+        data: TPMU_CAPABILITIES {
+            tpmProperties: tpm_properties,
+        },
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap(),
+        CapabilityData::TpmProperties(vec![
+            (TPM2_PT_MANUFACTURER, 0x4942_4d00),
+            (TPM2_PT_PCR_COUNT, 24)
+        ])
+    );
+}
+
+#[test]
+fn decode_ecc_curves() {
+    let mut ecc_curves = TPML_ECC_CURVE {
+        count: 2,
+        ..Default::default()
+    };
+    ecc_curves.eccCurves[0] = TPM2_ECC_NIST_P256;
+    ecc_curves.eccCurves[1] = 0x7fff;
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_ECC_CURVES,
+        data: TPMU_CAPABILITIES {
+            eccCurves: ecc_curves,
+        },
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap(),
+        CapabilityData::EccCurves(vec![EllipticCurve::NistP256])
+    );
+}
+
+#[test]
+fn invalid_count() {
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_HANDLES,
+        data: TPMU_CAPABILITIES {
+            handles: TPML_HANDLE {
+                count: 1000,
+                ..Default::default()
+            },
+        },
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
+
+#[test]
+fn unsupported_capability() {
+    let capability_data = TPMS_CAPABILITY_DATA {
+        capability: TPM2_CAP_VENDOR_PROPERTY,
+        ..Default::default()
+    };
+    assert_eq!(
+        CapabilityData::try_from(capability_data).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+    );
+}
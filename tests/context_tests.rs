@@ -91,7 +91,7 @@ fn comprehensive_test() {
     let mut context = create_ctx_with_session();
     let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-    let prim_key_handle = context
+    let (prim_key_handle, _) = context
         .create_primary_key(
             ESYS_TR_RH_OWNER,
             &decryption_key_pub(),
@@ -121,7 +121,7 @@ fn comprehensive_test() {
         .unwrap();
     context.set_sessions((new_session, ESYS_TR_NONE, ESYS_TR_NONE));
 
-    let (key_priv, key_pub) = context
+    let (key_priv, key_pub, _) = context
         .create_key(
             prim_key_handle,
             &signing_key_pub(),
@@ -216,7 +216,7 @@ mod test_start_sess {
     #[test]
     fn test_bound_sess() {
         let mut context = create_ctx_with_session();
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(ESYS_TR_RH_OWNER, &decryption_key_pub(), &[], &[], &[], &[])
             .unwrap();
 
@@ -316,38 +316,169 @@ mod test_pcr_read {
         assert_eq!(input.pcrSelections[0].pcrSelect[1], 0b0000_0000);
         assert_eq!(input.pcrSelections[0].pcrSelect[2], 0b0000_0000);
         // Read the pcr slots.
-        let (update_counter, output, tpml_digest) = context.pcr_read(pcr_selections).unwrap();
+        let (update_counter, pcr_data) = context.pcr_read(pcr_selections).unwrap();
 
         // Verify that the selected slots have been read.
         assert_ne!(update_counter, 0);
-        assert_eq!(output.count, input.count);
-        assert_eq!(
-            output.pcrSelections[0].sizeofSelect,
-            input.pcrSelections[0].sizeofSelect
-        );
-        assert_eq!(input.pcrSelections[0].hash, output.pcrSelections[0].hash);
-        assert_eq!(
-            input.pcrSelections[0].pcrSelect[0],
-            output.pcrSelections[0].pcrSelect[0]
-        );
-        assert_eq!(
-            input.pcrSelections[0].pcrSelect[1],
-            output.pcrSelections[0].pcrSelect[1]
-        );
+        assert_eq!(pcr_data.len(), 1);
+        // Check that there exist a digest that is not empty
+        let digest = pcr_data
+            .get(HashingAlgorithm::Sha256, PcrSlot::Slot0)
+            .unwrap();
+        assert_eq!(digest.len(), 32);
+    }
+
+    #[test]
+    fn test_pcr_read_more_than_eight() {
+        let mut context = create_ctx_without_session();
+        let pcr_slots = [
+            PcrSlot::Slot0,
+            PcrSlot::Slot1,
+            PcrSlot::Slot2,
+            PcrSlot::Slot3,
+            PcrSlot::Slot4,
+            PcrSlot::Slot5,
+            PcrSlot::Slot6,
+            PcrSlot::Slot7,
+            PcrSlot::Slot8,
+            PcrSlot::Slot9,
+            PcrSlot::Slot10,
+            PcrSlot::Slot11,
+            PcrSlot::Slot12,
+            PcrSlot::Slot13,
+            PcrSlot::Slot14,
+            PcrSlot::Slot15,
+            PcrSlot::Slot16,
+            PcrSlot::Slot17,
+            PcrSlot::Slot18,
+            PcrSlot::Slot19,
+            PcrSlot::Slot20,
+            PcrSlot::Slot21,
+            PcrSlot::Slot22,
+            PcrSlot::Slot23,
+        ];
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &pcr_slots)
+            .build();
+        let (_, pcr_data) = context.pcr_read(pcr_selections).unwrap();
+
+        assert_eq!(pcr_data.len(), 24);
+        let read_slots: Vec<PcrSlot> = pcr_data
+            .iter()
+            .map(|(hashing_algorithm, pcr_slot, digest)| {
+                assert_eq!(hashing_algorithm, HashingAlgorithm::Sha256);
+                assert_eq!(digest.len(), 32);
+                pcr_slot
+            })
+            .collect();
+        assert_eq!(read_slots, pcr_slots.to_vec());
+    }
+}
+
+mod test_pcr_extend_event_reset {
+    use super::*;
+    use tss_esapi::utils::hash;
+    use tss_esapi::utils::DigestValues;
+
+    fn read_pcr(context: &mut Context, pcr_slot: PcrSlot) -> Vec<u8> {
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[pcr_slot])
+            .build();
+        let (_, pcr_data) = context.pcr_read(pcr_selections).unwrap();
+        pcr_data
+            .get(HashingAlgorithm::Sha256, pcr_slot)
+            .unwrap()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_pcr_extend_reset() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        // PCR 16 is the debug PCR, resettable from locality 0
+        context.pcr_reset(ESYS_TR_PCR16).unwrap();
+        assert_eq!(read_pcr(&mut context, PcrSlot::Slot16), vec![0; 32]);
+
+        let mut digests = DigestValues::new();
+        digests.set(HashingAlgorithm::Sha256, &HASH[..32]).unwrap();
+        context.pcr_extend(ESYS_TR_PCR16, digests).unwrap();
+        let expected = hash::hash(HashingAlgorithm::Sha256, &[&[0; 32], &HASH[..32]]).unwrap();
+        assert_eq!(read_pcr(&mut context, PcrSlot::Slot16), expected);
+
+        context.pcr_reset(ESYS_TR_PCR16).unwrap();
+        assert_eq!(read_pcr(&mut context, PcrSlot::Slot16), vec![0; 32]);
+    }
+
+    #[test]
+    fn test_pcr_event() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context.pcr_reset(ESYS_TR_PCR23).unwrap();
+
+        let event_data = b"measured event";
+        let digests = context.pcr_event(ESYS_TR_PCR23, event_data).unwrap();
+        let event_digest = hash::hash(HashingAlgorithm::Sha256, &[event_data]).unwrap();
         assert_eq!(
-            input.pcrSelections[0].pcrSelect[2],
-            output.pcrSelections[0].pcrSelect[2]
+            digests.get(HashingAlgorithm::Sha256).unwrap(),
+            &event_digest[..]
         );
+        let expected = hash::hash(HashingAlgorithm::Sha256, &[&[0; 32], &event_digest]).unwrap();
+        assert_eq!(read_pcr(&mut context, PcrSlot::Slot23), expected);
 
-        // Check that there exist a digest that is not empty
-        assert_eq!(tpml_digest.count, 1);
-        let digest = &tpml_digest.digests[0];
-        assert_ne!(digest.size, 0);
+        context.pcr_reset(ESYS_TR_PCR23).unwrap();
+    }
+
+    #[test]
+    fn test_pcr_allocate() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        // Keep the SHA256 bank fully allocated, leaving the other banks unchanged
+        let pcr_allocation = PcrSelectionsBuilder::new()
+            .with_selection(
+                HashingAlgorithm::Sha256,
+                &[
+                    PcrSlot::Slot0,
+                    PcrSlot::Slot1,
+                    PcrSlot::Slot2,
+                    PcrSlot::Slot3,
+                    PcrSlot::Slot4,
+                    PcrSlot::Slot5,
+                    PcrSlot::Slot6,
+                    PcrSlot::Slot7,
+                    PcrSlot::Slot8,
+                    PcrSlot::Slot9,
+                    PcrSlot::Slot10,
+                    PcrSlot::Slot11,
+                    PcrSlot::Slot12,
+                    PcrSlot::Slot13,
+                    PcrSlot::Slot14,
+                    PcrSlot::Slot15,
+                    PcrSlot::Slot16,
+                    PcrSlot::Slot17,
+                    PcrSlot::Slot18,
+                    PcrSlot::Slot19,
+                    PcrSlot::Slot20,
+                    PcrSlot::Slot21,
+                    PcrSlot::Slot22,
+                    PcrSlot::Slot23,
+                ],
+            )
+            .build();
+        let (allocation_success, max_pcr, size_needed, size_available) = context
+            .pcr_allocate(ESYS_TR_RH_PLATFORM, pcr_allocation)
+            .unwrap();
+        assert!(allocation_success);
+        assert_eq!(max_pcr, 24);
+        assert!(size_needed <= size_available);
     }
 }
 
 mod test_quote {
     use super::*;
+    use std::convert::TryFrom;
+    use tss_esapi::utils::attest::{Attest, AttestInfo};
+    use tss_esapi::utils::tags::StructureTag;
+    use tss_esapi::utils::verify::verify_quote;
 
     #[test]
     fn pcr_quote() {
@@ -363,14 +494,263 @@ mod test_quote {
         // No qualifying data
         let qualifying_data = vec![0xff; 16];
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
             .unwrap();
 
         let res = context
-            .quote(key_handle, &qualifying_data, scheme, pcr_selections)
+            .quote(key_handle, &qualifying_data, scheme, pcr_selections.clone())
             .expect("Failed to get a quote");
         assert!(res.0.size != 0);
+
+        let attest = Attest::try_from(res.0).unwrap();
+        assert_eq!(attest.attestation_type(), StructureTag::AttestQuote);
+        assert_eq!(attest.extra_data(), &qualifying_data[..]);
+        match attest.attested() {
+            AttestInfo::Quote(info) => {
+                assert_eq!(info.pcr_selections, pcr_selections);
+                assert_eq!(info.pcr_digest.len(), 32);
+            }
+            _ => panic!("Wrong attestation type returned."),
+        }
+    }
+
+    #[test]
+    fn verify_pcr_quote() {
+        let mut context = create_ctx_with_session();
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot1])
+            .build();
+        let scheme = TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        };
+        let nonce = vec![0xab; 16];
+
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        let key_pub = context.read_public(key_handle).unwrap();
+
+        let (attest, signature) = context
+            .quote(key_handle, &nonce, scheme, pcr_selections.clone())
+            .unwrap();
+        let attest = &attest.attestationData[..attest.size as usize];
+        let (_, pcr_data) = context.pcr_read(pcr_selections).unwrap();
+
+        let verification = verify_quote(&key_pub, attest, &signature, &nonce, &pcr_data).unwrap();
+        assert!(verification.is_valid());
+
+        let verification =
+            verify_quote(&key_pub, attest, &signature, &[0xcd; 16], &pcr_data).unwrap();
+        assert!(!verification.is_valid());
+    }
+}
+
+mod test_certify {
+    use super::*;
+    use std::convert::TryFrom;
+    use tss_esapi::utils::attest::{Attest, AttestInfo};
+
+    fn null_scheme() -> TPMT_SIG_SCHEME {
+        TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_certify() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_PASSWORD, ESYS_TR_NONE));
+        let (certify_info, _) = context
+            .certify(key_handle, key_handle, &[0x12; 16], null_scheme())
+            .unwrap();
+
+        let attest = Attest::try_from(certify_info).unwrap();
+        assert_eq!(attest.extra_data(), &[0x12; 16]);
+        match attest.attested() {
+            AttestInfo::Certify(info) => assert!(!info.name.is_empty()),
+            _ => panic!("Wrong attestation type returned."),
+        }
+    }
+
+    #[test]
+    fn test_certify_creation() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, creation) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        assert_eq!(creation.creation_hash.len(), 32);
+        assert_eq!(creation.creation_ticket.hierarchy(), Hierarchy::Owner);
+
+        let (certify_info, _) = context
+            .certify_creation(
+                key_handle,
+                key_handle,
+                &[0x34; 16],
+                &creation.creation_hash,
+                null_scheme(),
+                creation.creation_ticket.clone(),
+            )
+            .unwrap();
+
+        let attest = Attest::try_from(certify_info).unwrap();
+        assert_eq!(attest.extra_data(), &[0x34; 16]);
+        match attest.attested() {
+            AttestInfo::Creation(info) => {
+                assert_eq!(info.creation_hash, creation.creation_hash);
+            }
+            _ => panic!("Wrong attestation type returned."),
+        }
+
+        // The ticket does not match another creation hash
+        assert!(context
+            .certify_creation(
+                key_handle,
+                key_handle,
+                &[],
+                &[0xaa; 32],
+                null_scheme(),
+                creation.creation_ticket,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_create_key_creation_data() {
+        let mut context = create_ctx_with_session();
+        let (prim_key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &decryption_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        let (_, _, creation) = context
+            .create_key(prim_key_handle, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        assert_eq!(creation.creation_hash.len(), 32);
+        assert!(!creation.creation_ticket.digest().is_empty());
+    }
+
+    #[test]
+    fn test_get_time() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_PASSWORD, ESYS_TR_NONE));
+        let (time_info, _) = context
+            .get_time(key_handle, &[0x56; 16], null_scheme())
+            .unwrap();
+
+        let attest = Attest::try_from(time_info).unwrap();
+        assert_eq!(attest.extra_data(), &[0x56; 16]);
+        match attest.attested() {
+            AttestInfo::Time(info) => {
+                assert_eq!(info.firmware_version, attest.firmware_version());
+            }
+            _ => panic!("Wrong attestation type returned."),
+        }
+    }
+}
+
+mod test_audit {
+    use super::*;
+    use std::convert::TryFrom;
+    use tss_esapi::utils::attest::{Attest, AttestInfo};
+    use tss_esapi::utils::audit::{self, AuditedCommand};
+    use tss_esapi::utils::command_code::CommandCode;
+
+    fn null_scheme() -> TPMT_SIG_SCHEME {
+        TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_session_audit() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+
+        let audit_session = context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                TPM2_SE_HMAC,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap();
+        let session_attr = TpmaSessionBuilder::new()
+            .with_flag(TPMA_SESSION_CONTINUESESSION)
+            .with_audit_reset()
+            .build();
+        context
+            .tr_sess_set_attributes(audit_session, session_attr)
+            .unwrap();
+        context.set_sessions((audit_session, ESYS_TR_NONE, ESYS_TR_NONE));
+        let random = context.get_random(16).unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_PASSWORD, ESYS_TR_NONE));
+        let (audit_info, _) = context
+            .get_session_audit_digest(key_handle, audit_session, &[], null_scheme())
+            .unwrap();
+
+        let mut response_parameters = vec![0x00, 0x10];
+        response_parameters.extend_from_slice(&random);
+        let expected = audit::audit_digest(
+            HashingAlgorithm::Sha256,
+            &[AuditedCommand {
+                command_code: CommandCode::GetRandom,
+                handle_names: vec![],
+                command_parameters: vec![0x00, 0x10],
+                response_parameters,
+            }],
+        )
+        .unwrap();
+        match Attest::try_from(audit_info).unwrap().attested() {
+            AttestInfo::SessionAudit(info) => assert_eq!(info.session_digest, expected),
+            _ => panic!("Wrong attestation type returned."),
+        }
+    }
+
+    #[test]
+    fn test_command_audit() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context
+            .set_command_code_audit_status(ESYS_TR_RH_OWNER, None, &[CommandCode::GetRandom], &[])
+            .unwrap();
+        let _ = context.get_random(16).unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_PASSWORD, ESYS_TR_NONE));
+        let (audit_info, _) = context
+            .get_command_audit_digest(key_handle, &[0x78; 16], null_scheme())
+            .unwrap();
+        let attest = Attest::try_from(audit_info).unwrap();
+        assert_eq!(attest.extra_data(), &[0x78; 16]);
+        match attest.attested() {
+            AttestInfo::CommandAudit(info) => {
+                assert_eq!(info.audit_digest.len(), info.digest_algorithm.digest_size());
+            }
+            _ => panic!("Wrong attestation type returned."),
+        }
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context
+            .set_command_code_audit_status(ESYS_TR_RH_OWNER, None, &[], &[CommandCode::GetRandom])
+            .unwrap();
     }
 }
 
@@ -436,7 +816,7 @@ mod test_create_primary {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -522,7 +902,7 @@ mod test_create {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -533,7 +913,7 @@ mod test_create {
             )
             .unwrap();
 
-        let (_, _) = context
+        let (_, _, _) = context
             .create_key(
                 prim_key_handle,
                 &decryption_key_pub(),
@@ -550,7 +930,7 @@ mod test_create {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -578,7 +958,7 @@ mod test_create {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -606,7 +986,7 @@ mod test_create {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -634,7 +1014,7 @@ mod test_create {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -666,7 +1046,7 @@ mod test_load {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -677,7 +1057,7 @@ mod test_load {
             )
             .unwrap();
 
-        let (private, public) = context
+        let (private, public, _) = context
             .create_key(
                 prim_key_handle,
                 &signing_key_pub(),
@@ -700,7 +1080,7 @@ mod test_sign {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -730,7 +1110,7 @@ mod test_sign {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -760,7 +1140,7 @@ mod test_sign {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -794,7 +1174,7 @@ mod test_verify_sig {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -828,7 +1208,7 @@ mod test_verify_sig {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -865,7 +1245,7 @@ mod test_verify_sig {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -890,7 +1270,7 @@ mod test_verify_sig {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -960,7 +1340,7 @@ mod test_read_pub {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -982,7 +1362,7 @@ mod test_flush_context {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -1001,7 +1381,7 @@ mod test_flush_context {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -1012,7 +1392,7 @@ mod test_flush_context {
             )
             .unwrap();
 
-        let (private, public) = context
+        let (private, public, _) = context
             .create_key(
                 prim_key_handle,
                 &signing_key_pub(),
@@ -1037,7 +1417,7 @@ mod test_ctx_save {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let key_handle = context
+        let (key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -1055,7 +1435,7 @@ mod test_ctx_save {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -1066,7 +1446,7 @@ mod test_ctx_save {
             )
             .unwrap();
 
-        let (private, public) = context
+        let (private, public, _) = context
             .create_key(
                 prim_key_handle,
                 &signing_key_pub(),
@@ -1091,7 +1471,7 @@ mod test_ctx_load {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &decryption_key_pub(),
@@ -1102,7 +1482,7 @@ mod test_ctx_load {
             )
             .unwrap();
 
-        let (private, public) = context
+        let (private, public, _) = context
             .create_key(
                 prim_key_handle,
                 &signing_key_pub(),
@@ -1129,7 +1509,7 @@ mod test_handle_auth {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -1165,7 +1545,7 @@ mod test_handle_auth {
         let mut context = create_ctx_with_session();
         let key_auth: Vec<u8> = context.get_random(16).unwrap();
 
-        let prim_key_handle = context
+        let (prim_key_handle, _) = context
             .create_primary_key(
                 ESYS_TR_RH_OWNER,
                 &signing_key_pub(),
@@ -1236,6 +1616,155 @@ mod test_test_parms {
     }
 }
 
+mod test_get_capability {
+    use super::*;
+    use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+    use tss_esapi::utils::capabilities::{Algorithm, CapabilityData, HandleType};
+    use tss_esapi::utils::command_code::CommandCode;
+
+    #[test]
+    fn test_get_algorithms() {
+        let mut context = create_ctx_without_session();
+        let algorithms = context.get_algorithms().unwrap();
+        assert!(algorithms.contains(&Algorithm::Hashing(HashingAlgorithm::Sha256)));
+    }
+
+    #[test]
+    fn test_get_commands() {
+        let mut context = create_ctx_without_session();
+        let commands = context.get_commands().unwrap();
+        assert!(commands.contains(&CommandCode::GetCapability));
+        assert!(commands.contains(&CommandCode::GetRandom));
+    }
+
+    #[test]
+    fn test_get_commands_paged() {
+        let mut context = create_ctx_without_session();
+        let (first_page, more_data) = context
+            .get_capability(TPM2_CAP_COMMANDS, TPM2_CC_FIRST, 4)
+            .unwrap();
+        assert!(more_data);
+        match first_page {
+            CapabilityData::Commands(commands) => assert!(commands.len() <= 4),
+            _ => panic!("Wrong capability returned."),
+        }
+    }
+
+    #[test]
+    fn test_get_pcr_banks() {
+        let mut context = create_ctx_without_session();
+        let pcr_banks = context.get_pcr_banks().unwrap();
+        assert!(pcr_banks
+            .iter()
+            .any(|(hashing_algorithm, _, pcr_slots)| hashing_algorithm
+                == HashingAlgorithm::Sha256
+                && !pcr_slots.is_empty()));
+    }
+
+    #[test]
+    fn test_get_properties() {
+        let mut context = create_ctx_without_session();
+        let fixed_properties = context.get_fixed_properties().unwrap();
+        assert!(fixed_properties
+            .iter()
+            .all(|(property, _)| *property >= TPM2_PT_FIXED && *property < TPM2_PT_VAR));
+        assert!(fixed_properties
+            .iter()
+            .any(|(property, _)| *property == TPM2_PT_MANUFACTURER));
+
+        let variable_properties = context.get_variable_properties().unwrap();
+        assert!(variable_properties.iter().all(
+            |(property, _)| *property >= TPM2_PT_VAR && *property < TPM2_PT_VAR + TPM2_PT_GROUP
+        ));
+    }
+
+    #[test]
+    fn test_get_handles() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let key_handle = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap()
+            .0;
+        let handles = context.get_handles(HandleType::Transient).unwrap();
+        assert!(!handles.is_empty());
+        assert!(handles
+            .iter()
+            .all(|handle| *handle >= HandleType::Transient.first_handle()
+                && *handle <= HandleType::Transient.last_handle()));
+        context.flush_context(key_handle).unwrap();
+    }
+
+    #[test]
+    fn test_get_ecc_curves() {
+        let mut context = create_ctx_without_session();
+        let _ = context.get_ecc_curves().unwrap();
+    }
+}
+
+mod test_tpm_info {
+    use super::*;
+    use tss_esapi::abstraction::tpm_info;
+
+    #[test]
+    fn test_get_tpm_info() {
+        let mut context = create_ctx_without_session();
+        let info = tpm_info::get_tpm_info(&mut context).unwrap();
+        assert_eq!(info.spec_family, "2.0");
+        assert!(!info.manufacturer_id.is_empty());
+        assert!(info.pcr_count >= 24);
+        assert!(info.max_digest_size >= 32);
+    }
+}
+
+mod test_evict_control {
+    use super::*;
+    use tss_esapi::utils::capabilities::HandleType;
+
+    const PERSISTENT_HANDLE: TPM2_HANDLE = 0x8100_0100;
+
+    #[test]
+    fn test_persist_and_evict() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let key_handle = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap()
+            .0;
+
+        let persistent_key_handle = context
+            .evict_control(ESYS_TR_RH_OWNER, key_handle, PERSISTENT_HANDLE)
+            .unwrap()
+            .unwrap();
+        context.flush_context(key_handle).unwrap();
+        context.tr_close(persistent_key_handle).unwrap();
+        assert!(context
+            .get_handles(HandleType::Persistent)
+            .unwrap()
+            .contains(&PERSISTENT_HANDLE));
+
+        // Open the persistent key again, as another client would
+        let opened_key_handle = context.tr_from_tpm_public(PERSISTENT_HANDLE).unwrap();
+        let _ = context.read_public(opened_key_handle).unwrap();
+        assert!(context
+            .evict_control(ESYS_TR_RH_OWNER, opened_key_handle, PERSISTENT_HANDLE)
+            .unwrap()
+            .is_none());
+        assert!(!context
+            .get_handles(HandleType::Persistent)
+            .unwrap()
+            .contains(&PERSISTENT_HANDLE));
+    }
+
+    #[test]
+    fn test_open_pcr() {
+        let mut context = create_ctx_without_session();
+        let pcr_handle = context.tr_from_tpm_public(16).unwrap();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context.pcr_reset(pcr_handle).unwrap();
+    }
+}
+
 mod test_hash {
     use super::*;
 
@@ -1262,3 +1791,1300 @@ mod test_hash {
         assert_ne!(ticket.digest().len(), 0); // Should do some better checking of the digest
     }
 }
+
+mod test_nv {
+    use super::*;
+    use tss_esapi::utils::nv::{NvIndexAttributes, NvIndexType, NvPublicBuilder};
+
+    #[test]
+    fn test_define_read_public_undefine() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0010)
+            .with_data_size(32)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[1, 2, 3], &nv_public)
+            .unwrap();
+
+        let read_public = context.nv_read_public(nv_handle).unwrap();
+        assert_eq!(read_public.nv_index(), 0x0150_0010);
+        assert_eq!(read_public.data_size(), 32);
+        assert_eq!(read_public.name_alg(), HashingAlgorithm::Sha256);
+        assert_eq!(
+            read_public.attributes().index_type().unwrap(),
+            NvIndexType::Ordinary
+        );
+        assert!(read_public.attributes().auth_read());
+        assert!(!read_public.attributes().written());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_write_read_chunked() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0011)
+            .with_data_size(1500)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        let data: Vec<u8> = (0..1500).map(|i| (i % 251) as u8).collect();
+        context.nv_write(nv_handle, nv_handle, &data, 0).unwrap();
+        let read_data = context.nv_read(nv_handle, nv_handle, 1500, 0).unwrap();
+        assert_eq!(data, read_data);
+
+        let read_data = context.nv_read(nv_handle, nv_handle, 600, 700).unwrap();
+        assert_eq!(&data[700..1300], &read_data[..]);
+        assert!(context
+            .nv_read_public(nv_handle)
+            .unwrap()
+            .attributes()
+            .written());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_owner_read_write() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0012)
+            .with_index_attributes(NvIndexAttributes::new_owner_read_write())
+            .with_data_size(16)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        context
+            .nv_write(ESYS_TR_RH_OWNER, nv_handle, &HASH[..16], 0)
+            .unwrap();
+        let read_data = context.nv_read(ESYS_TR_RH_OWNER, nv_handle, 16, 0).unwrap();
+        assert_eq!(&HASH[..16], &read_data[..]);
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_invalid_nv_public() {
+        assert!(NvPublicBuilder::new().with_data_size(32).build().is_err());
+        assert!(NvPublicBuilder::new()
+            .with_nv_index(0x8100_0001)
+            .with_data_size(32)
+            .build()
+            .is_err());
+        assert!(NvPublicBuilder::new()
+            .with_nv_index(0x0150_0013)
+            .with_data_size(32)
+            .with_auth_policy(&KEY[..65])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_nv_public_conversion() {
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_index_type(NvIndexType::Counter);
+        attributes.set_no_da(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0014)
+            .with_name_alg(HashingAlgorithm::Sha1)
+            .with_index_attributes(attributes)
+            .with_auth_policy(&HASH[..20])
+            .with_data_size(8)
+            .build()
+            .unwrap();
+
+        let tss_nv_public: TPM2B_NV_PUBLIC = nv_public.clone().try_into().unwrap();
+        assert_eq!(tss_nv_public.nvPublic.nvIndex, 0x0150_0014);
+        assert_eq!(tss_nv_public.nvPublic.nameAlg, TPM2_ALG_SHA1);
+        assert_eq!(
+            tss_nv_public.nvPublic.attributes,
+            TPMA_NV_AUTHREAD
+                | TPMA_NV_AUTHWRITE
+                | TPMA_NV_NO_DA
+                | (u32::from(TPM2_NT_COUNTER) << TPMA_NV_TPM2_NT_SHIFT)
+        );
+        assert_eq!(tss_nv_public.nvPublic.authPolicy.size, 20);
+        assert_eq!(nv_public, tss_nv_public.try_into().unwrap());
+    }
+}
+
+mod test_nv_special {
+    use super::*;
+    use enumflags2::BitFlags;
+    use tss_esapi::abstraction::nv::{NvBits, NvCounter, NvExtend};
+    use tss_esapi::response_code::{Error, WrapperErrorKind};
+    use tss_esapi::utils::nv::{NvIndexAttributes, NvIndexType, NvPublicBuilder};
+
+    #[derive(BitFlags, Copy, Clone, Debug, PartialEq)]
+    #[repr(u64)]
+    enum Feature {
+        Basic = 0x1,
+        Extra = 0x2,
+        Premium = 0x100_0000_0000,
+    }
+
+    fn attributes(index_type: NvIndexType) -> NvIndexAttributes {
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_index_type(index_type);
+        attributes
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0020)
+            .with_index_attributes(attributes(NvIndexType::Counter))
+            .with_data_size(8)
+            .build()
+            .unwrap();
+        let counter =
+            NvCounter::define(&mut context, ESYS_TR_RH_OWNER, &[], &nv_public, None).unwrap();
+
+        counter.increment(&mut context).unwrap();
+        let first = counter.read(&mut context).unwrap();
+        counter.increment(&mut context).unwrap();
+        counter.increment(&mut context).unwrap();
+        assert_eq!(counter.read(&mut context).unwrap(), first + 2);
+
+        let reopened = NvCounter::open(&mut context, counter.nv_index_handle(), None).unwrap();
+        assert_eq!(reopened.read(&mut context).unwrap(), first + 2);
+        assert!(NvExtend::open(&mut context, counter.nv_index_handle(), None).is_err());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, counter.nv_index_handle())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0021)
+            .with_index_attributes(attributes(NvIndexType::Bits))
+            .with_data_size(8)
+            .build()
+            .unwrap();
+        let bits: NvBits<Feature> =
+            NvBits::define(&mut context, ESYS_TR_RH_OWNER, &[], &nv_public, None).unwrap();
+
+        bits.set_bits(&mut context, Feature::Basic.into()).unwrap();
+        assert_eq!(bits.read(&mut context).unwrap(), Feature::Basic);
+        bits.set_bits(&mut context, Feature::Premium.into())
+            .unwrap();
+        assert_eq!(
+            bits.read(&mut context).unwrap(),
+            Feature::Basic | Feature::Premium
+        );
+        assert_eq!(bits.read_raw(&mut context).unwrap(), 0x100_0000_0001);
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, bits.nv_index_handle())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0022)
+            .with_index_attributes(attributes(NvIndexType::Extend))
+            .with_data_size(32)
+            .build()
+            .unwrap();
+        let extend =
+            NvExtend::define(&mut context, ESYS_TR_RH_OWNER, &[], &nv_public, None).unwrap();
+
+        extend.extend(&mut context, b"measurement").unwrap();
+        let digest = extend.read(&mut context).unwrap();
+        assert_eq!(digest.len(), 32);
+
+        // the new value is H(old value || data), old value being all zeroes
+        let mut data = vec![0; 32];
+        data.extend_from_slice(b"measurement");
+        let (expected, _) = context
+            .hash(&data, HashingAlgorithm::Sha256, Hierarchy::Null)
+            .unwrap();
+        assert_eq!(digest, expected);
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, extend.nv_index_handle())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_wrong_type() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0023)
+            .with_data_size(8)
+            .build()
+            .unwrap();
+        assert!(NvCounter::define(&mut context, ESYS_TR_RH_OWNER, &[], &nv_public, None).is_err());
+    }
+
+    #[test]
+    fn test_extend_wrong_size() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0024)
+            .with_index_attributes(attributes(NvIndexType::Extend))
+            .with_data_size(20)
+            .build()
+            .unwrap();
+        assert_eq!(
+            NvExtend::define(&mut context, ESYS_TR_RH_OWNER, &[], &nv_public, None).unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::WrongParamSize)
+        );
+    }
+}
+
+mod test_nv_lock {
+    use super::*;
+    use tss_esapi::utils::attest::AttestInfo;
+    use tss_esapi::utils::nv::{NvIndexAttributes, NvPublicBuilder};
+    use tss_esapi::utils::tags::StructureTag;
+
+    #[test]
+    fn test_write_lock() {
+        let mut context = create_ctx_with_session();
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_write_stclear(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0030)
+            .with_index_attributes(attributes)
+            .with_data_size(16)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        context
+            .nv_write(nv_handle, nv_handle, &HASH[..16], 0)
+            .unwrap();
+        context.nv_write_lock(nv_handle, nv_handle).unwrap();
+        assert!(context
+            .nv_read_public(nv_handle)
+            .unwrap()
+            .attributes()
+            .write_locked());
+        assert!(context
+            .nv_write(nv_handle, nv_handle, &HASH[16..32], 0)
+            .is_err());
+        assert_eq!(
+            context.nv_read(nv_handle, nv_handle, 16, 0).unwrap(),
+            HASH[..16].to_vec()
+        );
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_read_lock() {
+        let mut context = create_ctx_with_session();
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_read_stclear(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0031)
+            .with_index_attributes(attributes)
+            .with_data_size(16)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        context
+            .nv_write(nv_handle, nv_handle, &HASH[..16], 0)
+            .unwrap();
+        context.nv_read_lock(nv_handle, nv_handle).unwrap();
+        assert!(context.nv_read(nv_handle, nv_handle, 16, 0).is_err());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_global_write_lock() {
+        let mut context = create_ctx_with_session();
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_global_lock(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0032)
+            .with_index_attributes(attributes)
+            .with_data_size(16)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        context.nv_global_write_lock(ESYS_TR_RH_OWNER).unwrap();
+        assert!(context
+            .nv_write(nv_handle, nv_handle, &HASH[..16], 0)
+            .is_err());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_nv_certify() {
+        let mut context = create_ctx_with_session();
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0033)
+            .with_data_size(32)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+        context
+            .nv_write(nv_handle, nv_handle, &HASH[..32], 0)
+            .unwrap();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+
+        // Both the signing key and the NV index need to be authorized
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_PASSWORD, ESYS_TR_NONE));
+        let scheme = TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        };
+        let (certify_info, attest, signature) = context
+            .nv_certify(key_handle, nv_handle, nv_handle, &[0xff; 16], scheme, 16, 8)
+            .unwrap();
+        assert_ne!(certify_info.size, 0);
+
+        assert_eq!(attest.attestation_type(), StructureTag::AttestNv);
+        assert_eq!(attest.extra_data(), &[0xff; 16][..]);
+        match attest.attested() {
+            AttestInfo::Nv(info) => {
+                assert_eq!(info.offset, 8);
+                assert_eq!(&info.nv_contents[..], &HASH[8..24]);
+                assert_eq!(
+                    &info.index_name[..],
+                    &context.tr_get_name(nv_handle).unwrap().name[..info.index_name.len()]
+                );
+            }
+            _ => panic!("Wrong attestation type returned."),
+        }
+        if let SignatureData::RsaSignature(signature) = signature.signature {
+            assert_ne!(signature.len(), 0);
+        } else {
+            panic!("Wrong signature type returned.");
+        }
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+}
+
+mod test_ek_certificate {
+    use super::*;
+    use tss_esapi::abstraction::ek;
+    use tss_esapi::utils::algorithm_specifiers::AsymmetricAlgorithm;
+    use tss_esapi::utils::nv::{NvIndexAttributes, NvIndexType, NvPublicBuilder};
+
+    fn fake_certificate(content_len: usize) -> Vec<u8> {
+        let mut certificate = vec![0x30, 0x82, (content_len >> 8) as u8, content_len as u8];
+        certificate.extend((0..content_len).map(|i| (i % 256) as u8));
+        certificate
+    }
+
+    fn provision(context: &mut Context, nv_index: TPM2_HANDLE, data: &[u8], size: u16) -> ESYS_TR {
+        let mut attributes = NvIndexAttributes(0);
+        attributes.set_index_type(NvIndexType::Ordinary);
+        attributes.set_pp_write(true);
+        attributes.set_pp_read(true);
+        attributes.set_owner_read(true);
+        attributes.set_auth_read(true);
+        attributes.set_no_da(true);
+        attributes.set_platform_create(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(nv_index)
+            .with_index_attributes(attributes)
+            .with_data_size(size)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_PLATFORM, &[], &nv_public)
+            .unwrap();
+        context
+            .nv_write(ESYS_TR_RH_PLATFORM, nv_handle, data, 0)
+            .unwrap();
+        nv_handle
+    }
+
+    #[test]
+    fn test_retrieve_large_certificate() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let certificate = fake_certificate(1200);
+        // Pad the index after the certificate
+        let mut data = certificate.clone();
+        data.resize(1300, 0xff);
+        let nv_handle = provision(
+            &mut context,
+            ek::RSA_2048_EK_CERTIFICATE_NV_INDEX,
+            &data,
+            1300,
+        );
+
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+        let retrieved =
+            ek::retrieve_ek_certificate(&mut context, AsymmetricAlgorithm::Rsa).unwrap();
+        assert_eq!(certificate, retrieved);
+        let listed = ek::list_ek_certificates(&mut context).unwrap();
+        assert!(listed.contains(&(ek::RSA_2048_EK_CERTIFICATE_NV_INDEX, certificate)));
+        assert!(ek::retrieve_ek_certificate(&mut context, AsymmetricAlgorithm::Ecc).is_err());
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        context
+            .nv_undefine_space(ESYS_TR_RH_PLATFORM, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_retrieve_certificate_chain() {
+        let mut context = create_ctx_without_session();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let first = fake_certificate(300);
+        let second = fake_certificate(400);
+        let mut data = first.clone();
+        data.extend_from_slice(&second);
+        let nv_handle = provision(
+            &mut context,
+            ek::EK_CERTIFICATE_CHAIN_NV_INDEX_FIRST,
+            &data,
+            data.len() as u16,
+        );
+
+        let chain = ek::retrieve_ek_certificate_chain(&mut context).unwrap();
+        assert_eq!(chain, vec![first, second]);
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_PLATFORM, nv_handle)
+            .unwrap();
+    }
+}
+
+mod test_policy {
+    use super::*;
+
+    // Digest of PolicyAuthValue (and PolicyPassword) for SHA256
+    const POLICY_AUTH_VALUE_DIGEST: [u8; 32] = [
+        0x8f, 0xcd, 0x21, 0x69, 0xab, 0x92, 0x69, 0x4e, 0x0c, 0x63, 0x3f, 0x1a, 0xb7, 0x72, 0x84,
+        0x2b, 0x82, 0x41, 0xbb, 0xc2, 0x02, 0x88, 0x98, 0x1f, 0xc7, 0xac, 0x1e, 0xdd, 0xc1, 0xfd,
+        0xdb, 0x0e,
+    ];
+
+    fn start_trial_session(context: &mut Context) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                TPM2_SE_TRIAL,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_auth_value_and_password() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_trial_session(&mut context);
+
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            vec![0; 32]
+        );
+        context.policy_auth_value(trial_session).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            POLICY_AUTH_VALUE_DIGEST.to_vec()
+        );
+
+        context.policy_restart(trial_session).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            vec![0; 32]
+        );
+        context.policy_password(trial_session).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            POLICY_AUTH_VALUE_DIGEST.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_policy_pcr() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_trial_session(&mut context);
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot1])
+            .build();
+
+        // Let the TPM use the current PCR values
+        context
+            .policy_pcr(trial_session, &[], pcr_selections.clone())
+            .unwrap();
+        let implicit_digest = context.policy_get_digest(trial_session).unwrap();
+
+        // Provide the digest of the PCR values explicitly
+        let (_, pcr_data) = context.pcr_read(pcr_selections.clone()).unwrap();
+        let mut concatenated = Vec::new();
+        for (_, _, digest) in pcr_data.iter() {
+            concatenated.extend_from_slice(digest);
+        }
+        let (pcr_digest, _) = context
+            .hash(&concatenated, HashingAlgorithm::Sha256, Hierarchy::Null)
+            .unwrap();
+        context.policy_restart(trial_session).unwrap();
+        context
+            .policy_pcr(trial_session, &pcr_digest, pcr_selections)
+            .unwrap();
+        let explicit_digest = context.policy_get_digest(trial_session).unwrap();
+
+        assert_eq!(implicit_digest.len(), 32);
+        assert_eq!(implicit_digest, explicit_digest);
+    }
+}
+
+mod test_policy_signed_secret {
+    use super::*;
+    use std::time::Duration;
+    use tss_esapi::response_code::{Error, WrapperErrorKind};
+    use tss_esapi::utils::tags::StructureTag;
+
+    fn start_policy_session(context: &mut Context, session_type: TPM2_SE) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                session_type,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_secret() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let (_, ticket) = context
+            .policy_secret(ESYS_TR_RH_OWNER, trial_session, &[], &[], b"ref", None)
+            .unwrap();
+        assert!(ticket.is_none());
+        let nonce_tpm = context.tr_sess_get_nonce_tpm(policy_session).unwrap();
+        let _ = context
+            .policy_secret(
+                ESYS_TR_RH_OWNER,
+                policy_session,
+                &nonce_tpm,
+                &[],
+                b"ref",
+                None,
+            )
+            .unwrap();
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            context.policy_get_digest(policy_session).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_policy_secret_ticket() {
+        let mut context = create_ctx_without_session();
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let nonce_tpm = context.tr_sess_get_nonce_tpm(policy_session).unwrap();
+        let (timeout, ticket) = context
+            .policy_secret(
+                ESYS_TR_RH_OWNER,
+                policy_session,
+                &nonce_tpm,
+                &[],
+                &[],
+                Some(Duration::from_secs(3600)),
+            )
+            .unwrap();
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+        let ticket = ticket.unwrap();
+        assert_eq!(ticket.tag(), StructureTag::AuthSecret);
+        let expected_digest = context.policy_get_digest(policy_session).unwrap();
+
+        let owner_name = context.tr_get_name(ESYS_TR_RH_OWNER).unwrap();
+        context.policy_restart(policy_session).unwrap();
+        context
+            .policy_ticket(
+                policy_session,
+                &timeout,
+                &[],
+                &[],
+                &owner_name.name[..owner_name.size as usize],
+                ticket,
+            )
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(policy_session).unwrap(),
+            expected_digest
+        );
+    }
+
+    #[test]
+    fn test_policy_secret_expiration_too_short() {
+        let mut context = create_ctx_without_session();
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let nonce_tpm = context.tr_sess_get_nonce_tpm(policy_session).unwrap();
+        assert_eq!(
+            context
+                .policy_secret(
+                    ESYS_TR_RH_OWNER,
+                    policy_session,
+                    &nonce_tpm,
+                    &[],
+                    &[],
+                    Some(Duration::from_millis(500)),
+                )
+                .unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::WrongParamSize)
+        );
+    }
+
+    #[test]
+    fn test_policy_signed() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+        let nonce_tpm = context.tr_sess_get_nonce_tpm(policy_session).unwrap();
+
+        // aHash = H(nonceTPM || expiration || cpHashA || policyRef), with expiration = 0
+        let mut to_sign = nonce_tpm.clone();
+        to_sign.extend_from_slice(&[0, 0, 0, 0]);
+        to_sign.extend_from_slice(b"policy");
+        let (a_hash, _) = context
+            .hash(&to_sign, HashingAlgorithm::Sha256, Hierarchy::Null)
+            .unwrap();
+        let scheme = TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        };
+        let validation = TPMT_TK_HASHCHECK {
+            tag: TPM2_ST_HASHCHECK,
+            hierarchy: TPM2_RH_NULL,
+            digest: Default::default(),
+        };
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let signature = context
+            .sign(key_handle, &a_hash, scheme, &validation)
+            .unwrap();
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+
+        let (_, ticket) = context
+            .policy_signed(
+                key_handle,
+                policy_session,
+                &nonce_tpm,
+                &[],
+                b"policy",
+                None,
+                signature,
+            )
+            .unwrap();
+        assert!(ticket.is_none());
+        assert_eq!(context.policy_get_digest(policy_session).unwrap().len(), 32);
+    }
+}
+
+mod test_policy_or_authorize {
+    use super::*;
+
+    fn start_policy_session(context: &mut Context, session_type: TPM2_SE) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                session_type,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    fn sha256(context: &mut Context, data: &[u8]) -> Vec<u8> {
+        context
+            .hash(data, HashingAlgorithm::Sha256, Hierarchy::Null)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_policy_or() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+
+        context.policy_auth_value(trial_session).unwrap();
+        let auth_value_digest = context.policy_get_digest(trial_session).unwrap();
+        let other_digest = HASH[..32].to_vec();
+        context
+            .policy_or(trial_session, &[&auth_value_digest, &other_digest])
+            .unwrap();
+
+        // policyDigest = H(0...0 || TPM_CC_PolicyOR || digests)
+        let mut data = vec![0; 32];
+        data.extend_from_slice(&TPM2_CC_PolicyOR.to_be_bytes());
+        data.extend_from_slice(&auth_value_digest);
+        data.extend_from_slice(&other_digest);
+        let expected = sha256(&mut context, &data);
+        assert_eq!(context.policy_get_digest(trial_session).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_policy_or_wrong_size() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let digest = [0; 32];
+
+        assert!(context.policy_or(trial_session, &[&digest]).is_err());
+        assert!(context.policy_or(trial_session, &[&digest[..]; 9]).is_err());
+    }
+
+    #[test]
+    fn test_policy_authorize() {
+        let mut context = create_ctx_with_session();
+        let (key_handle, _) = context
+            .create_primary_key(ESYS_TR_RH_OWNER, &signing_key_pub(), &[], &[], &[], &[])
+            .unwrap();
+        let key_name = context.tr_get_name(key_handle).unwrap();
+        let key_name = key_name.name[..key_name.size as usize].to_vec();
+
+        // Approve a policy requiring the auth value of the object
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        context.policy_auth_value(trial_session).unwrap();
+        let approved_policy = context.policy_get_digest(trial_session).unwrap();
+        let policy_ref = b"v1";
+        let mut to_sign = approved_policy.clone();
+        to_sign.extend_from_slice(policy_ref);
+        let a_hash = sha256(&mut context, &to_sign);
+        let scheme = TPMT_SIG_SCHEME {
+            scheme: TPM2_ALG_NULL,
+            details: Default::default(),
+        };
+        let validation = TPMT_TK_HASHCHECK {
+            tag: TPM2_ST_HASHCHECK,
+            hierarchy: TPM2_RH_NULL,
+            digest: Default::default(),
+        };
+        let signature = context
+            .sign(key_handle, &a_hash, scheme, &validation)
+            .unwrap();
+        let ticket = context
+            .verify_signature(key_handle, &a_hash, &signature.try_into().unwrap())
+            .unwrap();
+        assert_eq!(ticket.hierarchy(), Hierarchy::Owner);
+
+        // Satisfy the approved policy, then have it authorized
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+        context.policy_auth_value(policy_session).unwrap();
+        context
+            .policy_authorize(
+                policy_session,
+                &approved_policy,
+                policy_ref,
+                &key_name,
+                ticket,
+            )
+            .unwrap();
+
+        // policyDigest = H(H(0...0 || TPM_CC_PolicyAuthorize || keyName) || policyRef)
+        let mut data = vec![0; 32];
+        data.extend_from_slice(&TPM2_CC_PolicyAuthorize.to_be_bytes());
+        data.extend_from_slice(&key_name);
+        let mut data = sha256(&mut context, &data);
+        data.extend_from_slice(policy_ref);
+        let expected = sha256(&mut context, &data);
+        assert_eq!(context.policy_get_digest(policy_session).unwrap(), expected);
+    }
+}
+
+mod test_policy_calculator {
+    use super::*;
+    use tss_esapi::utils::policy::PolicyCalculator;
+
+    fn start_trial_session(context: &mut Context) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                TPM2_SE_TRIAL,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_calculator_matches_trial_session() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_trial_session(&mut context);
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot1])
+            .build();
+
+        let (_, pcr_data) = context.pcr_read(pcr_selections.clone()).unwrap();
+        let values: Vec<&[u8]> = pcr_data.iter().map(|(_, _, digest)| digest).collect();
+        context
+            .policy_pcr(trial_session, &[], pcr_selections.clone())
+            .unwrap();
+        calculator
+            .policy_pcr_values(pcr_selections, &values)
+            .unwrap();
+
+        context.policy_auth_value(trial_session).unwrap();
+        calculator.policy_auth_value().unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let _ = context
+            .policy_secret(ESYS_TR_RH_OWNER, trial_session, &[], &[], b"ref", None)
+            .unwrap();
+        context.set_sessions((ESYS_TR_NONE, ESYS_TR_NONE, ESYS_TR_NONE));
+        calculator
+            .policy_secret(&TPM2_RH_OWNER.to_be_bytes(), b"ref")
+            .unwrap();
+
+        let first_branch = context.policy_get_digest(trial_session).unwrap();
+        assert_eq!(first_branch, calculator.digest().to_vec());
+
+        let second_branch = HASH[..32].to_vec();
+        context
+            .policy_or(trial_session, &[&first_branch, &second_branch])
+            .unwrap();
+        calculator
+            .policy_or(&[&first_branch, &second_branch])
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+    }
+}
+
+mod test_policy_nv {
+    use super::*;
+    use tss_esapi::utils::nv::NvPublicBuilder;
+    use tss_esapi::utils::policy::{ComparisonOperation, PolicyCalculator};
+
+    fn start_policy_session(context: &mut Context, session_type: TPM2_SE) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                session_type,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    fn nv_name(context: &mut Context, nv_handle: ESYS_TR) -> Vec<u8> {
+        let name = context.tr_get_name(nv_handle).unwrap();
+        name.name[..name.size as usize].to_vec()
+    }
+
+    #[test]
+    fn test_policy_nv() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0020)
+            .with_data_size(8)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+        context
+            .nv_write(nv_handle, nv_handle, &7_u64.to_be_bytes(), 0)
+            .unwrap();
+
+        let operand_b = 5_u64.to_be_bytes();
+        context
+            .policy_nv(
+                nv_handle,
+                nv_handle,
+                trial_session,
+                &operand_b,
+                0,
+                ComparisonOperation::UnsignedGreaterOrEqual,
+            )
+            .unwrap();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        calculator
+            .policy_nv(
+                &nv_name(&mut context, nv_handle),
+                &operand_b,
+                0,
+                ComparisonOperation::UnsignedGreaterOrEqual,
+            )
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        // The content of the index satisfies the comparison
+        context
+            .policy_nv(
+                nv_handle,
+                nv_handle,
+                policy_session,
+                &operand_b,
+                0,
+                ComparisonOperation::UnsignedGreaterOrEqual,
+            )
+            .unwrap();
+        assert!(context
+            .policy_nv(
+                nv_handle,
+                nv_handle,
+                policy_session,
+                &operand_b,
+                0,
+                ComparisonOperation::Equal,
+            )
+            .is_err());
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_policy_counter_timer() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+
+        // The restart count is at offset 20 of TPMS_TIME_INFO
+        let operand_b = 0_u32.to_be_bytes();
+        context
+            .policy_counter_timer(
+                trial_session,
+                &operand_b,
+                20,
+                ComparisonOperation::UnsignedGreaterOrEqual,
+            )
+            .unwrap();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        calculator
+            .policy_counter_timer(&operand_b, 20, ComparisonOperation::UnsignedGreaterOrEqual)
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context
+            .policy_counter_timer(
+                policy_session,
+                &operand_b,
+                20,
+                ComparisonOperation::UnsignedGreaterOrEqual,
+            )
+            .unwrap();
+        assert!(context
+            .policy_counter_timer(
+                policy_session,
+                &operand_b,
+                20,
+                ComparisonOperation::UnsignedLessThan,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_policy_nv_written() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+
+        context.policy_nv_written(trial_session, false).unwrap();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        calculator.policy_nv_written(false).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+    }
+
+    #[test]
+    fn test_policy_authorize_nv() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0021)
+            .with_data_size(34)
+            .build()
+            .unwrap();
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        // The index holds the approved policy, preceded by its hashing algorithm
+        let mut approved_policy = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        approved_policy.policy_auth_value().unwrap();
+        let mut nv_data = TPM2_ALG_SHA256.to_be_bytes().to_vec();
+        nv_data.extend_from_slice(approved_policy.digest());
+        context.nv_write(nv_handle, nv_handle, &nv_data, 0).unwrap();
+
+        context
+            .policy_authorize_nv(nv_handle, nv_handle, trial_session)
+            .unwrap();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        calculator
+            .policy_authorize_nv(&nv_name(&mut context, nv_handle))
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context.policy_auth_value(policy_session).unwrap();
+        context
+            .policy_authorize_nv(nv_handle, nv_handle, policy_session)
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(policy_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+}
+
+mod test_policy_command {
+    use super::*;
+    use tss_esapi::utils::command_code::CommandCode;
+    use tss_esapi::utils::policy::{self, PolicyCalculator};
+
+    fn start_trial_session(context: &mut Context) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                TPM2_SE_TRIAL,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_command_code_cp_hash_name_hash() {
+        let mut context = create_ctx_without_session();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        let owner_name = TPM2_RH_OWNER.to_be_bytes();
+        let cp_hash = policy::cp_hash(
+            HashingAlgorithm::Sha256,
+            CommandCode::Clear,
+            &[&owner_name],
+            &[],
+        )
+        .unwrap();
+        let name_hash = policy::name_hash(HashingAlgorithm::Sha256, &[&owner_name]).unwrap();
+
+        let trial_session = start_trial_session(&mut context);
+        context
+            .policy_command_code(trial_session, CommandCode::Sign)
+            .unwrap();
+        calculator.policy_command_code(CommandCode::Sign).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context.policy_restart(trial_session).unwrap();
+        context.policy_cp_hash(trial_session, &cp_hash).unwrap();
+        calculator.restart();
+        calculator.policy_cp_hash(&cp_hash).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context.policy_restart(trial_session).unwrap();
+        context.policy_name_hash(trial_session, &name_hash).unwrap();
+        calculator.restart();
+        calculator.policy_name_hash(&name_hash).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+    }
+
+    #[test]
+    fn test_policy_duplication_select_template() {
+        let mut context = create_ctx_without_session();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        let mut object_name = vec![0x00, 0x0b];
+        object_name.extend_from_slice(&HASH[..32]);
+        let mut new_parent_name = vec![0x00, 0x0b];
+        new_parent_name.extend_from_slice(&HASH[32..]);
+
+        let trial_session = start_trial_session(&mut context);
+        context
+            .policy_duplication_select(trial_session, &object_name, &new_parent_name, true)
+            .unwrap();
+        calculator
+            .policy_duplication_select(&object_name, &new_parent_name, true)
+            .unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+
+        context.policy_restart(trial_session).unwrap();
+        context.policy_template(trial_session, &HASH[..32]).unwrap();
+        calculator.restart();
+        calculator.policy_template(&HASH[..32]).unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+    }
+}
+
+mod test_policy_locality {
+    use super::*;
+    use tss_esapi::utils::nv::{NvIndexAttributes, NvPublicBuilder};
+    use tss_esapi::utils::policy::PolicyCalculator;
+    use tss_esapi::utils::LocalityAttributes;
+
+    fn start_policy_session(context: &mut Context, session_type: TPM2_SE) -> ESYS_TR {
+        context
+            .start_auth_session(
+                ESYS_TR_NONE,
+                ESYS_TR_NONE,
+                &[],
+                session_type,
+                utils::TpmtSymDefBuilder::aes_256_cfb(),
+                TPM2_ALG_SHA256,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_policy_locality_physical_presence_trial() {
+        let mut context = create_ctx_without_session();
+        let trial_session = start_policy_session(&mut context, TPM2_SE_TRIAL);
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        let mut locality = LocalityAttributes::from_locality(1).unwrap();
+        locality.set_locality_three(true);
+
+        context.policy_locality(trial_session, locality).unwrap();
+        context.policy_physical_presence(trial_session).unwrap();
+        calculator.policy_locality(locality).unwrap();
+        calculator.policy_physical_presence().unwrap();
+        assert_eq!(
+            context.policy_get_digest(trial_session).unwrap(),
+            calculator.digest().to_vec()
+        );
+    }
+
+    #[test]
+    fn test_policy_locality_nv_write() {
+        let mut context = create_ctx_without_session();
+        let locality = LocalityAttributes::from_locality(3).unwrap();
+        let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+        calculator.policy_locality(locality).unwrap();
+
+        let mut attributes = NvIndexAttributes::new_auth_read_write();
+        attributes.set_auth_write(false);
+        attributes.set_policy_write(true);
+        let nv_public = NvPublicBuilder::new()
+            .with_nv_index(0x0150_0030)
+            .with_index_attributes(attributes)
+            .with_auth_policy(calculator.digest())
+            .with_data_size(8)
+            .build()
+            .unwrap();
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        let nv_handle = context
+            .nv_define_space(ESYS_TR_RH_OWNER, &[], &nv_public)
+            .unwrap();
+
+        let policy_session = start_policy_session(&mut context, TPM2_SE_POLICY);
+        context.policy_locality(policy_session, locality).unwrap();
+        context.set_sessions((policy_session, ESYS_TR_NONE, ESYS_TR_NONE));
+
+        // Locality 0 is not allowed by the policy
+        assert!(context
+            .nv_write(nv_handle, nv_handle, &HASH[..8], 0)
+            .is_err());
+
+        context.set_tcti_locality(3).unwrap();
+        context
+            .nv_write(nv_handle, nv_handle, &HASH[..8], 0)
+            .unwrap();
+        context.set_tcti_locality(0).unwrap();
+
+        context.set_sessions((ESYS_TR_PASSWORD, ESYS_TR_NONE, ESYS_TR_NONE));
+        assert_eq!(
+            context.nv_read(nv_handle, nv_handle, 8, 0).unwrap(),
+            HASH[..8].to_vec()
+        );
+        context
+            .nv_undefine_space(ESYS_TR_RH_OWNER, nv_handle)
+            .unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use std::convert::TryFrom;
+use tss_esapi::constants::*;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::TPML_DIGEST_VALUES;
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::DigestValues;
+
+#[test]
+fn test_set_and_get() {
+    let mut digest_values = DigestValues::new();
+    assert!(digest_values.is_empty());
+    digest_values
+        .set(HashingAlgorithm::Sha256, &[1; 32])
+        .unwrap();
+    digest_values.set(HashingAlgorithm::Sha1, &[2; 20]).unwrap();
+    digest_values
+        .set(HashingAlgorithm::Sha256, &[3; 32])
+        .unwrap();
+
+    assert_eq!(digest_values.len(), 2);
+    assert_eq!(
+        digest_values.get(HashingAlgorithm::Sha256).unwrap(),
+        &[3; 32][..]
+    );
+    assert_eq!(
+        digest_values.get(HashingAlgorithm::Sha1).unwrap(),
+        &[2; 20][..]
+    );
+    assert_eq!(digest_values.get(HashingAlgorithm::Sha384), None);
+    let algorithms: Vec<HashingAlgorithm> = digest_values
+        .iter()
+        .map(|(algorithm, _)| algorithm)
+        .collect();
+    assert_eq!(
+        algorithms,
+        vec![HashingAlgorithm::Sha256, HashingAlgorithm::Sha1]
+    );
+}
+
+#[test]
+fn test_wrong_digest_size() {
+    let mut digest_values = DigestValues::new();
+    assert_eq!(
+        digest_values
+            .set(HashingAlgorithm::Sha384, &[0; 32])
+            .unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+}
+
+#[test]
+fn test_conversion() {
+    let mut digest_values = DigestValues::new();
+    digest_values.set(HashingAlgorithm::Sha1, &[1; 20]).unwrap();
+    digest_values
+        .set(HashingAlgorithm::Sha512, &[2; 64])
+        .unwrap();
+
+    let tpml_digest_values = TPML_DIGEST_VALUES::try_from(digest_values.clone()).unwrap();
+    assert_eq!(tpml_digest_values.count, 2);
+    assert_eq!(tpml_digest_values.digests[0].hashAlg, TPM2_ALG_SHA1);
+    assert_eq!(
+        unsafe { tpml_digest_values.digests[0].digest.sha1 },
+        [1; 20]
+    );
+    assert_eq!(tpml_digest_values.digests[1].hashAlg, TPM2_ALG_SHA512);
+    assert_eq!(
+        unsafe { &tpml_digest_values.digests[1].digest.sha512[..] },
+        &[2; 64][..]
+    );
+
+    assert_eq!(
+        DigestValues::try_from(tpml_digest_values).unwrap(),
+        digest_values
+    );
+}
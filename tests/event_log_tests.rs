@@ -0,0 +1,171 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::event_log::{EventLog, EventType};
+use tss_esapi::utils::PcrSlot;
+
+// Logs with the measurements of a firmware booting `\EFI\BOOT\BOOTX64.EFI`
+const SHA1_LOG: &[u8] = include_bytes!("fixtures/event_log_sha1.bin");
+const CRYPTO_AGILE_LOG: &[u8] = include_bytes!("fixtures/event_log_crypto_agile.bin");
+// Size of the last event of the crypto-agile log
+const LAST_EVENT_SIZE: usize = 93;
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn pcr_slots() -> Vec<PcrSlot> {
+    (0..8)
+        .map(|index| PcrSlot::from_index(index).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_parse_sha1_log() {
+    let event_log = EventLog::parse(SHA1_LOG).unwrap();
+    assert!(!event_log.is_crypto_agile());
+    assert_eq!(event_log.algorithms(), &[HashingAlgorithm::Sha1]);
+
+    let events = event_log.events();
+    assert_eq!(events.len(), 12);
+    assert_eq!(events[0].pcr_index(), 0);
+    assert_eq!(events[0].event_type(), EventType::SCrtmVersion);
+    assert_eq!(events[0].data(), &[b'1', 0, b'.', 0, b'0', 0, 0, 0]);
+    assert_eq!(events[3].event_type(), EventType::Separator);
+    assert_eq!(
+        events[3].digests().get(HashingAlgorithm::Sha1).unwrap(),
+        &from_hex("9069ca78e7450a285173431b3e52c5c25299e473")[..]
+    );
+    assert_eq!(events[11].pcr_index(), 4);
+    assert_eq!(
+        events[11].event_type(),
+        EventType::EfiBootServicesApplication
+    );
+    assert_eq!(events[11].data(), b"\\EFI\\BOOT\\BOOTX64.EFI");
+}
+
+#[test]
+fn test_parse_crypto_agile_log() {
+    let event_log = EventLog::parse(CRYPTO_AGILE_LOG).unwrap();
+    assert!(event_log.is_crypto_agile());
+    assert_eq!(
+        event_log.algorithms(),
+        &[HashingAlgorithm::Sha1, HashingAlgorithm::Sha256]
+    );
+
+    let events = event_log.events();
+    assert_eq!(events.len(), 14);
+    // Header and startup locality
+    assert_eq!(events[0].event_type(), EventType::NoAction);
+    assert_eq!(events[1].event_type(), EventType::NoAction);
+    assert_eq!(events[1].data(), b"StartupLocality\0\x03");
+    assert_eq!(events[9].event_type(), EventType::Separator);
+    assert_eq!(events[9].digests().len(), 2);
+    assert_eq!(
+        events[9].digests().get(HashingAlgorithm::Sha256).unwrap(),
+        &from_hex("df3f619804a92fdb4057192dc43dd748ea778adc52bc498ce80524c014b81119")[..]
+    );
+}
+
+#[test]
+fn test_replay_sha1_log() {
+    let pcr_data = EventLog::parse(SHA1_LOG).unwrap().replay().unwrap();
+    assert_eq!(pcr_data.len(), 8);
+    let expected = [
+        "a973e3b4330d5d097802cc9caba6e9c1f079ca62",
+        "b2a83b0ebf2f8374299a5b2bdfc31ea955ad7236",
+        "b2a83b0ebf2f8374299a5b2bdfc31ea955ad7236",
+        "b2a83b0ebf2f8374299a5b2bdfc31ea955ad7236",
+        "5e79f5e3fb78dc565b87ad5bc34da7b7557e08ba",
+        "b2a83b0ebf2f8374299a5b2bdfc31ea955ad7236",
+        "b2a83b0ebf2f8374299a5b2bdfc31ea955ad7236",
+        "d5340e2e379fe394816432d4470eff1b1a01dc31",
+    ];
+    for (pcr_slot, value) in pcr_slots().into_iter().zip(expected.iter()) {
+        assert_eq!(
+            pcr_data.get(HashingAlgorithm::Sha1, pcr_slot).unwrap(),
+            &from_hex(value)[..]
+        );
+    }
+}
+
+#[test]
+fn test_replay_crypto_agile_log() {
+    let pcr_data = EventLog::parse(CRYPTO_AGILE_LOG).unwrap().replay().unwrap();
+    assert_eq!(pcr_data.len(), 16);
+    // PCR 0 starts from locality 3
+    assert_eq!(
+        pcr_data
+            .get(HashingAlgorithm::Sha1, PcrSlot::Slot0)
+            .unwrap(),
+        &from_hex("458090eb68b22045c72e1953f1ddd96a3627d902")[..]
+    );
+    let expected = [
+        "195643b344b32a3b1d54ab8f8a4890adb75206c7099977b3b3aadd30dca40e1d",
+        "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969",
+        "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969",
+        "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969",
+        "79014370090593c3bbbfa6028b97f6d500fedca4e9613e46b0eb389aee0a67b6",
+        "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969",
+        "3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969",
+        "7f7b8510c3d6b78e3e4e21839b579cd00ea16a2b89b64db2f3db4f8b7aa59013",
+    ];
+    for (pcr_slot, value) in pcr_slots().into_iter().zip(expected.iter()) {
+        assert_eq!(
+            pcr_data.get(HashingAlgorithm::Sha256, pcr_slot).unwrap(),
+            &from_hex(value)[..]
+        );
+    }
+}
+
+#[test]
+fn test_mismatches() {
+    let event_log = EventLog::parse(CRYPTO_AGILE_LOG).unwrap();
+    let pcr_data = event_log.replay().unwrap();
+    assert!(event_log.mismatches(&pcr_data).unwrap().is_empty());
+
+    // The PCRs do not contain the last event of the log
+    let truncated_log =
+        EventLog::parse(&CRYPTO_AGILE_LOG[..CRYPTO_AGILE_LOG.len() - LAST_EVENT_SIZE]).unwrap();
+    let mismatches = event_log
+        .mismatches(&truncated_log.replay().unwrap())
+        .unwrap();
+    assert_eq!(mismatches.len(), 2);
+    let mismatch = &mismatches[1];
+    assert_eq!(mismatch.hashing_algorithm, HashingAlgorithm::Sha256);
+    assert_eq!(mismatch.pcr_slot, PcrSlot::Slot4);
+    assert_eq!(
+        mismatch.expected,
+        from_hex("79014370090593c3bbbfa6028b97f6d500fedca4e9613e46b0eb389aee0a67b6")
+    );
+    assert_eq!(
+        mismatch.actual,
+        from_hex("3d458cfe55cc03ea1f443f1562beec8df51c75e14a9fcf9a7234a13f198e7969")
+    );
+    assert_eq!(mismatch.events, vec![9, 13]);
+    // Only the separator was extended
+    assert_eq!(mismatch.matching_events, Some(1));
+
+    // The log does not contain the last event extended into the PCRs
+    let mismatches = truncated_log.mismatches(&pcr_data).unwrap();
+    assert_eq!(mismatches.len(), 2);
+    assert_eq!(mismatches[0].pcr_slot, PcrSlot::Slot4);
+    assert_eq!(mismatches[0].events, vec![9]);
+    assert_eq!(mismatches[0].matching_events, None);
+}
+
+#[test]
+fn test_invalid_logs() {
+    assert_eq!(
+        EventLog::parse(&[]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    assert_eq!(
+        EventLog::parse(&CRYPTO_AGILE_LOG[..CRYPTO_AGILE_LOG.len() - 1]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
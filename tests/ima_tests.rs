@@ -0,0 +1,150 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::hash;
+use tss_esapi::utils::ima::{ImaAllowlist, ImaLog, ImaTemplate};
+use tss_esapi::utils::PcrSlot;
+
+// The same measurement list in both forms, with a violation on /var/log/messages
+const ASCII_LIST: &str = include_str!("fixtures/ima_ascii_runtime_measurements");
+const BINARY_LIST: &[u8] = include_bytes!("fixtures/ima_binary_runtime_measurements");
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_parse_ascii() {
+    let ima_log = ImaLog::parse_ascii(ASCII_LIST, HashingAlgorithm::Sha1).unwrap();
+    let entries = ima_log.entries();
+    assert_eq!(entries.len(), 6);
+
+    assert_eq!(entries[0].pcr_index(), 10);
+    assert_eq!(entries[0].template(), ImaTemplate::ImaNg);
+    assert_eq!(entries[0].file_name(), b"boot_aggregate");
+    assert_eq!(
+        entries[0].template_hash(),
+        &from_hex("01fb2f8a6d603fd73992768a0d7275c8adb81fda")[..]
+    );
+    assert_eq!(entries[1].file_digest_algorithm(), HashingAlgorithm::Sha256);
+    assert_eq!(
+        entries[1].file_digest(),
+        &hash::hash(HashingAlgorithm::Sha256, &[b"bash"]).unwrap()[..]
+    );
+    assert!(!entries[1].is_violation());
+    assert!(entries[2].is_violation());
+    assert_eq!(entries[3].template(), ImaTemplate::ImaSig);
+    assert_eq!(entries[3].file_name(), b"/usr/lib/libc.so.6");
+    assert_eq!(
+        entries[3].signature(),
+        &from_hex("030204a1b2c3d4000401020304")[..]
+    );
+    assert_eq!(entries[4].file_name(), b"/usr/bin/my tool");
+    assert!(entries[4].signature().is_empty());
+    assert_eq!(entries[5].template(), ImaTemplate::Ima);
+    assert_eq!(entries[5].file_digest_algorithm(), HashingAlgorithm::Sha1);
+    assert_eq!(entries[5].file_name(), b"/init");
+
+    assert!(ima_log.invalid_template_hashes().unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_binary() {
+    let ima_log = ImaLog::parse_binary(BINARY_LIST, HashingAlgorithm::Sha1).unwrap();
+    assert_eq!(
+        ima_log,
+        ImaLog::parse_ascii(ASCII_LIST, HashingAlgorithm::Sha1).unwrap()
+    );
+    assert!(ima_log.invalid_template_hashes().unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_template_hash() {
+    let tampered = ASCII_LIST.replace("/usr/bin/bash", "/usr/bin/sh");
+    let ima_log = ImaLog::parse_ascii(&tampered, HashingAlgorithm::Sha1).unwrap();
+    assert_eq!(ima_log.invalid_template_hashes().unwrap(), vec![1]);
+}
+
+#[test]
+fn test_replay() {
+    let ima_log = ImaLog::parse_binary(BINARY_LIST, HashingAlgorithm::Sha1).unwrap();
+
+    let pcr_data = ima_log.replay(HashingAlgorithm::Sha1).unwrap();
+    assert_eq!(pcr_data.len(), 1);
+    assert_eq!(
+        pcr_data
+            .get(HashingAlgorithm::Sha1, PcrSlot::Slot10)
+            .unwrap(),
+        &from_hex("19111751c66be67fd7392b2f1122e68703d37ac7")[..]
+    );
+
+    let pcr_data = ima_log.replay(HashingAlgorithm::Sha256).unwrap();
+    assert_eq!(
+        pcr_data
+            .get(HashingAlgorithm::Sha256, PcrSlot::Slot10)
+            .unwrap(),
+        &from_hex("9b0dd358992a0def52e4ff99a1fe3a3085451e896ec92e35f84c0236af74106a")[..]
+    );
+}
+
+#[test]
+fn test_allowlist() {
+    let ima_log = ImaLog::parse_ascii(ASCII_LIST, HashingAlgorithm::Sha1).unwrap();
+    let mut allowlist = ImaAllowlist::new();
+    for (name, data) in [
+        ("boot_aggregate", &b"boot"[..]),
+        ("/usr/bin/bash", &b"bash"[..]),
+        ("/usr/lib/libc.so.6", &b"libc"[..]),
+    ]
+    .iter()
+    {
+        allowlist.allow(
+            name,
+            HashingAlgorithm::Sha256,
+            &hash::hash(HashingAlgorithm::Sha256, &[data]).unwrap(),
+        );
+    }
+    // Another version of the tool is allowed, but not this one
+    allowlist.allow(
+        "/usr/bin/my tool",
+        HashingAlgorithm::Sha256,
+        &hash::hash(HashingAlgorithm::Sha256, &[b"tool v2"]).unwrap(),
+    );
+    // Digests computed with another algorithm do not match
+    allowlist.allow(
+        "/init",
+        HashingAlgorithm::Sha256,
+        &hash::hash(HashingAlgorithm::Sha256, &[b"init"]).unwrap(),
+    );
+
+    assert_eq!(ima_log.disallowed_entries(&allowlist), vec![2, 4, 5]);
+}
+
+#[test]
+fn test_invalid_lists() {
+    assert_eq!(
+        ImaLog::parse_binary(
+            &BINARY_LIST[..BINARY_LIST.len() - 1],
+            HashingAlgorithm::Sha1
+        )
+        .unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    // The template hashes are SHA1 digests
+    assert_eq!(
+        ImaLog::parse_ascii(ASCII_LIST, HashingAlgorithm::Sha256).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+    assert_eq!(
+        ImaLog::parse_ascii(
+            "10 01fb2f8a6d603fd73992768a0d7275c8adb81fda ima-buf sha256:00 kexec-cmdline",
+            HashingAlgorithm::Sha1
+        )
+        .unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+    );
+}
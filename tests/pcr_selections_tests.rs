@@ -164,4 +164,169 @@ mod test_pcr_selection_builder {
         // The try_from should then fail.
         PcrSelections::try_from(tpml_pcr_selection).unwrap_err();
     }
+
+    #[test]
+    fn test_high_pcr_slots() {
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot31])
+            .build();
+
+        let actual: TPML_PCR_SELECTION = pcr_selections.clone().into();
+        assert_eq!(actual.count, 1);
+        // The default size is too small to hold PCR 31.
+        assert_eq!(actual.pcrSelections[0].sizeofSelect, 4);
+        assert_eq!(actual.pcrSelections[0].pcrSelect, [0x01, 0x00, 0x00, 0x80]);
+        assert_eq!(PcrSelections::try_from(actual).unwrap(), pcr_selections);
+    }
+
+    #[test]
+    fn test_size_of_select_per_bank() {
+        let pcr_selections = PcrSelectionsBuilder::new()
+            .with_size_of_select(PcrSelectSize::FourBytes)
+            .with_selection(HashingAlgorithm::Sha1, &[PcrSlot::Slot7])
+            .with_bank_size_of_select(HashingAlgorithm::Sha256, PcrSelectSize::OneByte)
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot1])
+            .with_bank_size_of_select(HashingAlgorithm::Sha384, PcrSelectSize::OneByte)
+            .with_selection(HashingAlgorithm::Sha384, &[PcrSlot::Slot16])
+            .build();
+
+        let actual: TPML_PCR_SELECTION = pcr_selections.clone().into();
+        assert_eq!(actual.count, 3);
+        // The banks are kept in the order in which they were selected.
+        let expected = [
+            (HashingAlgorithm::Sha1, 4, [0x80, 0x00, 0x00, 0x00]),
+            (HashingAlgorithm::Sha256, 1, [0x02, 0x00, 0x00, 0x00]),
+            // Increased to hold PCR 16
+            (HashingAlgorithm::Sha384, 3, [0x00, 0x00, 0x01, 0x00]),
+        ];
+        for (index, (hash, size_of_select, pcr_select)) in expected.iter().enumerate() {
+            let pcr_selection = &actual.pcrSelections[index];
+            assert_eq!(pcr_selection.hash, Into::<TPM2_ALG_ID>::into(*hash));
+            assert_eq!(pcr_selection.sizeofSelect, *size_of_select);
+            assert_eq!(pcr_selection.pcrSelect, *pcr_select);
+        }
+
+        // Mixed sizes can be converted back.
+        assert_eq!(PcrSelections::try_from(actual).unwrap(), pcr_selections);
+    }
+
+    #[test]
+    fn test_conversion_of_data_with_bits_beyond_size_of_select() {
+        let mut tpml_pcr_selection: TPML_PCR_SELECTION = PcrSelectionsBuilder::new()
+            .with_bank_size_of_select(HashingAlgorithm::Sha256, PcrSelectSize::OneByte)
+            .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0])
+            .build()
+            .into();
+
+        tpml_pcr_selection.pcrSelections[0].pcrSelect[1] = 1;
+
+        PcrSelections::try_from(tpml_pcr_selection).unwrap_err();
+    }
+}
+
+mod test_pcr_selections_operations {
+    use super::*;
+
+    fn selections() -> PcrSelections {
+        PcrSelectionsBuilder::new()
+            .with_selection(
+                HashingAlgorithm::Sha256,
+                &[PcrSlot::Slot0, PcrSlot::Slot1, PcrSlot::Slot2],
+            )
+            .with_selection(HashingAlgorithm::Sha1, &[PcrSlot::Slot7])
+            .build()
+    }
+
+    #[test]
+    fn test_iter() {
+        let banks: Vec<(HashingAlgorithm, PcrSelectSize, Vec<PcrSlot>)> =
+            selections().iter().collect();
+        assert_eq!(
+            banks,
+            vec![
+                (
+                    HashingAlgorithm::Sha256,
+                    PcrSelectSize::ThreeBytes,
+                    vec![PcrSlot::Slot0, PcrSlot::Slot1, PcrSlot::Slot2]
+                ),
+                (
+                    HashingAlgorithm::Sha1,
+                    PcrSelectSize::ThreeBytes,
+                    vec![PcrSlot::Slot7]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subtract() {
+        let mut pcr_selections = selections();
+        pcr_selections.subtract(
+            &PcrSelectionsBuilder::new()
+                .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot1])
+                .with_selection(HashingAlgorithm::Sha1, &[PcrSlot::Slot7])
+                .with_selection(HashingAlgorithm::Sha384, &[PcrSlot::Slot0])
+                .build(),
+        );
+        assert_eq!(
+            pcr_selections,
+            PcrSelectionsBuilder::new()
+                .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot2])
+                .build()
+        );
+
+        pcr_selections.subtract(&selections());
+        assert!(pcr_selections.is_empty());
+        assert_eq!(pcr_selections.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_union() {
+        let mut pcr_selections = selections();
+        pcr_selections.union(
+            &PcrSelectionsBuilder::new()
+                .with_selection(HashingAlgorithm::Sha1, &[PcrSlot::Slot24])
+                .with_selection(HashingAlgorithm::Sha384, &[PcrSlot::Slot0])
+                .build(),
+        );
+        assert_eq!(
+            pcr_selections,
+            PcrSelectionsBuilder::new()
+                .with_selection(
+                    HashingAlgorithm::Sha256,
+                    &[PcrSlot::Slot0, PcrSlot::Slot1, PcrSlot::Slot2],
+                )
+                .with_bank_size_of_select(HashingAlgorithm::Sha1, PcrSelectSize::FourBytes)
+                .with_selection(HashingAlgorithm::Sha1, &[PcrSlot::Slot7, PcrSlot::Slot24])
+                .with_selection(HashingAlgorithm::Sha384, &[PcrSlot::Slot0])
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut pcr_selections = selections();
+        pcr_selections.intersect(
+            &PcrSelectionsBuilder::new()
+                .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot2, PcrSlot::Slot3])
+                .with_selection(HashingAlgorithm::Sha384, &[PcrSlot::Slot7])
+                .build(),
+        );
+        assert_eq!(
+            pcr_selections,
+            PcrSelectionsBuilder::new()
+                .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot2])
+                .build()
+        );
+    }
+
+    #[test]
+    fn test_is_empty() {
+        assert!(PcrSelections::default().is_empty());
+        assert!(PcrSelectionsBuilder::new()
+            .with_selection(HashingAlgorithm::Sha256, &[])
+            .build()
+            .is_empty());
+        assert!(!selections().is_empty());
+    }
 }
@@ -0,0 +1,317 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use std::convert::TryFrom;
+use tss_esapi::constants::*;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::{TPM2_CC, TPM2_EO};
+use tss_esapi::utils::algorithm_specifiers::HashingAlgorithm;
+use tss_esapi::utils::command_code::CommandCode;
+use tss_esapi::utils::policy::{self, ComparisonOperation, PolicyCalculator};
+use tss_esapi::utils::{LocalityAttributes, PcrSelectionsBuilder, PcrSlot};
+
+// Name of an NV index used in the tests below
+fn nv_index_name() -> Vec<u8> {
+    let mut name = vec![0x00, 0x0b];
+    name.extend(0..32);
+    name
+}
+
+fn new_parent_name() -> Vec<u8> {
+    let mut name = vec![0x00, 0x0b];
+    name.extend(32..64);
+    name
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+const POLICY_AUTH_VALUE_SHA256: &str =
+    "8fcd2169ab92694e0c633f1ab772842b8241bbc20288981fc7ac1eddc1fddb0e";
+// PolicyPCR over slots 0 and 1 of the SHA256 bank, both set to zero
+const POLICY_PCR_SHA256: &str = "182c84e9792152b63f7716ef2c303b0e34442f51e72883f944b18d3075b45719";
+
+#[test]
+fn test_empty_policy() {
+    let calculator = PolicyCalculator::new(HashingAlgorithm::Sha384).unwrap();
+    assert_eq!(calculator.hashing_algorithm(), HashingAlgorithm::Sha384);
+    assert_eq!(calculator.digest(), &[0; 48][..]);
+}
+
+#[test]
+fn test_unsupported_algorithm() {
+    assert_eq!(
+        PolicyCalculator::new(HashingAlgorithm::Sm3_256).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+    );
+}
+
+#[test]
+fn test_policy_auth_value() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_auth_value().unwrap();
+    assert_eq!(calculator.digest(), &from_hex(POLICY_AUTH_VALUE_SHA256)[..]);
+
+    calculator.restart();
+    calculator.policy_password().unwrap();
+    assert_eq!(calculator.digest(), &from_hex(POLICY_AUTH_VALUE_SHA256)[..]);
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha1).unwrap();
+    calculator.policy_auth_value().unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("af6038c78c5c962d37127e319124e3a8dc582e9b")[..]
+    );
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha384).unwrap();
+    calculator.policy_auth_value().unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("0eb13321e885c9603d394e1c33976d4660517111f440d377585f66a94a0eee0a7f73d10b68edc48f61bd3c8385dcddf5")[..]
+    );
+}
+
+#[test]
+fn test_policy_command_code() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_auth_value().unwrap();
+    calculator.policy_command_code(CommandCode::Sign).unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("d9979a6b278c1d135ce124837caf9de446d714718eee9e3620b58c80a043a953")[..]
+    );
+}
+
+#[test]
+fn test_policy_pcr() {
+    let pcr_selections = PcrSelectionsBuilder::new()
+        .with_selection(HashingAlgorithm::Sha256, &[PcrSlot::Slot0, PcrSlot::Slot1])
+        .build();
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_pcr_values(pcr_selections.clone(), &[&[0; 32], &[0; 32]])
+        .unwrap();
+    assert_eq!(calculator.digest(), &from_hex(POLICY_PCR_SHA256)[..]);
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    assert_eq!(
+        calculator.policy_pcr(pcr_selections, &[0; 20]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+}
+
+#[test]
+fn test_policy_secret() {
+    // Authorization policy of the default EK templates
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_secret(&TPM2_RH_ENDORSEMENT.to_be_bytes(), &[])
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("837197674484b3f81a90cc8d46a5d724fd52d76e06520b64f2a1da1b331469aa")[..]
+    );
+}
+
+#[test]
+fn test_policy_or() {
+    let auth_value = from_hex(POLICY_AUTH_VALUE_SHA256);
+    let pcr = from_hex(POLICY_PCR_SHA256);
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_auth_value().unwrap();
+    calculator.policy_or(&[&auth_value, &pcr]).unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("8965ada38526aa75d47072b4de2f9548434b34055835e53d31562392f8aecb18")[..]
+    );
+
+    assert_eq!(
+        calculator.policy_or(&[&auth_value]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+    assert_eq!(
+        calculator.policy_or(&[&auth_value, &[0; 20]]).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::WrongParamSize)
+    );
+}
+
+#[test]
+fn test_comparison_operation_conversion() {
+    assert_eq!(TPM2_EO::from(ComparisonOperation::Equal), TPM2_EO_EQ);
+    assert_eq!(
+        TPM2_EO::from(ComparisonOperation::UnsignedGreaterOrEqual),
+        TPM2_EO_UNSIGNED_GE
+    );
+    assert_eq!(
+        ComparisonOperation::try_from(TPM2_EO_BITCLEAR).unwrap(),
+        ComparisonOperation::BitClear
+    );
+    assert_eq!(
+        ComparisonOperation::try_from(0x000C).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
+
+#[test]
+fn test_policy_nv() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_nv(
+            &nv_index_name(),
+            &5_u64.to_be_bytes(),
+            0,
+            ComparisonOperation::UnsignedGreaterOrEqual,
+        )
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("51eb53de309567dc89861cfa2bb270a4c8a4197b79c1649e75739ee28c3d1215")[..]
+    );
+}
+
+#[test]
+fn test_policy_counter_timer() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_counter_timer(
+            &0_u32.to_be_bytes(),
+            20,
+            ComparisonOperation::UnsignedGreaterOrEqual,
+        )
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("c334637e272b7e5b1def233fddb6d22dbd6cd24818e8bb883f0d7a1f4b153daa")[..]
+    );
+}
+
+#[test]
+fn test_policy_nv_written() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_nv_written(true).unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("f7887d158ae8d38be0ac5319f37a9e07618bf54885453c7a54ddb0c6a6193beb")[..]
+    );
+}
+
+#[test]
+fn test_policy_authorize_nv() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_auth_value().unwrap();
+    calculator.policy_authorize_nv(&nv_index_name()).unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("a362cb3d316cbb90380836a136f4cdd1a3020e99b6abd74353638bd1e4c9f185")[..]
+    );
+}
+
+#[test]
+fn test_command_code_conversion() {
+    assert_eq!(TPM2_CC::from(CommandCode::Sign), TPM2_CC_Sign);
+    assert_eq!(
+        TPM2_CC::from(CommandCode::PcrSetAuthPolicy),
+        TPM2_CC_PCR_SetAuthPolicy
+    );
+    assert_eq!(
+        CommandCode::try_from(TPM2_CC_PolicyAuthorizeNV).unwrap(),
+        CommandCode::PolicyAuthorizeNv
+    );
+    assert_eq!(
+        CommandCode::try_from(0x0000_015a).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
+
+#[test]
+fn test_cp_hash_and_name_hash() {
+    let cp_hash = policy::cp_hash(
+        HashingAlgorithm::Sha256,
+        CommandCode::Unseal,
+        &[&nv_index_name()],
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        cp_hash,
+        from_hex("73e12c0d2439ec027056b895000025e0e9248552d76df6f7f0aa28886e0a970c")
+    );
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator.policy_cp_hash(&cp_hash).unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("c49c37b785a32e5dd9018f6126dce5252bf1a5eab563dde19343c40e44b6a6ce")[..]
+    );
+
+    let name_hash = policy::name_hash(
+        HashingAlgorithm::Sha256,
+        &[&nv_index_name(), &new_parent_name()],
+    )
+    .unwrap();
+    assert_eq!(
+        name_hash,
+        from_hex("03bbfa16f20db82b4bda3a24d0537317af1c46688c707591c6ce90c7d9b435a8")
+    );
+}
+
+#[test]
+fn test_policy_duplication_select() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_duplication_select(&nv_index_name(), &new_parent_name(), true)
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("7168fb8205f26b160e827a8417538877e7f2834340c0e425adf7e9997473ad99")[..]
+    );
+
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_duplication_select(&nv_index_name(), &new_parent_name(), false)
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("5611cf4ab6899fc8f144cd134f28052787e963ea3927ba9e22a5ec5f4d1821df")[..]
+    );
+}
+
+#[test]
+fn test_locality_attributes() {
+    let locality = LocalityAttributes::from_locality(3).unwrap();
+    assert_eq!(locality.0, 0x08);
+    assert!(locality.locality_three());
+    assert_eq!(locality.extended_locality(), None);
+
+    let locality = LocalityAttributes::from_locality(40).unwrap();
+    assert_eq!(locality.0, 40);
+    assert_eq!(locality.extended_locality(), Some(40));
+
+    assert_eq!(
+        LocalityAttributes::from_locality(10).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::InvalidParam)
+    );
+}
+
+#[test]
+fn test_policy_locality_and_physical_presence() {
+    let mut calculator = PolicyCalculator::new(HashingAlgorithm::Sha256).unwrap();
+    calculator
+        .policy_locality(LocalityAttributes::from_locality(3).unwrap())
+        .unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("7764491d5afe719035c0c09faa90c3490a7475d6df422b804e8f68aa65f8934f")[..]
+    );
+
+    calculator.restart();
+    calculator.policy_physical_presence().unwrap();
+    assert_eq!(
+        calculator.digest(),
+        &from_hex("0d7c6747b1b9facbba03492097aa9d5af792e5efc07346e05f9daa8b3d9e13b5")[..]
+    );
+}
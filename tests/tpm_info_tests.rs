@@ -0,0 +1,69 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use tss_esapi::abstraction::tpm_info::TpmInfo;
+use tss_esapi::constants::*;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::TPM2_PT;
+
+// Fixed properties reported by the IBM software TPM
+fn properties() -> Vec<(TPM2_PT, u32)> {
+    vec![
+        (TPM2_PT_FAMILY_INDICATOR, 0x322e_3000),
+        (TPM2_PT_LEVEL, 0),
+        (TPM2_PT_REVISION, 159),
+        (TPM2_PT_DAY_OF_YEAR, 0),
+        (TPM2_PT_YEAR, 2019),
+        (TPM2_PT_MANUFACTURER, 0x4942_4d00),
+        (TPM2_PT_VENDOR_STRING_1, 0x5357_2020),
+        (TPM2_PT_VENDOR_STRING_2, 0x2054_504d),
+        (TPM2_PT_VENDOR_STRING_3, 0),
+        (TPM2_PT_VENDOR_STRING_4, 0),
+        (TPM2_PT_FIRMWARE_VERSION_1, 0x2019_1023),
+        (TPM2_PT_FIRMWARE_VERSION_2, 0x0016_3636),
+        (TPM2_PT_PCR_COUNT, 24),
+        (TPM2_PT_MAX_DIGEST, 64),
+        (TPM2_PT_MAX_OBJECT_CONTEXT, 1984),
+        (TPM2_PT_MAX_SESSION_CONTEXT, 240),
+        (TPM2_PT_NV_BUFFER_MAX, 1024),
+    ]
+}
+
+#[test]
+fn tpm_info_from_properties() {
+    let info = TpmInfo::from_properties(&properties()).unwrap();
+    assert_eq!(info.manufacturer_id, "IBM");
+    assert_eq!(info.manufacturer_name, Some("IBM"));
+    assert_eq!(info.vendor_string, "SW   TPM");
+    assert_eq!(info.firmware_version, 0x2019_1023_0016_3636);
+    assert_eq!(info.spec_family, "2.0");
+    assert_eq!(info.spec_level, 0);
+    assert_eq!(info.spec_revision, 159);
+    assert_eq!(info.max_nv_buffer_size, 1024);
+    assert_eq!(info.max_digest_size, 64);
+    assert_eq!(info.max_object_context_size, 1984);
+    assert_eq!(info.max_session_context_size, 240);
+    assert_eq!(info.pcr_count, 24);
+}
+
+#[test]
+fn tpm_info_unknown_manufacturer() {
+    let mut properties = properties();
+    properties.retain(|(property, _)| {
+        *property != TPM2_PT_MANUFACTURER && *property != TPM2_PT_VENDOR_STRING_2
+    });
+    properties.push((TPM2_PT_MANUFACTURER, 0x5858_5800));
+    let info = TpmInfo::from_properties(&properties).unwrap();
+    assert_eq!(info.manufacturer_id, "XXX");
+    assert_eq!(info.manufacturer_name, None);
+    assert_eq!(info.vendor_string, "SW");
+}
+
+#[test]
+fn tpm_info_missing_property() {
+    let mut properties = properties();
+    properties.retain(|(property, _)| *property != TPM2_PT_PCR_COUNT);
+    assert_eq!(
+        TpmInfo::from_properties(&properties).unwrap_err(),
+        Error::WrapperError(WrapperErrorKind::ParamsMissing)
+    );
+}
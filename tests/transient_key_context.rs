@@ -6,7 +6,9 @@ use tss_esapi::response_code::{
 use tss_esapi::utils::algorithm_specifiers::{EllipticCurve, HashingAlgorithm};
 use tss_esapi::utils::{AsymSchemeUnion, PublicKey, Signature, SignatureData};
 use tss_esapi::{
-    abstraction::transient::{KeyParams, TransientKeyContextBuilder},
+    abstraction::transient::{
+        AlgorithmPreferences, KeyParams, RootKeyAlgorithm, TransientKeyContextBuilder,
+    },
     Tcti, TransientKeyContext,
 };
 
@@ -363,3 +365,93 @@ fn full_ecc_test() {
         let _ = ctx.verify_signature(key, &HASH, signature).unwrap();
     }
 }
+
+#[test]
+fn ecc_root_key() {
+    let mut ctx = unsafe {
+        TransientKeyContextBuilder::new()
+            .with_tcti(Tcti::Mssim)
+            .with_root_key_algorithm(RootKeyAlgorithm::Ecc(EllipticCurve::NistP256))
+            .build()
+            .unwrap()
+    };
+    assert!(ctx.negotiated_algorithms().is_none());
+    let (key, auth) = ctx
+        .create_signing_key(
+            KeyParams::Ecc {
+                curve: EllipticCurve::NistP256,
+                scheme: AsymSchemeUnion::ECDSA(HashingAlgorithm::Sha256),
+            },
+            16,
+        )
+        .unwrap();
+    let signature = ctx.sign(key.clone(), &auth, &HASH).unwrap();
+    let _ = ctx.verify_signature(key, &HASH, signature).unwrap();
+}
+
+#[test]
+fn negotiate_algorithms() {
+    let preferences = AlgorithmPreferences::default();
+    let mut ctx = unsafe {
+        TransientKeyContextBuilder::new()
+            .with_tcti(Tcti::Mssim)
+            .with_algorithm_negotiation(preferences.clone())
+            .build()
+            .unwrap()
+    };
+    let algorithms = ctx.negotiated_algorithms().unwrap();
+    assert!(preferences
+        .session_hash_algorithms
+        .contains(&algorithms.session_hash_algorithm));
+    assert!(preferences
+        .root_key_algorithms
+        .contains(&algorithms.root_key_algorithm));
+    let (key, auth) = ctx
+        .create_signing_key(
+            KeyParams::Rsa {
+                size: 2048,
+                scheme: AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+                pub_exponent: 0,
+            },
+            16,
+        )
+        .unwrap();
+    let signature = ctx.sign(key.clone(), &auth, &HASH).unwrap();
+    let _ = ctx.verify_signature(key, &HASH, signature).unwrap();
+}
+
+#[test]
+fn negotiate_unsupported_algorithms() {
+    let preferences = AlgorithmPreferences {
+        session_hash_algorithms: vec![],
+        ..Default::default()
+    };
+    assert_eq!(
+        unsafe {
+            TransientKeyContextBuilder::new()
+                .with_tcti(Tcti::Mssim)
+                .with_algorithm_negotiation(preferences)
+                .build()
+                .unwrap_err()
+        },
+        Error::WrapperError(ErrorKind::UnsupportedParam)
+    );
+}
+
+#[test]
+fn negotiate_wrong_key_size() {
+    let preferences = AlgorithmPreferences {
+        root_key_algorithms: vec![RootKeyAlgorithm::Rsa(2047)],
+        ..Default::default()
+    };
+    assert_eq!(
+        unsafe {
+            TransientKeyContextBuilder::new()
+                .with_tcti(Tcti::Mssim)
+                .with_algorithm_negotiation(preferences)
+                .build()
+                .unwrap_err()
+        },
+        Error::WrapperError(ErrorKind::WrongParamSize)
+    );
+}
@@ -0,0 +1,684 @@
+// Copyright 2020 Contributors to the Parsec project.
+// SPDX-License-Identifier: Apache-2.0
+use std::convert::TryInto;
+use tss_esapi::response_code::{Error, WrapperErrorKind};
+use tss_esapi::tss2_esys::{
+    TPM2B_ECC_PARAMETER, TPM2B_PUBLIC, TPM2B_PUBLIC_KEY_RSA, TPMS_ECC_POINT,
+};
+use tss_esapi::utils::algorithm_specifiers::{EllipticCurve, HashingAlgorithm};
+use tss_esapi::utils::tags::StructureTag;
+use tss_esapi::utils::verify::{
+    verify_quote, verify_quote_with_public_key, verify_signature, verify_signature_with_public_key,
+    QuoteVerificationFailure,
+};
+use tss_esapi::utils::{
+    create_unrestricted_signing_ecc_public, create_unrestricted_signing_rsa_public,
+    AsymSchemeUnion, PcrData, PcrSlot, PublicKey, Signature, SignatureData,
+};
+
+// Test vectors generated with OpenSSL
+const MESSAGE: &[u8] = b"Message to sign";
+
+const RSA_MODULUS: &str = concat!(
+    "c1e9e682eda3accd9282cc0f3beef51652aacca70c6a349b0d4ebfb4640715ea2bddccba1d8b91296ec24cd4a96ce4d2",
+    "14453e6e2525c26e848f90f8fe91ecbc679f369b5599d7341730087d4a20fd52cbaf3cbdefa737f0ba5e7b3470b815f1",
+    "178304634149a9bad9e2edcc2e5e933c19bae653aca5bdb7b3cfd13057bc4b762cea5796b58488824dfbdfe9d421b3dc",
+    "eb1ac59e0b309cf6b61578477b42bd1b1c02332744e929e5344da5c930efa7d5b0bec1b9dde91b412787195f79cb89a6",
+    "6c47828d4129808c135af67d43098b587482c88263124e7423e42813fbba3a15b512ec0bdc3235cafae29bb61f99b143",
+    "56c49878db87076ad8da3b39d92ab59d",
+);
+const RSASSA_SHA256_SIGNATURE: &str = concat!(
+    "71ee34dc7182af29b71c69ceca9e443cf94ae4f5526487bf54ae8884287ece6b7f90a9d0076e09771a27ca871cdb06ec",
+    "679f5df037835b56b63d929d1df2838ce6b2ebd89d0827d6fa7e4391f9b74682c19ef52b3cffa69575508739aa4bec25",
+    "94fc5b628148c1e395536fdbb8a031698b50d04cef5ab4bf07d6248de24372aab33ba836f9677476468c7c8151ebf186",
+    "849971c8b21f4092320f026b5a40e94d2ea06ee3f686832fbd5392121fd9c91dbbe40e78d6fd7b4142f262f00004b1a9",
+    "956355c472959e10543f6f9e7d9620438f1cf179034e27f3f01914ba5255a1c502311b1ef728c6694a112769f41b74b5",
+    "6f47e37d77ce48a4fa4f52a0a13d3725",
+);
+const RSAPSS_SHA256_SIGNATURE: &str = concat!(
+    "a562c0b0d453c56262e70542fcfd73c2ab57c572800a3393bdb4baf389cee402806237a1013108a86793f64e07b71e2f",
+    "96d3e87bd98dade7b44a5e6fe2d650db697dff6b1f140af74fc3387fd1618cb605916bfcec7e1fbdf3e55b133bb37a62",
+    "c8edf225f66449894f0eaae9d62af3d4b95d97c6a9953fcbbbbf758d04fa19094d894b68bbe0fb0fa68788e7b0e81b6c",
+    "07a17616222a5091ede5791a8b24e8b76adb4742f0190f4131a32aa3b0bf132d95d1fe92e511985bec84be8237f23375",
+    "3340ae0821e5cd05db557ae4c58558b4279ca7945de971b9272e4650e2ab309e37b0121a04aa0cd5022ddf45dca93518",
+    "840e8bde74cc175de609a14a062dc4e9",
+);
+// RSA key with a public exponent of 3
+const RSA_EXPONENT_3_MODULUS: &str = concat!(
+    "bcf2ccd73f0e2cba571c90cb2ff19da1fff1bcd1a7d67b8138631b42a0785dda2674d6fc051a2bd157e84460387a3fcc",
+    "99dcba5b1ebe8ec54a0962aa55748dc0fd854d4e50af0606a25ae757995979a1aefdd984045fb3527937d7e14071109c",
+    "49858d0b7d25b106a6bb0a55b136c7174c76b6ed7e7b8756eb6efa9296d15d69a83f17d5b9318a149befe6b4d3f5c7ae",
+    "4b2c0d3c8602ccaa8041fae38b5640fad1ec344d96a781a1a0ab2c2ccd2946f2ae2b701ac64da8c2de5839880858f7ac",
+    "5f76197bda997bfdf4a325efe6e76d32c141fa63b86e4835993b958f177c236bbb692a29b6011775ad31b76a72cd4243",
+    "4e25f3a3295306471b3bd2e6fed33777",
+);
+const RSA_EXPONENT_3_RSASSA_SHA256_SIGNATURE: &str = concat!(
+    "b2ab6d716067a0bd9e2bf9b1f4b073ba89bb1f1148315448161b8196be9e4bcd5956cc2c311a59044f175672221aaa71",
+    "2389f5f6fec301960a1c51acf03f455f2fd3e03e506b14ad057071182481c78ae8d0fb626d0c0d09ec078820696e20e9",
+    "85c41169d49ebb1d031509f93ddad56cb5f79633ad6f54efc2987f23e2506fb9156e841e217020c111e983a4a5f74a4c",
+    "6038729126be59ed732fd8a5aa408cd42785893784df98f2645735e47a46ba2ba1ebd7a0e5e35cee4e41c532d97704cb",
+    "ed25b476293d2a300f79b118ae33559346e03c6c6e91b21ac4a101acd32c8591458811b83063ea8243cca65ab24bb2a8",
+    "63bafda26cec7eba66198402ff43b63a",
+);
+// RSA key with a 2049-bit modulus, whose PSS encoded message is as long as the modulus
+const RSA_2049_MODULUS: &str = concat!(
+    "01c2a18f1c9762ae2ad7b8d3c8149e5e274f5a51e7c85fe8685630e5613878053c9f45f5ab290876fa08737405c440e6",
+    "c063073069547e9ec68a6fc1f5ee7431d8d78420a5f13fe11bbb656303be85d9c40f6eaf89122f1b24e1922c1da4062f",
+    "ca4fc81800993c15a16c6501fe68741093df9989557f421d34d66951312e0a27de3aa5550ddab5d2d14844d772d513b4",
+    "f8c3419abd06a0276577d30b9535869d1da639f7cadb4862942a29190117c336e2b5192cc91b32149f81f7ad3e1db171",
+    "ae595b2f93c9bb90143ea484bbb669ccfea1e44827065a75b191c5d31055087874eea8b4f23634426dc18dc940b79782",
+    "07636d6f0e796e2c4a5f462a643a8a9ef5",
+);
+const RSA_2049_RSAPSS_SHA256_SIGNATURE: &str = concat!(
+    "0096b7a1fb7ebf2f0f75b55b6f94e5169a703834cd33a04990e5115ff10abb80ac49e681ba2e56b8042b602d3f8d4608",
+    "7680e47203b3c862121680b1b35b51b835200a4033f5dfe06ac3c80ae3b0856270f01b578338fc0b10286b2e1ef3607e",
+    "1f7527dbcf4f6298ba19c7b2412849128e2c5dda43559ce98ec510b1e4e037817b44b7337dc9d6bfcc294a1d43e633b5",
+    "612e4b32c034c70d0c97bdc0867e72ac1562e5c44c2dc7e08a2fbefecd582d9feda9fab5a131723d993bfa0830edd09e",
+    "4042fe4e9dd1e63f5d76e9fd960601a14314fe33f1379a96132be0f097e219c403bb5ff75986af0934ec85dcb1d9bffd",
+    "e4fbf915cd3e0311aa4155fc8c8d655a7c",
+);
+// RSA key with a PSS signature using the longest salt
+const RSA_MAX_SALT_MODULUS: &str = concat!(
+    "9eeb90d275d9ed3f30bac6f348ff432ffe1d476aebfb0f04ccadcc004bc15c6c7a1f1ea9356f4353dbe8b6b65a975d20",
+    "cd2f22934b3747b75af2587eaeb35fbc64879cb8eaa980c8f31023842b233552254b55810157f5ebd2119b5e7ebdbea0",
+    "fcc60af1b66addf579ce76321ba321206d14d55595891ebcf60d2f9369d0044a7c5885651f39f9318087088cea9398d8",
+    "9b70c511cb9175f4510b87cabb2a9d85134f4a2ba68210a96b7c2a7f44521e788213aa014494bb6c326ff7bec66d8bce",
+    "d3315937edee00f042b633e7edd184db9a7351ca1b86c892947094a179fa25ea1061558509ae38a7df94dbe2c1b5820f",
+    "9515a3716f02306fae570e6c091a6ea5",
+);
+const RSA_MAX_SALT_RSAPSS_SHA256_SIGNATURE: &str = concat!(
+    "676043c7c6631585d1de750ca08df141b796b4e6113851c2d0fdca381ee8bdb9a2e4ad4397f82c0e392f169b496f3247",
+    "86db67f64b2ccfc27454a391c232b172dd7dc1b9ddeb2eb8d794767372f888e09ac33e274ea22ba9f145171ae2d47b88",
+    "014fc90a6c3f004308cd97b761073d32720cfe447a68628267e33778a08c95d205a666967fca0f86565c8aac0936b2bc",
+    "46881541b59a3f6ae0471df3f8287a5f1483ce4dd7f3773d704a60c225c3555fa4b118c0a083cd37a202ebad47073159",
+    "344203e69b4cc36f14fb1746f44703c62abd4986783c020c697e58ec743691069853f2d489c9fee48a2dd01e0ffd7dc5",
+    "6ab80b33feda11a007608747a1100f2b",
+);
+const P256_X: &str = "3f958bb61e3263ad37f896474c63d2c05e16ef38f929224379e678ba40986401";
+const P256_Y: &str = "535156c88417543d33ca2648a5ba820432fedfcb3ae78bf52c453c07cd222094";
+const P256_R: &str = "64b066267b0068f973062720e51abc8d1d01e62cdb345772e9b455e2f6a8d946";
+const P256_S: &str = "66343a4d57836b1d8f759e0d18cc33a77e4dd7f71980478b02e92b588c0e6c86";
+const P384_X: &str = "258104e6a4ba24d615844aa348785636ad0d0e2ff5347b1710abfeb2ed6ab126c2afb0b6d190e728baeea990388d205f";
+const P384_Y: &str = "3104bbb3ca07c05295615a040a108189f0e574088b32ee8863ec60e63ba76a465b40ef2a53e70e74681827e0e7b377ea";
+const P384_R: &str = "e09fa77c1793ab18bfe657e4ab58abd99377a52434c62d78d0319c45147e87f4a021c56420ea6d22571af540505f86f8";
+const P384_S: &str = "1242827c7696eb6d4b78c43b8899d06cddd0aa9b46b0b3727307df906d8cac8343cff8a2a1931e1794a3c0fbe5af32d8";
+const P521_X: &str = concat!(
+    "01476f7477ee080f95e449f21dd21a9f88e3ddfee5993fa98e7b4cd8b6256ac3322fd26fd301e874a1b90b03afd0218a",
+    "8f3da4200dc55a87a285cf4537a2ba8fa39c",
+);
+const P521_Y: &str = concat!(
+    "002eda30c6e5b9101d24dfd06f0b96ff24eee6c082a369c4b5e6e6e220449a72e9c9868f89d67f825ca2ea5064ea7062",
+    "3eb7ef12a844efdf80de0bbaaf8f500dbcac",
+);
+const P521_R: &str = concat!(
+    "0072144f1a478557fe048fb7628f8d614fef24db1d9095104d84c54b8e64bfa39fe9fd2afe2920bfc0574b9b590265fe",
+    "c9a1ff00491218d3954b0fbf095a4e264b8d",
+);
+const P521_S: &str = concat!(
+    "001bbaf0fafce367a8a6f4cc319ce94f3226a893b202e05e8e9b200d8a8e63849bff8a6b550fc68ca847cead2151d707",
+    "2164f94838606252d570ea854e9357ae0835",
+);
+const QUOTE: &str = concat!(
+    "ff54434780180004000baabb0010ffffffffffffffffffffffffffffffff010203040506070800000003000000020100",
+    "0000000000202000000001000b0303000000205189c77d29fe5d546a045ec46986852785fea5c13ac7da9c115ff5fb6e",
+    "df817c",
+);
+const QUOTE_RSASSA_SIGNATURE: &str = concat!(
+    "274f650284eec7f147e3b757508f5fc0776f45eec4bcf5ccac893c8a31c11ade39870536e49082595e3510d0fa5649e4",
+    "c9ea016d2c5ab995b1500fe50c036e2817cc9f90228de311595d8865ca79b16499df59e5ea9e09f96c055930b2495677",
+    "5471ca080a666cdead8f8acee6e21678f58a4f794bab947fb2095d834790f5bb93d842cf7b6129e364a7f46f5a8a59e5",
+    "d17e4dfed99c3a4d3fcde8f8111896f37580536e2ed27d8e445c2bd9f9228e1c170dde8ad47831e6bb8ded7b7f396ffd",
+    "f0baf2608e5f708e2d4859cbd8b896f4e9856b409ef93d36bf81dc912a50dbe5d7540b9293d8ff42f3237505d6568370",
+    "e197ea046c575646f8b6f1008cee4aee",
+);
+const QUOTE_P256_R: &str = "8de6bca955ba3a9ec38488854e5d7af252dc8eb0d397f613fa6bca5609991963";
+const QUOTE_P256_S: &str = "8a011234976d749aa74045bc3cb6b1ccdbf50b048cfc36a46b71337ab3bfe1d6";
+
+fn hex(string: &str) -> Vec<u8> {
+    (0..string.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&string[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn rsa_signature(scheme: AsymSchemeUnion, signature: &str) -> Signature {
+    Signature {
+        scheme,
+        signature: SignatureData::RsaSignature(hex(signature)),
+    }
+}
+
+fn ecdsa_signature(hashing_algorithm: HashingAlgorithm, r: &str, s: &str) -> Signature {
+    Signature {
+        scheme: AsymSchemeUnion::ECDSA(hashing_algorithm),
+        signature: SignatureData::EcdsaSignature {
+            r: hex(r),
+            s: hex(s),
+        },
+    }
+}
+
+fn rsa_key(modulus: &str, exponent: u32) -> TPM2B_PUBLIC {
+    let modulus = hex(modulus);
+    let mut public = create_unrestricted_signing_rsa_public(
+        AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+        (modulus.len() * 8).try_into().unwrap(),
+        exponent,
+    )
+    .unwrap();
+    let mut unique = TPM2B_PUBLIC_KEY_RSA {
+        size: modulus.len().try_into().unwrap(),
+        ..Default::default()
+    };
+    unique.buffer[..modulus.len()].copy_from_slice(&modulus);
+    public.publicArea.unique.rsa = unique;
+    public
+}
+
+fn ecc_parameter(value: &str) -> TPM2B_ECC_PARAMETER {
+    let value = hex(value);
+    let mut parameter = TPM2B_ECC_PARAMETER {
+        size: value.len().try_into().unwrap(),
+        ..Default::default()
+    };
+    parameter.buffer[..value.len()].copy_from_slice(&value);
+    parameter
+}
+
+fn ecc_key(curve: EllipticCurve, x: &str, y: &str) -> TPM2B_PUBLIC {
+    let mut public = create_unrestricted_signing_ecc_public(
+        AsymSchemeUnion::ECDSA(HashingAlgorithm::Sha256),
+        curve,
+    )
+    .unwrap();
+    public.publicArea.unique.ecc = TPMS_ECC_POINT {
+        x: ecc_parameter(x),
+        y: ecc_parameter(y),
+    };
+    public
+}
+
+fn pcr_data() -> PcrData {
+    let mut pcr_data = PcrData::new();
+    pcr_data.insert(HashingAlgorithm::Sha256, PcrSlot::Slot0, vec![0x11; 32]);
+    pcr_data.insert(HashingAlgorithm::Sha256, PcrSlot::Slot1, vec![0x22; 32]);
+    pcr_data
+}
+
+mod test_verify_signature {
+    use super::*;
+
+    #[test]
+    fn test_rsassa() {
+        let public_key = rsa_key(RSA_MODULUS, 0);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSASSA_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!verify_signature(&public_key, b"Other message", &signature).unwrap());
+
+        let mut bytes = hex(RSASSA_SHA256_SIGNATURE);
+        bytes[10] ^= 0x01;
+        let signature = Signature {
+            scheme: AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            signature: SignatureData::RsaSignature(bytes),
+        };
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+
+        // The PSS signature does not verify as a PKCS#1 v1.5 one
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSAPSS_SHA256_SIGNATURE,
+        );
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_rsapss() {
+        let public_key = rsa_key(RSA_MODULUS, 0);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSAPSS(HashingAlgorithm::Sha256),
+            RSAPSS_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!verify_signature(&public_key, b"Other message", &signature).unwrap());
+
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSAPSS(HashingAlgorithm::Sha256),
+            RSASSA_SHA256_SIGNATURE,
+        );
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_rsa_exponent() {
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSA_EXPONENT_3_RSASSA_SHA256_SIGNATURE,
+        );
+        let public_key = rsa_key(RSA_EXPONENT_3_MODULUS, 3);
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+
+        // An exponent of 0 stands for 65537
+        let public_key = rsa_key(RSA_EXPONENT_3_MODULUS, 0);
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        let public_key = rsa_key(RSA_MODULUS, 65537);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSASSA_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_rsapss_max_salt() {
+        let public_key = rsa_key(RSA_MAX_SALT_MODULUS, 0);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSAPSS(HashingAlgorithm::Sha256),
+            RSA_MAX_SALT_RSAPSS_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!verify_signature(&public_key, b"Other message", &signature).unwrap());
+    }
+
+    #[test]
+    fn test_rsapss_modulus_bits_not_multiple_of_eight() {
+        let public_key = rsa_key(RSA_2049_MODULUS, 0);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSAPSS(HashingAlgorithm::Sha256),
+            RSA_2049_RSAPSS_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        assert!(!verify_signature(&public_key, b"Other message", &signature).unwrap());
+    }
+
+    #[test]
+    fn test_rsa_wrong_signature_size() {
+        let public_key = rsa_key(RSA_MODULUS, 0);
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            &RSASSA_SHA256_SIGNATURE[2..],
+        );
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_ecdsa() {
+        let curves = [
+            (
+                EllipticCurve::NistP256,
+                HashingAlgorithm::Sha256,
+                P256_X,
+                P256_Y,
+                P256_R,
+                P256_S,
+            ),
+            (
+                EllipticCurve::NistP384,
+                HashingAlgorithm::Sha384,
+                P384_X,
+                P384_Y,
+                P384_R,
+                P384_S,
+            ),
+            (
+                EllipticCurve::NistP521,
+                HashingAlgorithm::Sha512,
+                P521_X,
+                P521_Y,
+                P521_R,
+                P521_S,
+            ),
+        ];
+        for (curve, hashing_algorithm, x, y, r, s) in curves.iter() {
+            let public_key = ecc_key(*curve, x, y);
+            let signature = ecdsa_signature(*hashing_algorithm, r, s);
+            assert!(verify_signature(&public_key, MESSAGE, &signature).unwrap());
+            assert!(!verify_signature(&public_key, b"Other message", &signature).unwrap());
+
+            // Swapping r and s invalidates the signature
+            let signature = ecdsa_signature(*hashing_algorithm, s, r);
+            assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_out_of_range() {
+        let public_key = ecc_key(EllipticCurve::NistP256, P256_X, P256_Y);
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, "00", P256_S);
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, &"ff".repeat(32));
+        assert!(!verify_signature(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_point_not_on_curve() {
+        let public_key = ecc_key(EllipticCurve::NistP256, P256_X, P256_X);
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, P256_S);
+        assert_eq!(
+            verify_signature(&public_key, MESSAGE, &signature).unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::InvalidParam)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_curve() {
+        let mut public_key = ecc_key(EllipticCurve::NistP256, P256_X, P256_Y);
+        let mut parameters = unsafe { public_key.publicArea.parameters.eccDetail };
+        parameters.curveID = EllipticCurve::BnP256.into();
+        public_key.publicArea.parameters.eccDetail = parameters;
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, P256_S);
+        assert_eq!(
+            verify_signature(&public_key, MESSAGE, &signature).unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_scheme() {
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSASSA_SHA256_SIGNATURE,
+        );
+        assert_eq!(
+            verify_signature(
+                &ecc_key(EllipticCurve::NistP256, P256_X, P256_Y),
+                MESSAGE,
+                &signature
+            )
+            .unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::InconsistentParams)
+        );
+
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, P256_S);
+        assert_eq!(
+            verify_signature(&rsa_key(RSA_MODULUS, 0), MESSAGE, &signature).unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::InconsistentParams)
+        );
+    }
+
+    #[test]
+    fn test_unsupported_scheme() {
+        let signature = Signature {
+            scheme: AsymSchemeUnion::SM2(HashingAlgorithm::Sha256),
+            signature: SignatureData::EcdsaSignature {
+                r: hex(P256_R),
+                s: hex(P256_S),
+            },
+        };
+        assert_eq!(
+            verify_signature(
+                &ecc_key(EllipticCurve::NistP256, P256_X, P256_Y),
+                MESSAGE,
+                &signature
+            )
+            .unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+        );
+    }
+
+    #[test]
+    fn test_rsa_public_key() {
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSASSA_SHA256_SIGNATURE,
+        );
+        let public_key = PublicKey::Rsa(hex(RSA_MODULUS));
+        assert!(verify_signature_with_public_key(&public_key, MESSAGE, &signature).unwrap());
+        assert!(
+            !verify_signature_with_public_key(&public_key, b"Other message", &signature).unwrap()
+        );
+
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSAPSS(HashingAlgorithm::Sha256),
+            RSAPSS_SHA256_SIGNATURE,
+        );
+        assert!(verify_signature_with_public_key(&public_key, MESSAGE, &signature).unwrap());
+
+        // The exponent of the key is taken to be 65537
+        let signature = rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            RSA_EXPONENT_3_RSASSA_SHA256_SIGNATURE,
+        );
+        let public_key = PublicKey::Rsa(hex(RSA_EXPONENT_3_MODULUS));
+        assert!(!verify_signature_with_public_key(&public_key, MESSAGE, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_ecc_public_key() {
+        let curves = [
+            (HashingAlgorithm::Sha256, P256_X, P256_Y, P256_R, P256_S),
+            (HashingAlgorithm::Sha384, P384_X, P384_Y, P384_R, P384_S),
+            (HashingAlgorithm::Sha512, P521_X, P521_Y, P521_R, P521_S),
+        ];
+        for (hashing_algorithm, x, y, r, s) in curves.iter() {
+            let public_key = PublicKey::Ecc {
+                x: hex(x),
+                y: hex(y),
+            };
+            let signature = ecdsa_signature(*hashing_algorithm, r, s);
+            assert!(verify_signature_with_public_key(&public_key, MESSAGE, &signature).unwrap());
+            assert!(
+                !verify_signature_with_public_key(&public_key, b"Other message", &signature)
+                    .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_ecc_public_key_unknown_size() {
+        let public_key = PublicKey::Ecc {
+            x: hex(&P256_X[..40]),
+            y: hex(&P256_Y[..40]),
+        };
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, P256_S);
+        assert_eq!(
+            verify_signature_with_public_key(&public_key, MESSAGE, &signature).unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::UnsupportedParam)
+        );
+    }
+
+    #[test]
+    fn test_public_key_inconsistent_scheme() {
+        let signature = ecdsa_signature(HashingAlgorithm::Sha256, P256_R, P256_S);
+        assert_eq!(
+            verify_signature_with_public_key(
+                &PublicKey::Rsa(hex(RSA_MODULUS)),
+                MESSAGE,
+                &signature
+            )
+            .unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::InconsistentParams)
+        );
+    }
+}
+
+mod test_verify_quote {
+    use super::*;
+
+    fn rsassa_quote_signature() -> Signature {
+        rsa_signature(
+            AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+            QUOTE_RSASSA_SIGNATURE,
+        )
+    }
+
+    #[test]
+    fn test_valid_quote() {
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &hex(QUOTE),
+            &rsassa_quote_signature(),
+            &[0xff; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert!(verification.is_valid());
+        assert!(verification.failures().is_empty());
+        assert_eq!(verification.attest().extra_data(), &[0xff; 16]);
+
+        let verification = verify_quote(
+            &ecc_key(EllipticCurve::NistP256, P256_X, P256_Y),
+            &hex(QUOTE),
+            &ecdsa_signature(HashingAlgorithm::Sha256, QUOTE_P256_R, QUOTE_P256_S),
+            &[0xff; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert!(verification.is_valid());
+    }
+
+    #[test]
+    fn test_valid_quote_with_public_key() {
+        let verification = verify_quote_with_public_key(
+            &PublicKey::Rsa(hex(RSA_MODULUS)),
+            &hex(QUOTE),
+            &rsassa_quote_signature(),
+            &[0xff; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert!(verification.is_valid());
+
+        let verification = verify_quote_with_public_key(
+            &PublicKey::Ecc {
+                x: hex(P256_X),
+                y: hex(P256_Y),
+            },
+            &hex(QUOTE),
+            &ecdsa_signature(HashingAlgorithm::Sha256, QUOTE_P256_R, QUOTE_P256_S),
+            &[0xee; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert_eq!(
+            verification.failures(),
+            &[QuoteVerificationFailure::NonceMismatch {
+                expected: vec![0xee; 16],
+                quoted: vec![0xff; 16],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &hex(QUOTE),
+            &rsa_signature(
+                AsymSchemeUnion::RSASSA(HashingAlgorithm::Sha256),
+                RSASSA_SHA256_SIGNATURE,
+            ),
+            &[0xff; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert!(!verification.is_valid());
+        assert_eq!(
+            verification.failures(),
+            &[QuoteVerificationFailure::InvalidSignature]
+        );
+    }
+
+    #[test]
+    fn test_nonce_mismatch() {
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &hex(QUOTE),
+            &rsassa_quote_signature(),
+            &[0xee; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert_eq!(
+            verification.failures(),
+            &[QuoteVerificationFailure::NonceMismatch {
+                expected: vec![0xee; 16],
+                quoted: vec![0xff; 16],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pcr_digest_mismatch() {
+        let mut pcr_data = pcr_data();
+        pcr_data.insert(HashingAlgorithm::Sha256, PcrSlot::Slot1, vec![0x33; 32]);
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &hex(QUOTE),
+            &rsassa_quote_signature(),
+            &[0xff; 16],
+            &pcr_data,
+        )
+        .unwrap();
+        match verification.failures() {
+            [QuoteVerificationFailure::PcrDigestMismatch { expected, quoted }] => {
+                assert_eq!(quoted, &hex(&QUOTE[QUOTE.len() - 64..]));
+                assert_ne!(expected, quoted);
+            }
+            failures => panic!("Unexpected failures: {:?}", failures),
+        }
+    }
+
+    #[test]
+    fn test_missing_pcr_value() {
+        let mut pcr_data = PcrData::new();
+        pcr_data.insert(HashingAlgorithm::Sha256, PcrSlot::Slot0, vec![0x11; 32]);
+        pcr_data.insert(HashingAlgorithm::Sha1, PcrSlot::Slot1, vec![0x22; 20]);
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &hex(QUOTE),
+            &rsassa_quote_signature(),
+            &[0xff; 16],
+            &pcr_data,
+        )
+        .unwrap();
+        assert_eq!(
+            verification.failures(),
+            &[QuoteVerificationFailure::MissingPcrValue {
+                hashing_algorithm: HashingAlgorithm::Sha256,
+                pcr_slot: PcrSlot::Slot1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_not_a_quote() {
+        // Change the attestation type to TPM2_ST_ATTEST_TIME, which has a different body
+        let mut attest = hex(QUOTE);
+        attest[5] = 0x19;
+        attest.truncate(attest.len() - 44);
+        attest.extend_from_slice(&[0; 33]);
+        let verification = verify_quote(
+            &rsa_key(RSA_MODULUS, 0),
+            &attest,
+            &rsassa_quote_signature(),
+            &[0xff; 16],
+            &pcr_data(),
+        )
+        .unwrap();
+        assert_eq!(
+            verification.failures(),
+            &[
+                QuoteVerificationFailure::InvalidSignature,
+                QuoteVerificationFailure::NotAQuote(StructureTag::AttestTime)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_attest() {
+        assert_eq!(
+            verify_quote(
+                &rsa_key(RSA_MODULUS, 0),
+                &hex(QUOTE)[..20],
+                &rsassa_quote_signature(),
+                &[0xff; 16],
+                &pcr_data(),
+            )
+            .unwrap_err(),
+            Error::WrapperError(WrapperErrorKind::InvalidParam)
+        );
+    }
+}